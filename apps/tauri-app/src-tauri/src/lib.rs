@@ -4,15 +4,47 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+/// 每本书的显示选项
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+struct BookDisplayOptions {
+    /// 简繁显示方式（如 "zh-Hans"、"zh-TW"），为空时按原文显示
+    #[serde(default)]
+    chinese_variant: Option<String>,
+}
+
+/// 按显示选项转换文档中的简繁字形
+///
+/// 原文的变体未知，这里按原文为简体处理；目标为简体时则从繁体转换
+fn apply_chinese_variant(
+    document: typesetting_engine::DocumentModel,
+    chinese_variant: Option<&str>,
+) -> Result<typesetting_engine::DocumentModel, String> {
+    use typesetting_engine::{ChineseConverter, ChineseVariant};
+
+    let target = match chinese_variant {
+        None | Some("") => return Ok(document),
+        Some(tag) => ChineseVariant::from_tag(tag)
+            .ok_or_else(|| format!("不支持的简繁显示方式: {}", tag))?,
+    };
+    let source = if target == ChineseVariant::Simplified {
+        ChineseVariant::Traditional
+    } else {
+        ChineseVariant::Simplified
+    };
+
+    Ok(ChineseConverter::new(source, target).convert_document(&document))
+}
+
 #[tauri::command]
-async fn parse_document_chapters(content: String) -> Result<Vec<String>, String> {
+async fn parse_document_chapters(content: String, chinese_variant: Option<String>) -> Result<Vec<String>, String> {
     // 异步解析文档
     let document = typesetting_engine::parse_document_async(content).await;
+    let document = apply_chinese_variant(document, chinese_variant.as_deref())?;
     
-    // 提取章节标题
+    // 提取章节标题，统一章节序号的写法
     let chapter_titles: Vec<String> = document.chapters
         .iter()
-        .map(|chapter| chapter.title.to_string())
+        .map(|chapter| chapter.normalized_title().into_owned())
         .collect();
     
     Ok(chapter_titles)
@@ -86,6 +118,8 @@ async fn typeset_document(content: String) -> Result<String, String> {
 }
 
 /// 为Tauri应用实现的渲染函数
+///
+/// 文本按引擎计算的行框逐行输出，分行和分页都与引擎一致
 fn render_pages_for_tauri(pages: &[typesetting_engine::Page]) -> String {
     let mut result = String::new();
     for (i, page) in pages.iter().enumerate() {
@@ -93,13 +127,25 @@ fn render_pages_for_tauri(pages: &[typesetting_engine::Page]) -> String {
         result.push_str("--- Page Start ---\n");
         
         // 遍历页面中的所有内容块
-        for block in &page.blocks {
+        for (index, block) in page.blocks.iter().enumerate() {
+            let lines: Vec<String> = page
+                .block_lines(index)
+                .map(|line| {
+                    let text = page.line_text(line).trim_end();
+                    if line.hyphenated { format!("{}-", text) } else { text.to_string() }
+                })
+                .collect();
+            let text = if lines.is_empty() { block.content.to_string() } else { lines.join("\n") };
+            let continuation = page.continuations.get(index).copied().unwrap_or_default();
+            
             match block.block_type {
                 typesetting_engine::ContentBlockType::Text => {
-                    result.push_str(&format!("Text: {}\n", block.content));
+                    // 接续上一页的段落片段不是新段落，不加首行缩进
+                    let indent = if continuation.from_previous { "" } else { "\u{3000}\u{3000}" };
+                    result.push_str(&format!("Text: {}{}\n", indent, text));
                 }
                 typesetting_engine::ContentBlockType::Title => {
-                    result.push_str(&format!("Title: {}\n", block.content));
+                    result.push_str(&format!("Title: {}\n", text));
                 }
                 typesetting_engine::ContentBlockType::Image => {
                     result.push_str(&format!("Image: {}\n", block.content));
@@ -107,6 +153,22 @@ fn render_pages_for_tauri(pages: &[typesetting_engine::Page]) -> String {
                 typesetting_engine::ContentBlockType::Blank => {
                     result.push('\n');
                 }
+                typesetting_engine::ContentBlockType::Verse => {
+                    // 诗词逐行缩进显示
+                    result.push_str("Verse:\n");
+                    for line in &lines {
+                        result.push_str(&format!("    {}\n", line));
+                    }
+                }
+                typesetting_engine::ContentBlockType::SceneBreak => {
+                    result.push_str(&format!("\n{}\n\n", text));
+                }
+                typesetting_engine::ContentBlockType::Footnote { ref anchor } => {
+                    result.push_str(&format!("Footnote [{}]: {}\n", anchor, text));
+                }
+                typesetting_engine::ContentBlockType::AuthorNote => {
+                    result.push_str(&format!("Author note: {}\n", text));
+                }
             }
         }
         
@@ -117,7 +179,7 @@ fn render_pages_for_tauri(pages: &[typesetting_engine::Page]) -> String {
 
 // 新增：获取文档的章节和页码映射关系
 #[tauri::command]
-fn get_document_chapter_page_mapping(content: &str) -> Result<Vec<(String, usize)>, String> {
+fn get_document_chapter_page_mapping(content: &str, chinese_variant: Option<String>) -> Result<Vec<(String, usize)>, String> {
     use typesetting_engine::{ParserEngine, LayoutEngine, PageConfig};
     
     // 创建解析引擎
@@ -125,6 +187,7 @@ fn get_document_chapter_page_mapping(content: &str) -> Result<Vec<(String, usize
     
     // 解析文档
     let document = parser.parse_txt(content);
+    let document = apply_chinese_variant(document, chinese_variant.as_deref())?;
     
     // 创建布局引擎
     let page_config = PageConfig {
@@ -155,14 +218,74 @@ fn get_document_chapter_page_mapping(content: &str) -> Result<Vec<(String, usize
 
 // 新增：懒加载特定章节并返回起始页码和总页数
 #[tauri::command]
-async fn load_chapter_content_with_offset(content: String, chapter_index: usize) -> Result<(String, usize, usize), String> {
-    use typesetting_engine::{ParserEngine, LayoutEngine, PageConfig, Page};
+async fn load_chapter_content_with_offset(content: String, chapter_index: usize, chinese_variant: Option<String>) -> Result<(String, usize, usize), String> {
+    let (chapter_pages, start_page_index, total_page_count) =
+        layout_chapter_with_offset(&content, chapter_index, chinese_variant.as_deref())?;
+    
+    // 渲染页面
+    let rendered = render_pages_for_tauri(&chapter_pages);
+    
+    Ok((rendered, start_page_index, total_page_count))
+}
+
+/// 章节的排版结果
+#[derive(Debug, Clone, serde::Serialize)]
+struct ChapterPages {
+    /// 章节的各页，包含引擎计算的行框和跨页段落的接续标记
+    pages: Vec<typesetting_engine::Page>,
+    /// 章节起始页码
+    start_page: usize,
+    /// 文档总页数
+    total_pages: usize,
+    /// 页面宽度（像素），行框坐标以页面左上角为原点
+    page_width: f32,
+    /// 页面高度（像素）
+    page_height: f32,
+}
+
+/// 懒加载特定章节，返回带行框的页面，前端按行框绘制即可与引擎分页一致
+#[tauri::command]
+async fn load_chapter_pages(content: String, chapter_index: usize, chinese_variant: Option<String>) -> Result<ChapterPages, String> {
+    let page_config = reader_page_config();
+    let (pages, start_page, total_pages) = layout_chapter_with_offset(&content, chapter_index, chinese_variant.as_deref())?;
+    
+    Ok(ChapterPages {
+        pages,
+        start_page,
+        total_pages,
+        page_width: page_config.width,
+        page_height: page_config.height,
+    })
+}
+
+/// 阅读器使用的页面配置
+fn reader_page_config() -> typesetting_engine::PageConfig {
+    typesetting_engine::PageConfig {
+        width: 800.0,
+        height: 1000.0,
+        margin_top: 40.0,
+        margin_bottom: 40.0,
+        margin_left: 40.0,
+        margin_right: 40.0,
+    }
+}
+
+/// 排版文档并取出特定章节的页面
+///
+/// 返回章节的页面、章节起始页码和文档总页数
+fn layout_chapter_with_offset(
+    content: &str,
+    chapter_index: usize,
+    chinese_variant: Option<&str>,
+) -> Result<(Vec<typesetting_engine::Page>, usize, usize), String> {
+    use typesetting_engine::{ParserEngine, LayoutEngine, Page};
     
     // 创建解析引擎
     let parser = ParserEngine::new();
     
     // 解析文档
-    let document = parser.parse_txt(&content);
+    let document = parser.parse_txt(content);
+    let document = apply_chinese_variant(document, chinese_variant)?;
     
     // 检查章节索引是否有效
     if chapter_index >= document.chapters.len() {
@@ -170,19 +293,12 @@ async fn load_chapter_content_with_offset(content: String, chapter_index: usize)
     }
     
     // 创建布局引擎
-    let page_config = PageConfig {
-        width: 800.0,
-        height: 1000.0,
-        margin_top: 40.0,
-        margin_bottom: 40.0,
-        margin_left: 40.0,
-        margin_right: 40.0,
-    };
-    let layout_engine = LayoutEngine::new(page_config.clone());
+    let layout_engine = LayoutEngine::new(reader_page_config());
     
     // 计算章节起始页码和总页数
     let mut start_page_index = 0;
     let mut total_page_count = 0;
+    let mut chapter_pages: Vec<Page> = Vec::new();
     
     // 逐个章节计算页数
     for (index, chapter) in document.chapters.iter().enumerate() {
@@ -196,22 +312,19 @@ async fn load_chapter_content_with_offset(content: String, chapter_index: usize)
         // 布局当前章节
         let pages: Vec<Page> = layout_engine.layout_document(&single_chapter_document);
         
-        // 如果是目标章节，记录起始页码
+        // 更新总页数
+        let page_count = pages.len();
+        
+        // 如果是目标章节，记录起始页码并保留其页面（已按显示选项转换）
         if index == chapter_index {
             start_page_index = total_page_count;
+            chapter_pages = pages;
         }
         
-        // 更新总页数
-        total_page_count += pages.len();
+        total_page_count += page_count;
     }
     
-    // 使用新的按需加载功能获取章节内容（异步）
-    let pages = typesetting_engine::layout_chapter_on_demand_async(content, chapter_index, page_config).await;
-    
-    // 渲染页面
-    let rendered = render_pages_for_tauri(&pages);
-    
-    Ok((rendered, start_page_index, total_page_count))
+    Ok((chapter_pages, start_page_index, total_page_count))
 }
 
 /// 初始化应用数据目录结构
@@ -232,6 +345,7 @@ fn initialize_app_directories(app_handle: &tauri::AppHandle) -> Result<(), Strin
         "cache/metadata",   // 缓存的元数据
         "logs",          // 日志文件
         "settings",      // 用户设置
+        "settings/books",   // 每本书的显示选项
         "downloads",     // 下载的书籍
         "downloads/temp",   // 下载临时文件
         "downloads/completed", // 已完成的下载
@@ -407,6 +521,181 @@ fn list_documents(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
     Ok(files)
 }
 
+/// 支持的文档格式信息
+#[derive(Debug, Clone, serde::Serialize)]
+struct SupportedFormatInfo {
+    /// 解析器名称
+    name: String,
+    /// 文件扩展名
+    extension: String,
+    /// MIME类型列表
+    mime_types: Vec<String>,
+}
+
+#[tauri::command]
+fn list_supported_formats() -> Vec<SupportedFormatInfo> {
+    use typesetting_engine::FileLoader;
+    
+    // 从排版引擎的解析器注册表中列出支持的格式
+    FileLoader::new()
+        .registry()
+        .supported_formats()
+        .into_iter()
+        .map(|format| SupportedFormatInfo {
+            name: format.name,
+            extension: format.format.extension().to_string(),
+            mime_types: format.mime_types,
+        })
+        .collect()
+}
+
+/// 语言片段信息
+#[derive(Debug, Clone, serde::Serialize)]
+struct LanguageRunInfo {
+    /// 片段文本
+    text: String,
+    /// 语言标签
+    language: String,
+}
+
+#[tauri::command]
+async fn detect_document_language(content: String) -> Result<Option<String>, String> {
+    // 解析时已识别文档语言，供断行、字体回退等按语言处理
+    let document = typesetting_engine::parse_document_async(content).await;
+    Ok(document.metadata.language.map(|language| language.into_owned()))
+}
+
+#[tauri::command]
+fn split_language_runs(text: &str, default_language: Option<String>) -> Vec<LanguageRunInfo> {
+    typesetting_engine::language_runs(text, default_language.as_deref())
+        .into_iter()
+        .map(|run| LanguageRunInfo {
+            text: text[run.byte_range].to_string(),
+            language: run.language.into_owned(),
+        })
+        .collect()
+}
+
+/// 句子边界信息
+#[derive(Debug, Clone, serde::Serialize)]
+struct SentenceInfo {
+    /// 句子文本
+    text: String,
+    /// 起始字符位置
+    char_start: usize,
+    /// 结束字符位置（不包含）
+    char_end: usize,
+    /// 起始字节位置
+    byte_start: usize,
+    /// 结束字节位置（不包含）
+    byte_end: usize,
+}
+
+#[tauri::command]
+fn segment_sentences(text: &str) -> Vec<SentenceInfo> {
+    use typesetting_engine::SentenceSegmenter;
+    
+    // 供朗读高亮、复制句子和逐句批注使用
+    SentenceSegmenter::new()
+        .segment(text)
+        .into_iter()
+        .map(|sentence| SentenceInfo {
+            text: sentence.text(text).to_string(),
+            char_start: sentence.char_range.start,
+            char_end: sentence.char_range.end,
+            byte_start: sentence.byte_range.start,
+            byte_end: sentence.byte_range.end,
+        })
+        .collect()
+}
+
+/// 分词结果
+#[derive(Debug, Clone, serde::Serialize)]
+struct WordInfo {
+    /// 词的文本
+    text: String,
+    /// 是否为可选中的词（汉字词或字母数字词）
+    selectable: bool,
+    /// 起始字符位置
+    char_start: usize,
+    /// 结束字符位置（不包含）
+    char_end: usize,
+}
+
+#[tauri::command]
+fn segment_words(text: &str, user_dictionary: Option<String>) -> Vec<WordInfo> {
+    use typesetting_engine::{WordKind, WordSegmenter};
+    
+    // 用户词典用于补充每本书的人名、地名
+    let segmenter = match user_dictionary.as_deref() {
+        Some(dictionary) => WordSegmenter::new().with_user_dictionary(dictionary),
+        None => WordSegmenter::new(),
+    };
+    
+    segmenter
+        .segment(text)
+        .into_iter()
+        .map(|word| WordInfo {
+            text: word.text(text).to_string(),
+            selectable: matches!(word.kind, WordKind::Cjk | WordKind::Alphanumeric),
+            char_start: word.char_range.start,
+            char_end: word.char_range.end,
+        })
+        .collect()
+}
+
+/// 获取某本书显示选项的保存路径
+///
+/// 文件名只能是单独的一段路径，含有路径分隔符或 `..` 时返回错误，避免写到设置目录之外
+fn book_display_options_path(app_handle: &tauri::AppHandle, filename: &str) -> Result<std::path::PathBuf, String> {
+    use tauri::Manager;
+
+    if filename.is_empty() || filename.contains(['/', '\\', ':', '\0']) || filename.contains("..") {
+        return Err(format!("无效的文件名: {}", filename));
+    }
+
+    let app_dir = app_handle.path().app_data_dir()
+        .map_err(|e| format!("无法获取应用数据目录: {}", e))?;
+    
+    Ok(app_dir.join("settings").join("books").join(format!("{}.json", filename)))
+}
+
+#[tauri::command]
+fn get_book_display_options(app_handle: tauri::AppHandle, filename: &str) -> Result<BookDisplayOptions, String> {
+    use std::fs;
+    
+    let options_path = book_display_options_path(&app_handle, filename)?;
+    
+    // 尚未设置过显示选项时使用默认值
+    if !options_path.exists() {
+        return Ok(BookDisplayOptions::default());
+    }
+    
+    let json = fs::read_to_string(&options_path)
+        .map_err(|e| format!("无法读取显示选项: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("显示选项格式错误: {}", e))
+}
+
+#[tauri::command]
+fn set_book_display_options(app_handle: tauri::AppHandle, filename: &str, options: BookDisplayOptions) -> Result<(), String> {
+    use std::fs;
+    
+    // 提前校验简繁显示方式，避免保存无法使用的选项
+    if let Some(tag) = options.chinese_variant.as_deref().filter(|tag| !tag.is_empty()) {
+        if typesetting_engine::ChineseVariant::from_tag(tag).is_none() {
+            return Err(format!("不支持的简繁显示方式: {}", tag));
+        }
+    }
+    
+    // 初始化应用目录结构
+    initialize_app_directories(&app_handle)?;
+    
+    let options_path = book_display_options_path(&app_handle, filename)?;
+    let json = serde_json::to_string_pretty(&options)
+        .map_err(|e| format!("无法序列化显示选项: {}", e))?;
+    fs::write(&options_path, json).map_err(|e| format!("无法保存显示选项: {}", e))
+}
+
 #[tauri::command]
 fn get_app_data_structure(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
     use std::fs;
@@ -468,7 +757,15 @@ pub fn run() {
             list_documents,
             get_app_data_structure,
             get_document_chapter_page_mapping,
-            load_chapter_content_with_offset
+            load_chapter_content_with_offset,
+            load_chapter_pages,
+            get_book_display_options,
+            set_book_display_options,
+            list_supported_formats,
+            segment_sentences,
+            segment_words,
+            detect_document_language,
+            split_language_runs
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
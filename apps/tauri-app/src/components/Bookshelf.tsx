@@ -5,6 +5,13 @@ import { open } from '@tauri-apps/plugin-dialog';
 import { readTextFile } from '@tauri-apps/plugin-fs';
 import BookCover from "./BookCover";
 
+// 排版引擎支持的文件格式
+interface SupportedFormat {
+  name: string;
+  extension: string;
+  mime_types: string[];
+}
+
 const Bookshelf: React.FC = () => {
   const [documents, setDocuments] = useState<string[]>([]);
   const [errorMessage, setErrorMessage] = useState("");
@@ -33,12 +40,15 @@ const Bookshelf: React.FC = () => {
     try {
       setErrorMessage(""); // 清除之前的错误信息
       
+      // 从排版引擎获取支持的文件格式
+      const formats = await invoke<SupportedFormat[]>("list_supported_formats");
+      
       const selected = await open({
         multiple: false,
-        filters: [{
-          name: "Text Files",
-          extensions: ["txt"]
-        }]
+        filters: formats.map(format => ({
+          name: format.name,
+          extensions: [format.extension]
+        }))
       });
 
       if (selected) {
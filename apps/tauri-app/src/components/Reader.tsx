@@ -2,9 +2,85 @@ import React, { useState, useEffect, useRef } from "react";
 import { useParams, useNavigate } from "react-router-dom";
 import { invoke } from "@tauri-apps/api/core";
 
+// 排版引擎输出的页面结构，字段与 typesetting_engine::Page 一致
+interface GlyphAdjustment {
+  offset: number;
+  spacing: number;
+}
+
+interface LineBox {
+  block_index: number;
+  byte_range: { start: number; end: number };
+  x: number;
+  y: number;
+  baseline: number;
+  width: number;
+  height: number;
+  hyphenated: boolean;
+  adjustments: GlyphAdjustment[];
+}
+
+interface Continuation {
+  from_previous: boolean;
+  to_next: boolean;
+}
+
+interface LayoutBlock {
+  content: string;
+  styles: {
+    font_size: number;
+    font_family: string;
+    bold: boolean;
+    italic: boolean;
+  };
+}
+
+interface LayoutPage {
+  blocks: LayoutBlock[];
+  continuations: Continuation[];
+  lines: LineBox[];
+  used_height: number;
+}
+
+// load_chapter_pages 的返回值
+interface ChapterPages {
+  pages: LayoutPage[];
+  start_page: number;
+  total_pages: number;
+  page_width: number;
+  page_height: number;
+}
+
 interface PageContent {
   pageNumber: number;
-  content: string;
+  page: LayoutPage;
+}
+
+const textEncoder = new TextEncoder();
+const textDecoder = new TextDecoder();
+
+/**
+ * 按行框的字节范围切出一行文本，并在两端对齐的字形间距处分段
+ * @param content - 内容块文本
+ * @param line - 行框
+ * @returns 依次排列的文本片段和片段前插入的间距（像素）
+ */
+function lineSegments(content: string, line: LineBox): [string, number][] {
+  const bytes = textEncoder.encode(content);
+  const { start, end } = line.byte_range;
+  const adjustments = line.adjustments
+    .filter(adjustment => adjustment.offset > start && adjustment.offset < end)
+    .sort((a, b) => a.offset - b.offset);
+  const segments: [string, number][] = [];
+  let from = start;
+  let spacing = 0;
+  for (const adjustment of adjustments) {
+    segments.push([textDecoder.decode(bytes.subarray(from, adjustment.offset)), spacing]);
+    from = adjustment.offset;
+    spacing = adjustment.spacing;
+  }
+  segments.push([textDecoder.decode(bytes.subarray(from, end)), spacing]);
+  return segments;
 }
 
 // 新增：章节信息接口
@@ -13,6 +89,20 @@ interface ChapterInfo {
   startPage: number;
 }
 
+// 每本书的显示选项
+interface BookDisplayOptions {
+  chinese_variant: string | null;
+}
+
+// 简繁显示方式
+const CHINESE_VARIANTS: [string, string][] = [
+  ["", "原文"],
+  ["zh-Hans", "简体"],
+  ["zh-Hant", "繁体"],
+  ["zh-TW", "繁体（台湾）"],
+  ["zh-HK", "繁体（香港）"],
+];
+
 const Reader: React.FC = () => {
   const { filename } = useParams<{ filename: string }>();
   const navigate = useNavigate();
@@ -22,11 +112,13 @@ const Reader: React.FC = () => {
   const [currentChapter, setCurrentChapter] = useState(0); // 当前章节索引
   const [currentPage, setCurrentPage] = useState(0);
   const [totalPages, setTotalPages] = useState(0); // 整个文档的总页数
+  const [pageSize, setPageSize] = useState({ width: 800, height: 1000 }); // 排版引擎使用的页面尺寸
   const [loading, setLoading] = useState(true);
   const [loadingChapter, setLoadingChapter] = useState(false); // 专门用于章节加载的状态
   const [error, setError] = useState("");
   const [fontSize, setFontSize] = useState(16);
   const [theme, setTheme] = useState("light");
+  const [chineseVariant, setChineseVariant] = useState(""); // 简繁显示方式，空字符串表示原文
   const contentRef = useRef<HTMLDivElement>(null);
 
   // 加载文档内容
@@ -46,8 +138,16 @@ const Reader: React.FC = () => {
       const content = await invoke<string>("load_document", { filename });
       setRawContent(content);
       
+      // 读取这本书的显示选项
+      const options = await invoke<BookDisplayOptions>("get_book_display_options", { filename });
+      const variant = options.chinese_variant || "";
+      setChineseVariant(variant);
+      
       // 获取章节信息和页码映射
-      const chapterList: string[] = await invoke<string[]>("parse_document_chapters", { content });
+      const chapterList: string[] = await invoke<string[]>("parse_document_chapters", {
+        content,
+        chineseVariant: variant || null
+      });
       
       // 初始化章节信息数组
       const chapterInfoList: ChapterInfo[] = chapterList.map((title, index) => ({
@@ -58,7 +158,10 @@ const Reader: React.FC = () => {
       setChapters(chapterInfoList);
       
       // 获取章节与页码的映射关系
-      const chapterPageMapping: [string, number][] = await invoke<[string, number][]>("get_document_chapter_page_mapping", { content });
+      const chapterPageMapping: [string, number][] = await invoke<[string, number][]>("get_document_chapter_page_mapping", {
+        content,
+        chineseVariant: variant || null
+      });
       console.log("章节页码映射:", chapterPageMapping);
       
       // 更新章节信息
@@ -71,7 +174,7 @@ const Reader: React.FC = () => {
       
       // 只加载第一章内容作为初始内容
       if (chapterInfoList.length > 0) {
-        await loadChapterContent(content, 0);
+        await loadChapterContent(content, 0, variant);
       }
       
       setError("");
@@ -86,25 +189,25 @@ const Reader: React.FC = () => {
    * 加载特定章节内容
    * @param content - 文档内容
    * @param chapterIndex - 章节索引
+   * @param variant - 简繁显示方式，默认使用当前设置
    */
-  async function loadChapterContent(content: string, chapterIndex: number) {
+  async function loadChapterContent(content: string, chapterIndex: number, variant: string = chineseVariant) {
     try {
       console.log("=== 开始加载章节内容 ===");
       console.log("章节索引:", chapterIndex);
       setLoadingChapter(true);
-      const result = await invoke<[string, number, number]>("load_chapter_content_with_offset", { 
+      const result = await invoke<ChapterPages>("load_chapter_pages", { 
         content, 
-        chapterIndex 
+        chapterIndex,
+        chineseVariant: variant || null
       });
       
-      const [typesetResult, startPage, totalPageCount] = result;
-      console.log("排版结果:", typesetResult);
-      console.log("章节起始页码:", startPage);
-      console.log("文档总页数:", totalPageCount);
+      console.log("章节起始页码:", result.start_page);
+      console.log("文档总页数:", result.total_pages);
       
-      processTypesetResult(typesetResult, startPage, totalPageCount);
+      processChapterPages(result);
       setCurrentChapter(chapterIndex);
-      setCurrentPage(startPage); // 设置当前页为章节起始页
+      setCurrentPage(result.start_page); // 设置当前页为章节起始页
       setError("");
       console.log("=== 章节内容加载完成 ===");
     } catch (err) {
@@ -117,42 +220,71 @@ const Reader: React.FC = () => {
 
   /**
    * 处理排版结果
-   * @param result - 排版引擎返回的结果
-   * @param startPage - 章节起始页码
-   * @param totalPageCount - 文档总页数
+   * @param result - 排版引擎返回的章节页面
    */
-  function processTypesetResult(result: string, startPage: number, totalPageCount: number) {
-    console.log("=== 开始处理排版结果 ===");
-    console.log("章节起始页码:", startPage);
-    console.log("文档总页数:", totalPageCount);
-    
-    // 解析排版引擎返回的结果
-    const pageSections = result.split('\n\n').filter(section => section.trim() !== '');
-    
-    const processedPages: PageContent[] = [];
-    
-    for (const section of pageSections) {
-      if (section.startsWith('Page ')) {
-        // 提取页码和内容
-        const lines = section.split('\n');
-        const contentLines = lines.slice(2, -1); // 跳过页码行和开始标记行，去掉结束标记行
-        const content = contentLines.join('\n');
-        
-        // 从排版结果中提取页码并加上起始页码偏移
-        const pageNumberMatch = section.match(/^Page (\d+)/);
-        const pageNumber = pageNumberMatch ? parseInt(pageNumberMatch[1]) : 1;
-        
-        processedPages.push({
-          pageNumber: startPage + pageNumber - 1, // 调整为绝对页码
-          content: content
-        });
-      }
-    }
+  function processChapterPages(result: ChapterPages) {
+    // 页面保持引擎的分页，页码加上章节起始页码成为绝对页码
+    const processedPages: PageContent[] = result.pages.map((page, index) => ({
+      pageNumber: result.start_page + index,
+      page
+    }));
     
-    console.log("处理后的页面:", processedPages);
     setPages(processedPages);
-    setTotalPages(totalPageCount); // 更新总页数
-    console.log("=== 排版结果处理完成 ===");
+    setPageSize({ width: result.page_width, height: result.page_height });
+    setTotalPages(result.total_pages); // 更新总页数
+  }
+
+  /**
+   * 按引擎计算的行框绘制页面
+   * @param page - 排版引擎输出的页面
+   */
+  function renderPage(page: LayoutPage) {
+    return (
+      <div
+        className="layout-page"
+        style={{
+          width: pageSize.width,
+          height: pageSize.height,
+          transform: `scale(${fontSize / 16})`,
+          transformOrigin: "top left"
+        }}
+      >
+        {page.lines.map((line, index) => {
+          const block = page.blocks[line.block_index];
+          // 跨页拆分的段落：接续上一页的片段首行不是段首，在下一页继续的片段末行不是段尾
+          const continuation = page.continuations[line.block_index];
+          const firstLine = index === 0 || page.lines[index - 1].block_index !== line.block_index;
+          const lastLine = index === page.lines.length - 1 || page.lines[index + 1].block_index !== line.block_index;
+          const classNames = ["layout-line"];
+          if (firstLine && !continuation?.from_previous) {
+            classNames.push("paragraph-start");
+          }
+          if (lastLine && !continuation?.to_next) {
+            classNames.push("paragraph-end");
+          }
+          return (
+            <div
+              key={index}
+              className={classNames.join(" ")}
+              style={{
+                left: line.x,
+                top: line.y,
+                height: line.height,
+                lineHeight: `${line.height}px`,
+                fontSize: block.styles.font_size,
+                fontWeight: block.styles.bold ? "bold" : undefined,
+                fontStyle: block.styles.italic ? "italic" : undefined
+              }}
+            >
+              {lineSegments(block.content, line).map(([text, spacing], segment) => (
+                <span key={segment} style={spacing ? { marginLeft: spacing } : undefined}>{text}</span>
+              ))}
+              {line.hyphenated ? "-" : null}
+            </div>
+          );
+        })}
+      </div>
+    );
   }
 
   // 返回书架
@@ -175,6 +307,22 @@ const Reader: React.FC = () => {
     setTheme(prev => prev === "light" ? "dark" : "light");
   };
 
+  // 切换简繁显示方式并保存为这本书的显示选项
+  const handleChineseVariantChange = async (e: React.ChangeEvent<HTMLSelectElement>) => {
+    if (!filename) {
+      return;
+    }
+    try {
+      await invoke("set_book_display_options", {
+        filename,
+        options: { chinese_variant: e.target.value || null }
+      });
+      await loadDocument(filename);
+    } catch (err) {
+      setError("保存显示选项失败: " + err);
+    }
+  };
+
   // 上一页
   const prevPage = () => {
     console.log("=== 翻页操作开始 ===");
@@ -244,7 +392,7 @@ const Reader: React.FC = () => {
     console.log("*** pages 状态更新，新的页面数量:", pages.length);
     console.log("*** 页面数据:", pages);
     if (pages.length > 0) {
-      console.log("*** 第一页内容预览:", pages[0].content.substring(0, 100) + "...");
+      console.log("*** 第一页行数:", pages[0].page.lines.length);
     }
   }, [pages]);
 
@@ -288,6 +436,13 @@ const Reader: React.FC = () => {
           <button onClick={toggleTheme}>
             {theme === "light" ? "夜间" : "日间"}
           </button>
+          <select value={chineseVariant} onChange={handleChineseVariantChange}>
+            {CHINESE_VARIANTS.map(([value, label]) => (
+              <option key={value} value={value}>
+                {label}
+              </option>
+            ))}
+          </select>
         </div>
       </div>
       
@@ -312,12 +467,11 @@ const Reader: React.FC = () => {
         {(loadingChapter || loading) ? (
           <div>章节加载中...</div>
         ) : pages.length > 0 ? (
-          <pre>{(() => {
-            // 根据当前页码查找对应的页面内容
+          (() => {
+            // 根据当前页码查找对应的页面
             const currentPageContent = pages.find(page => page.pageNumber === currentPage);
-            console.log(">>> 渲染页面内容，当前页:", currentPage, "页面数据:", currentPageContent?.content);
-            return currentPageContent?.content || "无内容";
-          })()}</pre>
+            return currentPageContent ? renderPage(currentPageContent.page) : <pre>无内容</pre>;
+          })()
         ) : (
           <pre>{rawContent}</pre>
         )}
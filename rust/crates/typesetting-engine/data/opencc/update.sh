@@ -0,0 +1,25 @@
+#!/bin/sh
+# 从 OpenCC 的发布版本下载完整词典，覆盖本目录中的同名文件
+#
+# HKPhrases.txt 没有对应的上游文件，保持不变。更新后运行 chinese_convert 的测试，
+# 确认本地补充的词条仍然生效
+set -eu
+
+OPENCC_VERSION="ver.1.1.9"
+BASE_URL="https://raw.githubusercontent.com/BYVoid/OpenCC/${OPENCC_VERSION}/data/dictionary"
+DIR=$(cd "$(dirname "$0")" && pwd)
+
+fetch() {
+    curl -fsSL "${BASE_URL}/$1"
+}
+
+for name in STCharacters STPhrases TSPhrases TWVariants HKVariants; do
+    fetch "${name}.txt" > "${DIR}/${name}.txt.tmp"
+    mv "${DIR}/${name}.txt.tmp" "${DIR}/${name}.txt"
+done
+
+# 上游把台湾用词分为三个文件，OpenCC 构建时同样把它们合并为 TWPhrases
+for name in TWPhrasesIT TWPhrasesName TWPhrasesOther; do
+    fetch "${name}.txt"
+done > "${DIR}/TWPhrases.txt.tmp"
+mv "${DIR}/TWPhrases.txt.tmp" "${DIR}/TWPhrases.txt"
@@ -0,0 +1,100 @@
+//! 对齐
+//!
+//! 按对齐方式计算每行的水平位置；两端对齐时把剩余空间分配到空格处，
+//! 中日文还分配到文字之间，并把每处插入的间距记录下来，渲染端据此可以精确还原
+
+use crate::line_break::Line;
+use crate::measure::is_wide;
+use crate::segment::graphemes;
+use serde::Serialize;
+
+/// 对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    /// 左对齐
+    #[default]
+    Left,
+    /// 右对齐
+    Right,
+    /// 居中
+    Center,
+    /// 两端对齐，段落最后一行左对齐
+    Justify,
+}
+
+/// 两端对齐时分配剩余空间的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextJustify {
+    /// 只在空格处分配
+    InterWord,
+    /// 在空格处和中日文字两侧分配，适合中日文排版
+    #[default]
+    InterCharacter,
+}
+
+/// 字形间距调整
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GlyphAdjustment {
+    /// 插入间距的位置：源文本中的字节偏移，间距加在从这里开始的字素簇之前
+    pub offset: usize,
+    /// 插入的间距（像素），压缩时为负数
+    pub spacing: f32,
+}
+
+/// 按对齐方式设置各行的位置和字形间距
+///
+/// # Arguments
+///
+/// * `text` - 断行的源文本
+/// * `lines` - 断行结果
+/// * `max_width` - 可用宽度（像素）
+/// * `align` - 对齐方式
+/// * `justify` - 两端对齐时分配剩余空间的位置
+pub fn align_lines(text: &str, lines: &mut [Line], max_width: f32, align: TextAlign, justify: TextJustify) {
+    for line in lines.iter_mut() {
+        let slack = max_width - line.width;
+        line.adjustments.clear();
+        line.x = match align {
+            TextAlign::Left => 0.0,
+            TextAlign::Right => slack,
+            TextAlign::Center => slack / 2.0,
+            TextAlign::Justify => {
+                // 全段最优断行中超出可用宽度的行只在空格处压缩
+                if slack < 0.0 {
+                    line.adjustments = justify_line(text, line, TextJustify::InterWord, slack);
+                } else if !line.ends_paragraph {
+                    line.adjustments = justify_line(text, line, justify, slack);
+                }
+                0.0
+            }
+        };
+    }
+}
+
+/// 把剩余空间平均分配到行内的可调整间隙
+///
+/// 没有可调整的间隙时保持左对齐
+fn justify_line(text: &str, line: &Line, justify: TextJustify, slack: f32) -> Vec<GlyphAdjustment> {
+    let content = text[line.byte_range.clone()].trim_end_matches([' ', '\t']);
+    let mut gaps = Vec::new();
+    let mut offset = line.byte_range.start;
+    let mut previous: Option<&str> = None;
+    for grapheme in graphemes(content) {
+        if let Some(before) = previous {
+            let space = before.starts_with([' ', '\t']);
+            let cjk = justify == TextJustify::InterCharacter
+                && !grapheme.starts_with([' ', '\t'])
+                && (before.chars().next().is_some_and(is_wide) || grapheme.chars().next().is_some_and(is_wide));
+            if space || cjk {
+                gaps.push(offset);
+            }
+        }
+        previous = Some(grapheme);
+        offset += grapheme.len();
+    }
+    if gaps.is_empty() {
+        return Vec::new();
+    }
+    let spacing = slack / gaps.len() as f32;
+    gaps.into_iter().map(|offset| GlyphAdjustment { offset, spacing }).collect()
+}
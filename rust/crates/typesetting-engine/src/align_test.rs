@@ -0,0 +1,101 @@
+//! 对齐单元测试
+//!
+//! 测试各种对齐方式下行的位置和两端对齐时字形间距的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::align::*;
+    use crate::document::{ContentBlock, ContentBlockType, TextStyle};
+    use crate::layout::{LayoutEngine, PageConfig, TypographyConfig};
+    use crate::line_break::*;
+    use crate::measure::ApproximateTextMeasurer;
+    use std::borrow::Cow;
+
+    /// 字号10的样式，半角字符宽5，全角字符宽10
+    fn style() -> TextStyle {
+        TextStyle {
+            font_size: 10.0,
+            font_family: Cow::Borrowed("serif"),
+            bold: false,
+            italic: false,
+        }
+    }
+
+    /// 按指定对齐方式断行
+    fn aligned(text: &str, max_width: f32, align: TextAlign, justify: TextJustify) -> Vec<Line> {
+        let mut lines = break_lines(text, &style(), &ApproximateTextMeasurer, max_width);
+        align_lines(text, &mut lines, max_width, align, justify);
+        lines
+    }
+
+    /// 测试左对齐、右对齐和居中的水平位置
+    #[test]
+    fn test_horizontal_position() {
+        let left = aligned("ab cd", 40.0, TextAlign::Left, TextJustify::InterWord);
+        assert_eq!(left[0].x, 0.0);
+        let right = aligned("ab cd", 40.0, TextAlign::Right, TextJustify::InterWord);
+        assert_eq!(right[0].x, 15.0);
+        let center = aligned("ab cd", 40.0, TextAlign::Center, TextJustify::InterWord);
+        assert_eq!(center[0].x, 7.5);
+        assert!(center[0].adjustments.is_empty());
+    }
+
+    /// 测试西文两端对齐只在空格处分配，段落最后一行左对齐
+    #[test]
+    fn test_justify_inter_word() {
+        let text = "aa b cc ddddd";
+        let lines = aligned(text, 40.0, TextAlign::Justify, TextJustify::InterWord);
+        assert_eq!(&text[lines[0].byte_range.clone()], "aa b cc ");
+        assert_eq!(lines[0].x, 0.0);
+        assert_eq!(lines[0].adjustments, vec![
+            GlyphAdjustment { offset: 3, spacing: 2.5 },
+            GlyphAdjustment { offset: 5, spacing: 2.5 },
+        ]);
+        assert!(lines[1].ends_paragraph);
+        assert!(lines[1].adjustments.is_empty());
+    }
+
+    /// 测试中文两端对齐分配到文字之间，剩余空间全部分配完
+    #[test]
+    fn test_justify_inter_character() {
+        let text = "一二三，四五六";
+        let lines = aligned(text, 45.0, TextAlign::Justify, TextJustify::InterCharacter);
+        assert_eq!(&text[lines[0].byte_range.clone()], "一二三，");
+        let spacing: f32 = lines[0].adjustments.iter().map(|adjustment| adjustment.spacing).sum();
+        assert_eq!(lines[0].adjustments.len(), 3);
+        assert_eq!(lines[0].width + spacing, 45.0);
+        assert_eq!(lines[0].adjustments[0].offset, 3);
+
+        // 只在空格处分配时中文行没有可调整的间隙
+        let lines = aligned(text, 45.0, TextAlign::Justify, TextJustify::InterWord);
+        assert!(lines[0].adjustments.is_empty());
+    }
+
+    /// 测试通过排版设置选择对齐方式
+    #[test]
+    fn test_typography_align() {
+        let config = PageConfig {
+            width: 60.0,
+            height: 200.0,
+            margin_top: 10.0,
+            margin_bottom: 10.0,
+            margin_left: 10.0,
+            margin_right: 10.0,
+        };
+        let block = ContentBlock {
+            block_type: ContentBlockType::Text,
+            content: Cow::Borrowed("一二三四五"),
+            styles: style(),
+            metrics: None,
+            language: None,
+        };
+        let typography = TypographyConfig {
+            align: TextAlign::Right,
+            ..TypographyConfig::default()
+        };
+        assert_ne!(typography.cache_key(), TypographyConfig::default().cache_key());
+        let lines = LayoutEngine::new(config).with_typography(typography).break_block(&block);
+        assert_eq!(lines[0].x, 0.0);
+        assert_eq!(lines[1].x, 30.0);
+    }
+}
@@ -24,6 +24,7 @@ pub async fn parse_document_async(content: String) -> DocumentModel {
             title: Cow::Borrowed("Error Document"),
             author: Cow::Borrowed("Unknown"),
             created_at: Cow::Owned(chrono::Utc::now().to_rfc3339()),
+            language: None,
         },
         chapters: Vec::new(),
         styles: Vec::new(),
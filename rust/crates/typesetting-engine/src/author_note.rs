@@ -0,0 +1,63 @@
+//! 作者的话识别
+//!
+//! 识别网络连载小说中夹在正文里的“作者有话说”、感言、求票和打赏鸣谢，
+//! 以及整章都是请假条、公告的章节，使阅读器可以隐藏、折叠或不计入页数和目录
+
+use crate::document::{Chapter, ContentBlockType};
+use crate::parser::chapter_label;
+use regex::Regex;
+
+lazy_static::lazy_static! {
+    /// 整章都是公告的章节标题（去除 `第N章` 之后），如 `请假条`、`上架感言`；
+    /// 关键词之后只能是标题结尾或分隔符，避免误伤 `公告天下`、`请假的少女` 这样的正文标题
+    static ref NOTICE_TITLE_REGEX: Regex = Regex::new(
+        r"^[\s:：、.．]*(?:请假(?:条|单|通知|公告|[一二两三四五六七八九十\d０-９]+天)?|(?:(?:上架|完本|完结|入[Vv]|VIP)?感言|公告|通知|单章|致读者|告读者|致歉|道歉|新书(?:发布|上传|已发)?|求(?:月票|推荐票?|收藏|订阅)|作者的话|作者有话说|更新说明|断更说明|休息一天)(?:[\s:：，,！!。.（(【\[].*)?)$"
+    ).unwrap();
+    /// 作者的话的开头，其后直到章节末尾的段落都属于作者的话
+    static ref NOTE_HEADER_REGEX: Regex = Regex::new(
+        r"^[\s【\[（(]*(?:作者有话说|作者有话要说|作者的话|作者留言|作者语|作家的话)"
+    ).unwrap();
+    /// 单独一段的作者的话，如 `PS：`、求票和打赏鸣谢
+    static ref NOTE_PARAGRAPH_REGEX: Regex = Regex::new(
+        r"^[\s【\[（(]*(?:[Pp]\.?[Ss]\.?\s*[：:，,.\d]|感言[：:]|求(?:月票|推荐票?|收藏|订阅|打赏)|感谢.{0,30}(?:打赏|月票|推荐票|盟主|万赏|舵主|订阅))"
+    ).unwrap();
+}
+
+/// 识别章节中的作者的话
+///
+/// 作者的话所在的文本块被标记为 [`ContentBlockType::AuthorNote`]；标题是公告、
+/// 或者所有文本块都是作者的话的章节被标记为整章公告
+///
+/// # Arguments
+///
+/// * `chapter` - 需要处理的章节
+pub(crate) fn detect_author_notes(chapter: &mut Chapter) {
+    let mut in_note = false;
+    for block in chapter.content.iter_mut() {
+        if block.block_type != ContentBlockType::Text {
+            continue;
+        }
+        let text = block.content.trim_start();
+        if NOTE_HEADER_REGEX.is_match(text) {
+            in_note = true;
+        }
+        if in_note || NOTE_PARAGRAPH_REGEX.is_match(text) {
+            block.block_type = ContentBlockType::AuthorNote;
+        }
+    }
+
+    let title = chapter.title.trim();
+    let rest = chapter_label(title).map_or(title, |label| &title[label.len()..]);
+    // 诗词、脚注等其他内容块也说明章节中有正文
+    let has_text = chapter.content.iter().any(|block| block.block_type != ContentBlockType::AuthorNote);
+    let has_note = chapter.content.iter().any(|block| block.block_type == ContentBlockType::AuthorNote);
+
+    if NOTICE_TITLE_REGEX.is_match(rest) || (has_note && !has_text) {
+        chapter.author_note = true;
+        for block in chapter.content.iter_mut() {
+            if block.block_type == ContentBlockType::Text {
+                block.block_type = ContentBlockType::AuthorNote;
+            }
+        }
+    }
+}
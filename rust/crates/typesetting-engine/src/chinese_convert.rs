@@ -0,0 +1,479 @@
+//! 简繁转换
+//! 
+//! 基于内置的 OpenCC 格式词典，在文档模型上进行简体、繁体以及台湾、香港地区字形和用词的转换
+//!
+//! `data/opencc` 中的词典来自 OpenCC（https://github.com/BYVoid/OpenCC 的 `data/dictionary`，
+//! Apache-2.0 许可，见 `data/opencc/LICENSE`），可用同目录下的 `update.sh` 更新为指定发布版本的完整词典。
+//! 只有 `HKPhrases.txt` 是本项目维护的香港用词表
+
+use crate::document::{DocumentModel, DocumentMetadata, Chapter, ContentBlock, FootnoteRef};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 中文变体
+/// 
+/// 转换的源和目标，繁体以 OpenCC 标准字形为中间形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChineseVariant {
+    /// 简体中文（中国大陆）
+    Simplified,
+    /// 繁体中文（OpenCC 标准字形）
+    Traditional,
+    /// 繁体中文（台湾地区字形与用词）
+    Taiwan,
+    /// 繁体中文（香港地区字形与用词）
+    HongKong,
+}
+
+impl ChineseVariant {
+    /// 从语言标签解析中文变体
+    /// 
+    /// 支持 `zh-Hans`、`zh-CN`、`zh-Hant`、`zh-TW`、`zh-HK` 等常见写法，不区分大小写
+    /// 
+    /// # Arguments
+    /// 
+    /// * `tag` - 语言标签
+    /// 
+    /// # Returns
+    /// 
+    /// 无法识别时返回None
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag.to_ascii_lowercase().replace('_', "-").as_str() {
+            "zh-hans" | "zh-cn" | "zh-sg" | "simplified" => Some(ChineseVariant::Simplified),
+            "zh-hant" | "traditional" => Some(ChineseVariant::Traditional),
+            "zh-tw" | "zh-hant-tw" | "taiwan" => Some(ChineseVariant::Taiwan),
+            "zh-hk" | "zh-mo" | "zh-hant-hk" | "hongkong" => Some(ChineseVariant::HongKong),
+            _ => None,
+        }
+    }
+
+    /// 获取对应的语言标签
+    /// 
+    /// # Returns
+    /// 
+    /// 返回 BCP 47 语言标签
+    pub fn tag(&self) -> &'static str {
+        match self {
+            ChineseVariant::Simplified => "zh-Hans",
+            ChineseVariant::Traditional => "zh-Hant",
+            ChineseVariant::Taiwan => "zh-TW",
+            ChineseVariant::HongKong => "zh-HK",
+        }
+    }
+}
+
+/// 转换词典
+/// 
+/// 词条为“原文 → 译文”的映射，可以是单字也可以是词组
+#[derive(Debug, Clone, Default)]
+pub struct ConversionDictionary {
+    /// 词条映射
+    entries: HashMap<String, String>,
+    /// 最长词条的字符数
+    max_key_chars: usize,
+}
+
+impl ConversionDictionary {
+    /// 创建一个空词典
+    /// 
+    /// # Returns
+    /// 
+    /// 返回一个新的ConversionDictionary实例
+    pub fn new() -> Self {
+        ConversionDictionary::default()
+    }
+
+    /// 解析 OpenCC 文本格式的词典
+    /// 
+    /// 每行一个词条，原文与译文以制表符分隔，有多个译文时以空格分隔并取第一个
+    /// 
+    /// # Arguments
+    /// 
+    /// * `text` - 词典文本
+    /// 
+    /// # Returns
+    /// 
+    /// 返回解析后的词典
+    pub fn from_opencc(text: &str) -> Self {
+        let mut dictionary = ConversionDictionary::new();
+        for (key, values) in opencc_entries(text) {
+            if let Some(value) = values.split(' ').next() {
+                dictionary.insert(key, value);
+            }
+        }
+        dictionary
+    }
+
+    /// 反向解析 OpenCC 文本格式的词典
+    /// 
+    /// 每个译文都映射回原文，同一个译文出现多次时保留第一次出现的映射
+    /// 
+    /// # Arguments
+    /// 
+    /// * `text` - 词典文本
+    /// 
+    /// # Returns
+    /// 
+    /// 返回反向的词典
+    pub fn from_opencc_reversed(text: &str) -> Self {
+        let mut dictionary = ConversionDictionary::new();
+        for (key, values) in opencc_entries(text) {
+            for value in values.split(' ').filter(|value| !value.is_empty()) {
+                if !dictionary.entries.contains_key(value) {
+                    dictionary.insert(value, key);
+                }
+            }
+        }
+        dictionary
+    }
+
+    /// 添加或覆盖一个词条
+    /// 
+    /// # Arguments
+    /// 
+    /// * `from` - 原文
+    /// * `to` - 译文
+    pub fn insert(&mut self, from: &str, to: &str) {
+        if from.is_empty() {
+            return;
+        }
+        self.max_key_chars = self.max_key_chars.max(from.chars().count());
+        self.entries.insert(from.to_string(), to.to_string());
+    }
+
+    /// 查询词条
+    /// 
+    /// # Arguments
+    /// 
+    /// * `from` - 原文
+    /// 
+    /// # Returns
+    /// 
+    /// 如果存在该词条则返回译文
+    pub fn get(&self, from: &str) -> Option<&str> {
+        self.entries.get(from).map(String::as_str)
+    }
+
+    /// 获取词条数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 判断词典是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// 遍历 OpenCC 词典文本中的词条
+fn opencc_entries(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    text.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('\t'))
+}
+
+lazy_static::lazy_static! {
+    static ref ST_CHARACTERS: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc(include_str!("../data/opencc/STCharacters.txt")));
+    static ref ST_PHRASES: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc(include_str!("../data/opencc/STPhrases.txt")));
+    static ref TS_CHARACTERS: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc_reversed(include_str!("../data/opencc/STCharacters.txt")));
+    static ref TS_PHRASES: Arc<ConversionDictionary> = {
+        // 专门的繁转简词组优先，其余由简转繁词组反向得到
+        let mut dictionary = ConversionDictionary::from_opencc_reversed(include_str!("../data/opencc/STPhrases.txt"));
+        for (key, values) in opencc_entries(include_str!("../data/opencc/TSPhrases.txt")) {
+            if let Some(value) = values.split(' ').next() {
+                dictionary.insert(key, value);
+            }
+        }
+        Arc::new(dictionary)
+    };
+    static ref TW_VARIANTS: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc(include_str!("../data/opencc/TWVariants.txt")));
+    static ref TW_VARIANTS_REV: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc_reversed(include_str!("../data/opencc/TWVariants.txt")));
+    static ref TW_PHRASES: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc(include_str!("../data/opencc/TWPhrases.txt")));
+    static ref TW_PHRASES_REV: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc_reversed(include_str!("../data/opencc/TWPhrases.txt")));
+    static ref HK_VARIANTS: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc(include_str!("../data/opencc/HKVariants.txt")));
+    static ref HK_VARIANTS_REV: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc_reversed(include_str!("../data/opencc/HKVariants.txt")));
+    static ref HK_PHRASES: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc(include_str!("../data/opencc/HKPhrases.txt")));
+    static ref HK_PHRASES_REV: Arc<ConversionDictionary> =
+        Arc::new(ConversionDictionary::from_opencc_reversed(include_str!("../data/opencc/HKPhrases.txt")));
+}
+
+/// 判断汉字是否只用于简体，即简转繁时会被转换的字
+pub(crate) fn is_simplified_char(c: char) -> bool {
+    let mut buffer = [0u8; 4];
+    let key: &str = c.encode_utf8(&mut buffer);
+    ST_CHARACTERS.get(key).is_some_and(|value| value != key)
+}
+
+/// 判断汉字是否只用于繁体，即繁转简时会被转换的字
+pub(crate) fn is_traditional_char(c: char) -> bool {
+    let mut buffer = [0u8; 4];
+    let key: &str = c.encode_utf8(&mut buffer);
+    TS_CHARACTERS.get(key).is_some_and(|value| value != key)
+}
+
+/// 转换阶段
+/// 
+/// 同一阶段内的词典一起参与最长匹配，长度相同时排在前面的词典优先
+type ConversionStage = Vec<Arc<ConversionDictionary>>;
+
+/// 简繁转换器
+/// 
+/// 将文本依次通过若干转换阶段：先从源变体转换为 OpenCC 标准繁体，再转换为目标变体
+pub struct ChineseConverter {
+    /// 源变体
+    from: ChineseVariant,
+    /// 目标变体
+    to: ChineseVariant,
+    /// 转换阶段列表
+    stages: Vec<ConversionStage>,
+}
+
+impl ChineseConverter {
+    /// 创建一个新的简繁转换器
+    /// 
+    /// # Arguments
+    /// 
+    /// * `from` - 源变体
+    /// * `to` - 目标变体
+    /// 
+    /// # Returns
+    /// 
+    /// 返回一个新的ChineseConverter实例
+    pub fn new(from: ChineseVariant, to: ChineseVariant) -> Self {
+        let mut stages: Vec<ConversionStage> = Vec::new();
+
+        if from != to {
+            // 源变体 → 标准繁体
+            match from {
+                ChineseVariant::Simplified => stages.push(vec![ST_PHRASES.clone(), ST_CHARACTERS.clone()]),
+                ChineseVariant::Traditional => {}
+                ChineseVariant::Taiwan => stages.push(vec![TW_PHRASES_REV.clone(), TW_VARIANTS_REV.clone()]),
+                ChineseVariant::HongKong => stages.push(vec![HK_PHRASES_REV.clone(), HK_VARIANTS_REV.clone()]),
+            }
+
+            // 标准繁体 → 目标变体
+            match to {
+                ChineseVariant::Simplified => stages.push(vec![TS_PHRASES.clone(), TS_CHARACTERS.clone()]),
+                ChineseVariant::Traditional => {}
+                ChineseVariant::Taiwan => {
+                    stages.push(vec![TW_PHRASES.clone()]);
+                    stages.push(vec![TW_VARIANTS.clone()]);
+                }
+                ChineseVariant::HongKong => {
+                    stages.push(vec![HK_PHRASES.clone()]);
+                    stages.push(vec![HK_VARIANTS.clone()]);
+                }
+            }
+        }
+
+        ChineseConverter { from, to, stages }
+    }
+
+    /// 添加用户词典
+    /// 
+    /// 用户词典在第一个转换阶段中优先于内置词典，适合为每本书补充人名、专有名词
+    /// 
+    /// # Arguments
+    /// 
+    /// * `dictionary` - 用户词典，原文为源变体的写法
+    /// 
+    /// # Returns
+    /// 
+    /// 返回添加了用户词典的转换器
+    pub fn with_user_dictionary(mut self, dictionary: ConversionDictionary) -> Self {
+        let dictionary = Arc::new(dictionary);
+        match self.stages.first_mut() {
+            Some(stage) => stage.insert(0, dictionary),
+            None => self.stages.push(vec![dictionary]),
+        }
+        self
+    }
+
+    /// 获取源变体
+    pub fn from_variant(&self) -> ChineseVariant {
+        self.from
+    }
+
+    /// 获取目标变体
+    pub fn to_variant(&self) -> ChineseVariant {
+        self.to
+    }
+
+    /// 转换一段文本
+    /// 
+    /// # Arguments
+    /// 
+    /// * `text` - 需要转换的文本
+    /// 
+    /// # Returns
+    /// 
+    /// 返回转换后的文本
+    pub fn convert(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        for stage in &self.stages {
+            result = convert_stage(stage, &result);
+        }
+        result
+    }
+
+    /// 转换整个文档
+    /// 
+    /// 转换文档标题、作者、章节标题和所有内容块，章节ID和样式保持不变
+    /// 
+    /// # Arguments
+    /// 
+    /// * `document` - 需要转换的文档模型
+    /// 
+    /// # Returns
+    /// 
+    /// 返回转换后的文档模型
+    pub fn convert_document(&self, document: &DocumentModel) -> DocumentModel {
+        if self.stages.is_empty() {
+            return document.clone();
+        }
+
+        DocumentModel {
+            metadata: DocumentMetadata {
+                title: self.convert_cow(&document.metadata.title),
+                author: self.convert_cow(&document.metadata.author),
+                created_at: document.metadata.created_at.clone(),
+                language: self.convert_language(&document.metadata.language),
+            },
+            chapters: document.chapters.iter().map(|chapter| self.convert_chapter(chapter)).collect(),
+            styles: document.styles.clone(),
+        }
+    }
+
+    /// 转换单个章节
+    /// 
+    /// # Arguments
+    /// 
+    /// * `chapter` - 需要转换的章节
+    /// 
+    /// # Returns
+    /// 
+    /// 返回转换后的章节
+    pub fn convert_chapter(&self, chapter: &Chapter) -> Chapter {
+        let mut footnote_refs = chapter.footnote_refs.clone();
+        let content = chapter.content
+            .iter()
+            .enumerate()
+            .map(|(block_index, block)| {
+                // 转换会改变文本长度，按脚注标记分段转换，使标记的字节范围保持有效
+                let mut refs: Vec<&mut FootnoteRef> = footnote_refs
+                    .iter_mut()
+                    .filter(|footnote_ref| footnote_ref.block_index == block_index)
+                    .collect();
+                if refs.is_empty() {
+                    return ContentBlock {
+                        content: self.convert_cow(&block.content),
+                        language: self.convert_language(&block.language),
+                        ..block.clone()
+                    };
+                }
+                refs.sort_by_key(|footnote_ref| footnote_ref.range.start);
+
+                let mut converted = String::with_capacity(block.content.len());
+                let mut last_end = 0;
+                for footnote_ref in refs {
+                    let range = footnote_ref.range.clone();
+                    converted.push_str(&self.convert(&block.content[last_end..range.start]));
+                    let start = converted.len();
+                    converted.push_str(&block.content[range.clone()]);
+                    footnote_ref.range = start..converted.len();
+                    last_end = range.end;
+                }
+                converted.push_str(&self.convert(&block.content[last_end..]));
+
+                ContentBlock {
+                    content: Cow::Owned(converted),
+                    language: self.convert_language(&block.language),
+                    ..block.clone()
+                }
+            })
+            .collect();
+
+        Chapter {
+            id: chapter.id.clone(),
+            title: self.convert_cow(&chapter.title),
+            number: chapter.number,
+            content,
+            footnote_refs,
+            author_note: chapter.author_note,
+        }
+    }
+
+    /// 转换文本并包装为Cow
+    fn convert_cow(&self, text: &str) -> Cow<'static, str> {
+        Cow::Owned(self.convert(text))
+    }
+
+    /// 转换语言标签：中文变体改为目标变体，其他语言保持不变
+    fn convert_language(&self, language: &Option<Cow<'static, str>>) -> Option<Cow<'static, str>> {
+        match language {
+            Some(tag) if ChineseVariant::from_tag(tag).is_some() => Some(Cow::Borrowed(self.to.tag())),
+            _ => language.clone(),
+        }
+    }
+}
+
+/// 用一个转换阶段转换文本
+/// 
+/// 从左到右进行正向最长匹配，未命中任何词条的字符原样保留
+/// 
+/// # Arguments
+/// 
+/// * `stage` - 转换阶段
+/// * `text` - 需要转换的文本
+/// 
+/// # Returns
+/// 
+/// 返回转换后的文本
+fn convert_stage(stage: &[Arc<ConversionDictionary>], text: &str) -> String {
+    let max_key_chars = stage.iter().map(|dictionary| dictionary.max_key_chars).max().unwrap_or(0);
+    if max_key_chars == 0 {
+        return text.to_string();
+    }
+
+    // 每个字符的起始字节位置，末尾追加文本长度便于切片
+    let mut boundaries: Vec<usize> = text.char_indices().map(|(index, _)| index).collect();
+    boundaries.push(text.len());
+    let char_count = boundaries.len() - 1;
+
+    let mut result = String::with_capacity(text.len());
+    let mut position = 0;
+
+    while position < char_count {
+        let longest = max_key_chars.min(char_count - position);
+        let mut matched = false;
+
+        for length in (1..=longest).rev() {
+            let candidate = &text[boundaries[position]..boundaries[position + length]];
+            if let Some(value) = stage.iter().find_map(|dictionary| dictionary.get(candidate)) {
+                result.push_str(value);
+                position += length;
+                matched = true;
+                break;
+            }
+        }
+
+        if !matched {
+            result.push_str(&text[boundaries[position]..boundaries[position + 1]]);
+            position += 1;
+        }
+    }
+
+    result
+}
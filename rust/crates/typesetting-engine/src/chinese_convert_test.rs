@@ -0,0 +1,133 @@
+//! 简繁转换单元测试
+//! 
+//! 测试简繁转换相关功能的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::chinese_convert::*;
+    use crate::parser::ParserEngine;
+
+    /// 测试简体转繁体
+    #[test]
+    fn test_simplified_to_traditional() {
+        let converter = ChineseConverter::new(ChineseVariant::Simplified, ChineseVariant::Traditional);
+        
+        assert_eq!(converter.convert("陆江仙做了一个很长很长的梦"), "陸江仙做了一個很長很長的夢");
+        assert_eq!(converter.convert("Hello, 世界!"), "Hello, 世界!");
+    }
+
+    /// 测试词组优先于单字
+    #[test]
+    fn test_phrase_level_conversion() {
+        let converter = ChineseConverter::new(ChineseVariant::Simplified, ChineseVariant::Traditional);
+        
+        // “发”在不同词组中对应不同的繁体字
+        assert_eq!(converter.convert("出发前理了头发"), "出發前理了頭髮");
+        assert_eq!(converter.convert("饼干很干净"), "餅乾很乾淨");
+    }
+
+    /// 测试干、发、后的常用词组
+    #[test]
+    fn test_ambiguous_character_phrases() {
+        for to in [ChineseVariant::Traditional, ChineseVariant::Taiwan, ChineseVariant::HongKong] {
+            let converter = ChineseConverter::new(ChineseVariant::Simplified, to);
+            assert_eq!(converter.convert("大家一起干杯"), "大家一起乾杯");
+            assert_eq!(converter.convert("天气干旱，他干脆去干活"), "天氣乾旱，他乾脆去幹活");
+            assert_eq!(converter.convert("毫不相干的干部"), "毫不相干的幹部");
+            assert_eq!(converter.convert("她洗了金发后出发"), "她洗了金髮後出發");
+            assert_eq!(converter.convert("千钧一发"), "千鈞一髮");
+            assert_eq!(converter.convert("皇太后与后羿"), "皇太后與后羿");
+        }
+    }
+
+    /// 测试地区用词
+    #[test]
+    fn test_regional_variants() {
+        let taiwan = ChineseConverter::new(ChineseVariant::Simplified, ChineseVariant::Taiwan);
+        assert_eq!(taiwan.convert("网络软件"), "網路軟體");
+        assert_eq!(taiwan.convert("这里"), "這裡");
+        
+        let hong_kong = ChineseConverter::new(ChineseVariant::Simplified, ChineseVariant::HongKong);
+        assert_eq!(hong_kong.convert("骑自行车"), "騎單車");
+        assert_eq!(hong_kong.convert("他说"), "他説");
+        
+        let mainland = ChineseConverter::new(ChineseVariant::Taiwan, ChineseVariant::Simplified);
+        assert_eq!(mainland.convert("網路軟體"), "网络软件");
+    }
+
+    /// 测试繁体转简体
+    #[test]
+    fn test_traditional_to_simplified() {
+        let converter = ChineseConverter::new(ChineseVariant::Traditional, ChineseVariant::Simplified);
+        
+        assert_eq!(converter.convert("頭髮與發展"), "头发与发展");
+        // 繁体中保留的字不应被错误转换
+        assert_eq!(converter.convert("乾隆皇帝"), "乾隆皇帝");
+        assert_eq!(converter.convert("乾淨"), "干净");
+    }
+
+    /// 测试相同变体之间不做转换
+    #[test]
+    fn test_identity_conversion() {
+        let converter = ChineseConverter::new(ChineseVariant::Simplified, ChineseVariant::Simplified);
+        assert_eq!(converter.convert("头发"), "头发");
+    }
+
+    /// 测试用户词典
+    #[test]
+    fn test_user_dictionary() {
+        let mut dictionary = ConversionDictionary::new();
+        dictionary.insert("李叶生", "李葉生");
+        dictionary.insert("于羽", "于羽");
+        
+        let converter = ChineseConverter::new(ChineseVariant::Simplified, ChineseVariant::Traditional)
+            .with_user_dictionary(dictionary);
+        
+        assert_eq!(converter.convert("于羽与李叶生"), "于羽與李葉生");
+    }
+
+    /// 测试语言标签解析
+    #[test]
+    fn test_variant_tags() {
+        assert_eq!(ChineseVariant::from_tag("zh-CN"), Some(ChineseVariant::Simplified));
+        assert_eq!(ChineseVariant::from_tag("zh_TW"), Some(ChineseVariant::Taiwan));
+        assert_eq!(ChineseVariant::from_tag("zh-HK"), Some(ChineseVariant::HongKong));
+        assert_eq!(ChineseVariant::from_tag("en"), None);
+        assert_eq!(ChineseVariant::from_tag(ChineseVariant::Traditional.tag()), Some(ChineseVariant::Traditional));
+    }
+
+    /// 测试文档转换
+    #[test]
+    fn test_document_conversion() {
+        let content = "第一章 李家\n\n李木田寅时醒了。\n\n第二章 鉴子\n\n远远地走来一个女孩。";
+        let parser = ParserEngine::new();
+        let mut document = parser.parse_txt(content);
+        document.metadata.title = "玄鉴仙族".into();
+        document.metadata.author = "季越人".into();
+        
+        let converter = ChineseConverter::new(ChineseVariant::Simplified, ChineseVariant::Taiwan);
+        let converted = converter.convert_document(&document);
+        
+        assert_eq!(converted.metadata.title, "玄鑒仙族");
+        assert_eq!(converted.metadata.author, "季越人");
+        assert_eq!(converted.chapters[1].title, "第二章 鑒子");
+        assert_eq!(converted.chapters[1].content[0].content, "遠遠地走來一個女孩。");
+        assert_eq!(converted.chapters[1].id, document.chapters[1].id);
+    }
+
+    /// 测试转换后脚注标记的位置仍然有效
+    #[test]
+    fn test_footnote_ranges_after_conversion() {
+        let content = "第一章 序\n\n他骑自行车[1]去了公司。\n\n[1]一辆旧车。";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        
+        let converter = ChineseConverter::new(ChineseVariant::Simplified, ChineseVariant::HongKong);
+        let converted = converter.convert_document(&document);
+        let chapter = &converted.chapters[0];
+        
+        assert_eq!(chapter.content[0].content, "他騎單車[1]去了公司。");
+        let footnote_ref = &chapter.footnote_refs[0];
+        assert_eq!(&chapter.content[0].content[footnote_ref.range.clone()], "[1]");
+    }
+}
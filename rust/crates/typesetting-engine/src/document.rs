@@ -2,7 +2,10 @@
 //! 
 //! 这个模块定义了排版引擎的核心数据结构，包括文档、章节、内容块等。
 
+use crate::parser::chapter_label;
+use serde::Serialize;
 use std::borrow::Cow;
+use std::ops::Range;
 
 /// 文档元数据
 /// 
@@ -15,6 +18,8 @@ pub struct DocumentMetadata {
     pub author: Cow<'static, str>,
     /// 创建时间（RFC3339格式）
     pub created_at: Cow<'static, str>,
+    /// 文档语言（BCP 47 语言标签，如 `zh-Hans`、`en`），导入器可以直接指定，未指定时自动识别
+    pub language: Option<Cow<'static, str>>,
 }
 
 /// 章节结构
@@ -26,14 +31,84 @@ pub struct Chapter {
     pub id: Cow<'static, str>,
     /// 章节标题
     pub title: Cow<'static, str>,
+    /// 章节序号，从 `第N章` 中解析，标题中没有序号时为None
+    pub number: Option<u32>,
     /// 章节内容块列表
     pub content: Vec<ContentBlock>,
+    /// 正文中的脚注引用
+    pub footnote_refs: Vec<FootnoteRef>,
+    /// 是否整章都是作者的话，如请假条、上架感言
+    pub author_note: bool,
+}
+
+impl Chapter {
+    /// 获取规范化的章节标题
+    /// 
+    /// 把标题中各种写法的章节序号统一为阿拉伯数字，如 `第一千零二十三章　归来` 变为 `第1023章 归来`
+    /// 
+    /// # Returns
+    /// 
+    /// 没有章节序号时返回原标题
+    pub fn normalized_title(&self) -> Cow<'_, str> {
+        let number = match self.number {
+            Some(number) => number,
+            None => return Cow::Borrowed(&self.title),
+        };
+        let title = self.title.trim();
+        let rest = chapter_label(title)
+            .map(|label| title[label.len()..].trim())
+            .unwrap_or(title);
+        if rest.is_empty() {
+            Cow::Owned(format!("第{}章", number))
+        } else {
+            Cow::Owned(format!("第{}章 {}", number, rest))
+        }
+    }
+
+    /// 计算章节指纹
+    /// 
+    /// 对标题和正文中的非空白字符做FNV-1a哈希，换行、缩进和分段方式的变化不影响指纹，
+    /// 结果在不同平台和版本之间保持稳定，可以持久化保存
+    /// 
+    /// # Returns
+    /// 
+    /// 返回64位指纹
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+        
+        // 标题与正文之间用 `\0` 分隔
+        let title = self.title.chars().chain(std::iter::once('\0'));
+        let text = self.content.iter().flat_map(|block| block.content.chars());
+        title
+            .chain(text)
+            .filter(|c| !c.is_whitespace())
+            .flat_map(|c| {
+                let mut buffer = [0u8; 4];
+                c.encode_utf8(&mut buffer);
+                buffer.into_iter().take(c.len_utf8())
+            })
+            .fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+    }
+}
+
+/// 脚注引用
+/// 
+/// 记录正文中脚注标记的位置，并通过锚点与章节末尾的脚注块关联
+#[derive(Debug, Clone, PartialEq)]
+pub struct FootnoteRef {
+    /// 脚注锚点，与对应脚注块的锚点相同
+    pub anchor: Cow<'static, str>,
+    /// 包含该引用的内容块在章节中的索引
+    pub block_index: usize,
+    /// 标记在内容块文本中的字节范围；行内注释被提取后标记不再出现在正文中，范围为空
+    pub range: Range<usize>,
 }
 
 /// 内容块类型枚举
 /// 
 /// 定义了文档中可能的内容类型
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum ContentBlockType {
     /// 普通文本内容
     Text,
@@ -43,12 +118,23 @@ pub enum ContentBlockType {
     Title,
     /// 空白内容
     Blank,
+    /// 场景分隔符，如 `※※※`、`***`
+    SceneBreak,
+    /// 诗词、对联等需要保留换行的内容，每行对应一个诗句
+    Verse,
+    /// 作者的话，如“作者有话说”、感言、求票和打赏鸣谢
+    AuthorNote,
+    /// 脚注内容
+    Footnote {
+        /// 脚注锚点
+        anchor: Cow<'static, str>,
+    },
 }
 
 /// 文本样式
 /// 
 /// 定义文本的显示样式属性
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct TextStyle {
     /// 字体大小（像素）
     pub font_size: f32,
@@ -63,7 +149,7 @@ pub struct TextStyle {
 /// 内容块
 /// 
 /// 文档的基本组成单元，可以是文本、图片等不同类型的内容
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ContentBlock {
     /// 内容块类型
     pub block_type: ContentBlockType,
@@ -73,12 +159,14 @@ pub struct ContentBlock {
     pub styles: TextStyle,
     /// 布局测量数据（可选）
     pub metrics: Option<LayoutMetrics>,
+    /// 内容块的语言，与文档语言相同时为None
+    pub language: Option<Cow<'static, str>>,
 }
 
 /// 布局测量数据
 /// 
 /// 包含内容块的尺寸信息，用于布局计算
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct LayoutMetrics {
     /// 宽度（像素）
     pub width: f32,
@@ -97,4 +185,32 @@ pub struct DocumentModel {
     pub chapters: Vec<Chapter>,
     /// 样式定义列表
     pub styles: Vec<TextStyle>,
-}
\ No newline at end of file
+}
+
+impl DocumentModel {
+    /// 按章节序号查找章节
+    /// 
+    /// # Arguments
+    /// 
+    /// * `number` - 章节序号
+    /// 
+    /// # Returns
+    /// 
+    /// 返回第一个序号匹配的章节索引，找不到时返回None
+    pub fn chapter_index_by_number(&self, number: u32) -> Option<usize> {
+        self.chapters.iter().position(|chapter| chapter.number == Some(number))
+    }
+
+    /// 获取内容块的语言
+    /// 
+    /// # Arguments
+    /// 
+    /// * `block` - 文档中的内容块
+    /// 
+    /// # Returns
+    /// 
+    /// 返回内容块的语言，内容块没有单独的语言时返回文档语言
+    pub fn block_language<'a>(&'a self, block: &'a ContentBlock) -> Option<&'a str> {
+        block.language.as_deref().or(self.metadata.language.as_deref())
+    }
+}
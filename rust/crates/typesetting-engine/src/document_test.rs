@@ -3,6 +3,7 @@
 //! 测试文档模型相关功能的正确性
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use crate::document::*;
     use std::borrow::Cow;
@@ -14,12 +15,16 @@ mod tests {
             title: Cow::Borrowed("Test Document"),
             author: Cow::Borrowed("Test Author"),
             created_at: Cow::Borrowed("2023-01-01"),
+            language: None,
         };
 
         let chapter = Chapter {
             id: Cow::Borrowed("chapter_1"),
             title: Cow::Borrowed("Test Chapter"),
+            number: None,
             content: vec![],
+            footnote_refs: vec![],
+            author_note: false,
         };
 
         let document = DocumentModel {
@@ -48,6 +53,7 @@ mod tests {
             content: Cow::Borrowed("Hello, world!"),
             styles: style.clone(),
             metrics: None,
+            language: None,
         };
 
         assert_eq!(block.content, "Hello, world!");
@@ -70,6 +76,7 @@ mod tests {
             content: Cow::Borrowed("Text content"),
             styles: style.clone(),
             metrics: None,
+            language: None,
         };
 
         let title_block = ContentBlock {
@@ -77,6 +84,7 @@ mod tests {
             content: Cow::Borrowed("Title content"),
             styles: style.clone(),
             metrics: None,
+            language: None,
         };
 
         let image_block = ContentBlock {
@@ -84,6 +92,7 @@ mod tests {
             content: Cow::Borrowed("Image content"),
             styles: style.clone(),
             metrics: None,
+            language: None,
         };
 
         let blank_block = ContentBlock {
@@ -91,6 +100,7 @@ mod tests {
             content: Cow::Borrowed(""),
             styles: style.clone(),
             metrics: None,
+            language: None,
         };
 
         assert!(matches!(text_block.block_type, ContentBlockType::Text));
@@ -119,6 +129,7 @@ mod tests {
             content: Cow::Borrowed("Text content"),
             styles: style,
             metrics: Some(metrics.clone()),
+            language: None,
         };
 
         assert_eq!(block.metrics, Some(metrics));
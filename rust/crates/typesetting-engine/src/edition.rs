@@ -0,0 +1,306 @@
+//! 版本合并
+//!
+//! 比较同一本连载小说的两个版本（如重新下载后新增了章节），找出新增、删除和修改的章节，
+//! 生成保留旧章节ID的合并文档，并把阅读进度、书签和批注的位置迁移到新版本
+
+use crate::document::{Chapter, DocumentModel};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+/// 文本中的位置
+///
+/// 用于记录阅读进度、书签以及批注的起止位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadingPosition {
+    /// 章节ID
+    pub chapter_id: Cow<'static, str>,
+    /// 内容块在章节中的索引
+    pub block_index: usize,
+    /// 在内容块文本中的字节偏移
+    pub offset: usize,
+}
+
+/// 两个版本中互相对应的章节
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterMatch {
+    /// 章节在旧版本中的索引
+    pub old_index: usize,
+    /// 章节在新版本中的索引
+    pub new_index: usize,
+    /// 章节内容是否发生了变化
+    pub changed: bool,
+}
+
+/// 版本差异
+///
+/// 章节依次按以下顺序匹配：ID和内容都相同、内容相同、章节序号或标题相同，
+/// 最后在前后两个已匹配章节之间的空隙中，按位置对应数量相同的剩余章节。
+/// 内容比较使用 [`Chapter::fingerprint`]，不受换行和空白变化的影响
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EditionDiff {
+    /// 互相对应的章节，按新版本中的顺序排列
+    pub matches: Vec<ChapterMatch>,
+    /// 新版本中新增的章节索引
+    pub added: Vec<usize>,
+    /// 旧版本中被删除的章节索引
+    pub removed: Vec<usize>,
+}
+
+impl EditionDiff {
+    /// 比较两个版本的文档
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - 旧版本的文档
+    /// * `new` - 新版本的文档
+    ///
+    /// # Returns
+    ///
+    /// 返回两个版本之间的差异
+    pub fn compute(old: &DocumentModel, new: &DocumentModel) -> Self {
+        let old_fingerprints: Vec<u64> = old.chapters.iter().map(Chapter::fingerprint).collect();
+        let new_fingerprints: Vec<u64> = new.chapters.iter().map(Chapter::fingerprint).collect();
+        let mut old_to_new: Vec<Option<usize>> = vec![None; old.chapters.len()];
+        let mut new_to_old: Vec<Option<usize>> = vec![None; new.chapters.len()];
+
+        // ID和内容都相同
+        let new_ids: HashMap<&str, usize> = new.chapters
+            .iter()
+            .enumerate()
+            .map(|(index, chapter)| (chapter.id.as_ref(), index))
+            .collect();
+        for (old_index, chapter) in old.chapters.iter().enumerate() {
+            if let Some(&new_index) = new_ids.get(chapter.id.as_ref()) {
+                if old_fingerprints[old_index] == new_fingerprints[new_index] && new_to_old[new_index].is_none() {
+                    old_to_new[old_index] = Some(new_index);
+                    new_to_old[new_index] = Some(old_index);
+                }
+            }
+        }
+
+        // 内容相同（章节插入或删除后ID会整体后移），其次是章节序号或标题相同，最后在已匹配章节之间按位置对应
+        match_by_key(&mut old_to_new, &mut new_to_old, |index| old_fingerprints[index].to_string(), |index| new_fingerprints[index].to_string());
+        match_by_key(&mut old_to_new, &mut new_to_old, |index| title_key(&old.chapters[index]), |index| title_key(&new.chapters[index]));
+        match_in_gaps(&mut old_to_new, &mut new_to_old);
+
+        let mut diff = EditionDiff::default();
+        for (new_index, old_index) in new_to_old.iter().enumerate() {
+            match *old_index {
+                Some(old_index) => diff.matches.push(ChapterMatch {
+                    old_index,
+                    new_index,
+                    changed: old_fingerprints[old_index] != new_fingerprints[new_index],
+                }),
+                None => diff.added.push(new_index),
+            }
+        }
+        diff.removed = (0..old.chapters.len()).filter(|&index| old_to_new[index].is_none()).collect();
+        diff
+    }
+
+    /// 获取内容发生变化的章节
+    ///
+    /// # Returns
+    ///
+    /// 返回内容发生变化的章节匹配
+    pub fn changed(&self) -> impl Iterator<Item = &ChapterMatch> {
+        self.matches.iter().filter(|chapter_match| chapter_match.changed)
+    }
+
+    /// 判断两个版本是否相同
+    ///
+    /// # Returns
+    ///
+    /// 没有新增、删除或修改的章节时返回true
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed().next().is_none()
+    }
+
+    /// 合并两个版本
+    ///
+    /// 合并后的文档使用新版本的元数据、章节顺序和内容，已有章节沿用旧版本的ID，
+    /// 新增章节的ID从旧版本的最大序号之后继续编号，因此按章节ID保存的数据不会错位
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - 旧版本的文档
+    /// * `new` - 新版本的文档
+    ///
+    /// # Returns
+    ///
+    /// 返回合并后的文档
+    pub fn merge(&self, old: &DocumentModel, new: &DocumentModel) -> DocumentModel {
+        let mut merged = new.clone();
+        for chapter_match in &self.matches {
+            merged.chapters[chapter_match.new_index].id = old.chapters[chapter_match.old_index].id.clone();
+        }
+
+        let used: HashSet<Cow<'static, str>> = self.matches
+            .iter()
+            .map(|chapter_match| old.chapters[chapter_match.old_index].id.clone())
+            .collect();
+        let mut next_id = old.chapters
+            .iter()
+            .filter_map(|chapter| chapter.id.strip_prefix("chapter_")?.parse::<usize>().ok())
+            .max()
+            .map_or(0, |max| max + 1);
+        for &new_index in &self.added {
+            let id = loop {
+                let id = format!("chapter_{}", next_id);
+                next_id += 1;
+                if !used.contains(id.as_str()) {
+                    break id;
+                }
+            };
+            merged.chapters[new_index].id = Cow::Owned(id);
+        }
+        merged
+    }
+
+    /// 把旧版本中的位置迁移到新版本
+    ///
+    /// 未修改的章节保持原位置；修改过的章节按内容相同的段落定位，找不到时按比例定位到段落开头；
+    /// 被删除的章节定位到其后第一个保留章节的开头
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - 旧版本的文档
+    /// * `new` - 新版本的文档或合并后的文档
+    /// * `position` - 旧版本中的位置
+    ///
+    /// # Returns
+    ///
+    /// 返回新版本中的位置，位置所在的章节不存在或新版本没有可定位的章节时返回None
+    pub fn remap_position(&self, old: &DocumentModel, new: &DocumentModel, position: &ReadingPosition) -> Option<ReadingPosition> {
+        let old_index = old.chapters.iter().position(|chapter| chapter.id == position.chapter_id)?;
+
+        let chapter_match = match self.matches.iter().find(|chapter_match| chapter_match.old_index == old_index) {
+            Some(chapter_match) => chapter_match,
+            None => {
+                let next = self.matches
+                    .iter()
+                    .filter(|chapter_match| chapter_match.old_index > old_index)
+                    .min_by_key(|chapter_match| chapter_match.old_index)
+                    .map(|chapter_match| chapter_match.new_index)
+                    .unwrap_or(new.chapters.len().checked_sub(1)?);
+                return Some(ReadingPosition {
+                    chapter_id: new.chapters[next].id.clone(),
+                    block_index: 0,
+                    offset: 0,
+                });
+            }
+        };
+
+        let old_chapter = &old.chapters[chapter_match.old_index];
+        let new_chapter = &new.chapters[chapter_match.new_index];
+        let chapter_id = new_chapter.id.clone();
+        // 指纹忽略空白和段落边界，未修改的章节也要确认段落本身相同，偏移才有效
+        let old_block = old_chapter.content.get(position.block_index);
+        let new_block = new_chapter.content.get(position.block_index);
+        if let (Some(old_block), Some(new_block)) = (old_block, new_block) {
+            if old_block.content == new_block.content {
+                return Some(ReadingPosition { chapter_id, ..position.clone() });
+            }
+        }
+
+        // 按比例估计的段落位置，用于在多个相同段落中选择最近的一个
+        let expected = match old_chapter.content.len() {
+            0 => 0,
+            len => position.block_index.min(len - 1) * new_chapter.content.len() / len,
+        };
+        let same_block = old_chapter.content.get(position.block_index).and_then(|old_block| {
+            new_chapter.content
+                .iter()
+                .enumerate()
+                .filter(|(_, block)| block.content == old_block.content)
+                .map(|(index, _)| index)
+                .min_by_key(|&index| index.abs_diff(expected))
+        });
+
+        Some(match same_block {
+            Some(block_index) => ReadingPosition { chapter_id, block_index, offset: position.offset },
+            None if chapter_match.changed => ReadingPosition {
+                chapter_id,
+                block_index: expected.min(new_chapter.content.len().saturating_sub(1)),
+                offset: 0,
+            },
+            // 只有空白或段落划分不同时保留大致的段内位置，偏移收回到段落内的字符边界
+            None => {
+                let block_index = position.block_index.min(new_chapter.content.len().saturating_sub(1));
+                let offset = new_chapter.content.get(block_index).map_or(0, |block| {
+                    let mut offset = position.offset.min(block.content.len());
+                    while !block.content.is_char_boundary(offset) {
+                        offset -= 1;
+                    }
+                    offset
+                });
+                ReadingPosition { chapter_id, block_index, offset }
+            }
+        })
+    }
+}
+
+/// 按键匹配尚未匹配的章节
+///
+/// 键相同的章节按出现顺序一一对应
+fn match_by_key(
+    old_to_new: &mut [Option<usize>],
+    new_to_old: &mut [Option<usize>],
+    old_key: impl Fn(usize) -> String,
+    new_key: impl Fn(usize) -> String,
+) {
+    let mut candidates: HashMap<String, Vec<usize>> = HashMap::new();
+    for new_index in (0..new_to_old.len()).rev() {
+        if new_to_old[new_index].is_none() {
+            candidates.entry(new_key(new_index)).or_default().push(new_index);
+        }
+    }
+    for (old_index, matched) in old_to_new.iter_mut().enumerate() {
+        if matched.is_some() {
+            continue;
+        }
+        if let Some(new_index) = candidates.get_mut(&old_key(old_index)).and_then(Vec::pop) {
+            *matched = Some(new_index);
+            new_to_old[new_index] = Some(old_index);
+        }
+    }
+}
+
+/// 按位置匹配已匹配章节之间剩余的章节
+///
+/// 只处理前后都有已匹配章节、且两个版本中剩余章节数量相同的空隙，
+/// 如两个版本都把第5章改了标题和内容。文档开头和末尾的章节以及数量不同的空隙保持未匹配，
+/// 避免把无关的章节当作同一章
+fn match_in_gaps(old_to_new: &mut [Option<usize>], new_to_old: &mut [Option<usize>]) {
+    let anchors: Vec<(usize, usize)> = old_to_new
+        .iter()
+        .enumerate()
+        .filter_map(|(old_index, new_index)| Some((old_index, (*new_index)?)))
+        .collect();
+    for pair in anchors.windows(2) {
+        let ((old_start, new_start), (old_end, new_end)) = (pair[0], pair[1]);
+        // 前后顺序颠倒的锚点之间没有可以对应的空隙
+        if new_end <= new_start {
+            continue;
+        }
+        let old_gap: Vec<usize> = (old_start + 1..old_end).filter(|&index| old_to_new[index].is_none()).collect();
+        let new_gap: Vec<usize> = (new_start + 1..new_end).filter(|&index| new_to_old[index].is_none()).collect();
+        if old_gap.len() != new_gap.len() {
+            continue;
+        }
+        for (old_index, new_index) in old_gap.into_iter().zip(new_gap) {
+            old_to_new[old_index] = Some(new_index);
+            new_to_old[new_index] = Some(old_index);
+        }
+    }
+}
+
+/// 生成按标题匹配章节时使用的键
+///
+/// 优先使用章节序号，使 `第十章` 和 `第10章` 可以对应
+fn title_key(chapter: &Chapter) -> String {
+    match chapter.number {
+        Some(number) => format!("#{}", number),
+        None => chapter.title.chars().filter(|c| !c.is_whitespace()).collect(),
+    }
+}
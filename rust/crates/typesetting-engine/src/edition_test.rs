@@ -0,0 +1,156 @@
+//! 版本合并单元测试
+//!
+//! 测试版本比较、合并和位置迁移功能的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::edition::*;
+    use crate::parser::ParserEngine;
+
+    /// 生成包含指定章节的小说文本
+    fn novel(chapters: &[(&str, &str)]) -> String {
+        chapters
+            .iter()
+            .map(|(title, body)| format!("{}\n{}\n", title, body))
+            .collect()
+    }
+
+    /// 测试新增、删除和修改章节的识别
+    #[test]
+    fn test_edition_diff() {
+        let parser = ParserEngine::new();
+        let old = parser.parse_txt(&novel(&[
+            ("第一章 青杜", "陆江仙睁开眼。\n\n山下传来钟声。"),
+            ("第二章 请假条", "今天停更一天。"),
+            ("第三章 镜中", "古镜微微发亮。"),
+        ]));
+        let new = parser.parse_txt(&novel(&[
+            ("第一章 青杜", "陆江仙睁开眼。\n山下传来钟声。"),
+            ("第二章 符箓", "陆长湖画出一张符箓。"),
+            ("第三章 镜中", "古镜微微发亮，照出一道人影。"),
+            ("第四章 新篇", "新的一天开始了。"),
+        ]));
+
+        let diff = EditionDiff::compute(&old, &new);
+        // 只改变了分段方式的章节不算修改
+        assert_eq!(diff.matches[0], ChapterMatch { old_index: 0, new_index: 0, changed: false });
+        // 序号相同的章节被视为同一章的修改
+        assert_eq!(diff.matches[1], ChapterMatch { old_index: 1, new_index: 1, changed: true });
+        assert_eq!(diff.changed().count(), 2);
+        assert_eq!(diff.added, vec![3]);
+        assert!(diff.removed.is_empty());
+        assert!(!diff.is_unchanged());
+        assert!(EditionDiff::compute(&old, &old).is_unchanged());
+    }
+
+    /// 测试章节增删后按内容匹配，合并文档沿用旧ID
+    #[test]
+    fn test_edition_merge() {
+        let parser = ParserEngine::new();
+        let old = parser.parse_txt(&novel(&[
+            ("第一章 楔子", "很久以前。"),
+            ("第二章 青杜", "陆江仙睁开眼。"),
+            ("第三章 镜中", "古镜微微发亮。"),
+        ]));
+        let new = parser.parse_txt(&novel(&[
+            ("第二章 青杜", "陆江仙睁开眼。"),
+            ("第三章 镜中", "古镜微微发亮。"),
+            ("第四章 符箓", "陆长湖画出一张符箓。"),
+            ("第五章 新篇", "新的一天开始了。"),
+        ]));
+
+        let diff = EditionDiff::compute(&old, &new);
+        assert_eq!(diff.removed, vec![0]);
+        assert_eq!(diff.added, vec![2, 3]);
+        assert_eq!(diff.changed().count(), 0);
+
+        // 新版本中的ID整体前移，合并后已有章节仍使用旧ID，新增章节继续编号
+        let merged = diff.merge(&old, &new);
+        let ids: Vec<&str> = merged.chapters.iter().map(|chapter| chapter.id.as_ref()).collect();
+        assert_eq!(ids, vec!["chapter_1", "chapter_2", "chapter_3", "chapter_4"]);
+        assert_eq!(merged.chapters[2].title, "第四章 符箓");
+    }
+
+    /// 测试只在已匹配章节之间按位置对应剩余章节
+    #[test]
+    fn test_edition_gap_matching() {
+        let parser = ParserEngine::new();
+        let old = parser.parse_txt(&novel(&[
+            ("第一章 青杜", "陆江仙睁开眼。"),
+            ("第二章 楔子", "很久以前。"),
+            ("第三章 镜中", "古镜微微发亮。"),
+            ("第四章 后记", "全书完。"),
+        ]));
+        let new = parser.parse_txt(&novel(&[
+            ("第一章 青杜", "陆江仙睁开眼。"),
+            ("第九章 序章", "很久很久以前。"),
+            ("第三章 镜中", "古镜微微发亮。"),
+            ("第十章 番外", "月华如水。"),
+        ]));
+
+        let diff = EditionDiff::compute(&old, &new);
+        // 前后章节都已匹配的空隙中，序号和内容都改变的章节按位置对应
+        assert!(diff.matches.contains(&ChapterMatch { old_index: 1, new_index: 1, changed: true }));
+        // 末尾无关的章节即使ID相同也不对应
+        assert_eq!(diff.removed, vec![3]);
+        assert_eq!(diff.added, vec![3]);
+    }
+
+    /// 测试阅读位置迁移
+    #[test]
+    fn test_remap_position() {
+        let parser = ParserEngine::new();
+        let old = parser.parse_txt(&novel(&[
+            ("第一章 楔子", "很久以前。"),
+            ("第二章 青杜", "陆江仙睁开眼。\n\n山下传来钟声。\n\n他起身出门。"),
+        ]));
+        let new = parser.parse_txt(&novel(&[
+            ("第二章 青杜", "陆江仙睁开眼。\n\n天色微明。\n\n山下传来钟声。\n\n他起身出门。"),
+        ]));
+        let diff = EditionDiff::compute(&old, &new);
+        let merged = diff.merge(&old, &new);
+
+        // 修改过的章节按相同段落定位，保留段内偏移
+        let position = ReadingPosition { chapter_id: "chapter_1".into(), block_index: 1, offset: 6 };
+        assert_eq!(
+            diff.remap_position(&old, &merged, &position),
+            Some(ReadingPosition { chapter_id: "chapter_1".into(), block_index: 2, offset: 6 })
+        );
+
+        // 被删除章节中的位置移到其后第一个保留章节的开头
+        let position = ReadingPosition { chapter_id: "chapter_0".into(), block_index: 0, offset: 3 };
+        assert_eq!(
+            diff.remap_position(&old, &merged, &position),
+            Some(ReadingPosition { chapter_id: "chapter_1".into(), block_index: 0, offset: 0 })
+        );
+
+        let position = ReadingPosition { chapter_id: "chapter_9".into(), block_index: 0, offset: 0 };
+        assert_eq!(diff.remap_position(&old, &merged, &position), None);
+    }
+
+    /// 测试只有空白或段落划分变化的章节中的位置迁移
+    #[test]
+    fn test_remap_position_unchanged_chapter() {
+        let parser = ParserEngine::new();
+        let text = novel(&[("第一章 青杜", "陆江仙睁开眼。山下\n\n传来钟声。\n\n他起身出门。")]);
+        let old = parser.parse_txt(&text);
+        let mut new = parser.parse_txt(&text);
+
+        // 段落中插入空白，旧偏移落在 `。` 的中间
+        new.chapters[0].content[2].content = "他起身 出门。".into();
+        // 文字跨越段落边界移动，旧偏移超出段落末尾
+        new.chapters[0].content[0].content = "陆江仙睁开眼。".into();
+        new.chapters[0].content[1].content = "山下传来钟声。".into();
+
+        let diff = EditionDiff::compute(&old, &new);
+        assert!(!diff.matches[0].changed);
+
+        for (block_index, offset) in [(2, 18), (0, 27)] {
+            let position = ReadingPosition { chapter_id: "chapter_0".into(), block_index, offset };
+            let remapped = diff.remap_position(&old, &new, &position).unwrap();
+            let block = &new.chapters[0].content[remapped.block_index].content;
+            assert!(remapped.offset <= block.len());
+            assert!(block.is_char_boundary(remapped.offset));
+        }
+    }
+}
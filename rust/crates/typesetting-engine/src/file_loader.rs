@@ -3,10 +3,12 @@
 //! 负责从文件系统加载多种格式的文件，并处理不同的字符编码
 
 use crate::prelude::*;
+use crate::format_registry::ParserRegistry;
 use std::path::Path;
+use std::sync::Arc;
 
 /// 支持的文件格式
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FileFormat {
     /// 纯文本文件
     Txt,
@@ -15,20 +17,68 @@ pub enum FileFormat {
     Other(String),
 }
 
+impl FileFormat {
+    /// 获取格式对应的文件扩展名
+    /// 
+    /// # Returns
+    /// 
+    /// 返回小写的文件扩展名（不含点）
+    pub fn extension(&self) -> &str {
+        match self {
+            FileFormat::Txt => "txt",
+            FileFormat::Other(ext) => ext,
+        }
+    }
+}
+
 /// 文件加载器
 /// 
 /// 提供跨平台的文件加载和编码处理功能，支持多种文件格式
-#[derive(Default)]
-pub struct FileLoader;
+pub struct FileLoader {
+    /// 解析器注册表，按文件格式选择解析器
+    registry: Arc<ParserRegistry>,
+}
+
+impl Default for FileLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl FileLoader {
     /// 创建一个新的文件加载器实例
     /// 
+    /// 使用只包含内置解析器的默认注册表
+    /// 
     /// # Returns
     /// 
     /// 返回一个新的FileLoader实例
     pub fn new() -> Self {
-        FileLoader
+        FileLoader {
+            registry: Arc::new(ParserRegistry::with_defaults()),
+        }
+    }
+
+    /// 创建一个使用指定解析器注册表的文件加载器实例
+    /// 
+    /// # Arguments
+    /// 
+    /// * `registry` - 解析器注册表
+    /// 
+    /// # Returns
+    /// 
+    /// 返回一个新的FileLoader实例
+    pub fn with_registry(registry: Arc<ParserRegistry>) -> Self {
+        FileLoader { registry }
+    }
+
+    /// 获取解析器注册表
+    /// 
+    /// # Returns
+    /// 
+    /// 返回注册表的Arc引用
+    pub fn registry(&self) -> Arc<ParserRegistry> {
+        self.registry.clone()
     }
 
     /// 检测文件格式
@@ -77,23 +127,36 @@ impl FileLoader {
         // 读取为字节以便进行编码检测
         let bytes = fs::read(file_path)?;
         
+        Ok(Self::decode_text(&bytes))
+    }
+
+    /// 将字节内容解码为字符串
+    /// 
+    /// 优先按UTF-8解码，失败时使用Latin-1编码作为后备
+    /// 
+    /// # Arguments
+    /// 
+    /// * `bytes` - 原始字节内容
+    /// 
+    /// # Returns
+    /// 
+    /// 返回解码后的UTF-8字符串
+    pub fn decode_text(bytes: &[u8]) -> String {
         // 首先尝试以UTF-8读取
-        match String::from_utf8(bytes.clone()) {
-            Ok(content) => {
-                Ok(content)
-            },
+        match std::str::from_utf8(bytes) {
+            Ok(content) => content.to_string(),
             Err(_) => {
                 // 如果UTF-8读取失败，使用Latin-1编码作为后备
                 // Latin-1编码是一种单字节编码，可以无损地表示所有256个字节值
-                let content = bytes.iter().map(|&b| b as char).collect();
-                Ok(content)
+                bytes.iter().map(|&b| b as char).collect()
             }
         }
     }
     
     /// 加载并解析文档
     /// 
-    /// 加载文件并将其解析为文档模型
+    /// 加载文件并将其解析为文档模型。先按扩展名在注册表中查找解析器，
+    /// 找不到时根据文件内容嗅探，仍然找不到时按纯文本解析
     /// 
     /// # Arguments
     /// 
@@ -107,26 +170,22 @@ impl FileLoader {
     /// 
     /// 当文件无法读取或解析时返回错误
     pub fn load_and_parse_document(&self, file_path: &str) -> Result<crate::DocumentModel> {
-        use crate::ParserEngine;
-        
         // 检测文件格式
         let format = Self::detect_format(file_path);
         
-        // 根据不同格式加载文件
-        let content = match format {
-            FileFormat::Txt => {
-                self.load_text_file(file_path)?
-            },
-            FileFormat::Other(_) => {
-                // 对于其他格式，暂时也使用文本加载方式
-                self.load_text_file(file_path)?
-            }
-        };
+        // 读取为字节，由解析器自行处理编码
+        let bytes = std::fs::read(file_path)?;
         
-        // 创建解析器并解析内容
-        let parser = ParserEngine::new();
-        let document = parser.parse_txt(&content);
+        // 根据格式选择解析器
+        let parser = self.registry
+            .parser_for_format(&format)
+            .or_else(|| self.registry.sniff(&bytes))
+            .or_else(|| self.registry.parser_for_format(&FileFormat::Txt))
+            .ok_or_else(|| anyhow::anyhow!("不支持的文件格式: {}", format.extension()))?;
         
+        // 导入器没有指定语言时自动识别
+        let mut document = parser.parse(&bytes)?;
+        crate::language::detect_document_language(&mut document);
         Ok(document)
     }
 }
\ No newline at end of file
@@ -0,0 +1,405 @@
+//! 字体读取
+//!
+//! 从TTF、OTF和TTC字体文件中读取排版所需的度量信息：字符到字形的映射（`cmap`）、
+//! 字形步进宽度（`hmtx`）以及行高相关的上伸、下伸和行距（`hhea`）。不负责字形轮廓和渲染
+
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// TTC字体集合的文件头标记
+const TTC_TAG: &[u8; 4] = b"ttcf";
+
+/// 字体
+///
+/// 字体集合（TTC）中的每一个字体需要分别读取
+#[derive(Debug, Clone)]
+pub struct Font {
+    /// 字体族名称，如 `Source Han Serif SC`
+    family: String,
+    /// 字体样式名称，如 `Bold`、`Italic`
+    subfamily: String,
+    /// 每个em的设计单位数
+    units_per_em: u16,
+    /// 上伸高度（设计单位）
+    ascender: i16,
+    /// 下伸高度（设计单位，通常为负数）
+    descender: i16,
+    /// 行距（设计单位）
+    line_gap: i16,
+    /// 每个字形的步进宽度（设计单位）
+    advances: Vec<u16>,
+    /// 字符到字形索引的映射
+    glyphs: HashMap<char, u16>,
+}
+
+impl Font {
+    /// 从字体文件内容中读取字体
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - TTF、OTF或TTC文件的内容
+    /// * `index` - 字体在TTC集合中的索引，单个字体文件使用0
+    ///
+    /// # Returns
+    ///
+    /// 返回读取出的字体
+    ///
+    /// # Errors
+    ///
+    /// 文件不是受支持的字体格式、缺少必要的表或表数据损坏时返回错误
+    pub fn from_bytes(data: &[u8], index: u32) -> Result<Self> {
+        let offset = face_offset(data, index)?;
+        let tables = read_table_directory(data, offset)?;
+        let table = |tag: &str| -> Result<&[u8]> {
+            tables.get(tag).copied().ok_or_else(|| anyhow::anyhow!("字体缺少 {} 表", tag))
+        };
+
+        let head = table("head")?;
+        let units_per_em = read_u16(head, 18)?;
+        if units_per_em == 0 {
+            anyhow::bail!("字体的 unitsPerEm 为0");
+        }
+
+        let hhea = table("hhea")?;
+        let ascender = read_i16(hhea, 4)?;
+        let descender = read_i16(hhea, 6)?;
+        let line_gap = read_i16(hhea, 8)?;
+        let metrics_count = read_u16(hhea, 34)? as usize;
+        let glyph_count = read_u16(table("maxp")?, 4)? as usize;
+
+        // 字形数多于度量数时，其余字形沿用最后一个步进宽度（等宽的CJK字体常见）
+        let hmtx = table("hmtx")?;
+        let mut advances = Vec::with_capacity(glyph_count.max(metrics_count));
+        for glyph in 0..metrics_count {
+            advances.push(read_u16(hmtx, glyph * 4)?);
+        }
+        let last = advances.last().copied().unwrap_or(units_per_em);
+        advances.resize(glyph_count.max(metrics_count), last);
+
+        let (family, subfamily) = tables.get("name").map_or((String::new(), String::new()), |name| read_names(name));
+
+        Ok(Font {
+            family,
+            subfamily,
+            units_per_em,
+            ascender,
+            descender,
+            line_gap,
+            advances,
+            glyphs: read_cmap(table("cmap")?)?,
+        })
+    }
+
+    /// 从文件读取字体
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 字体文件路径
+    /// * `index` - 字体在TTC集合中的索引，单个字体文件使用0
+    ///
+    /// # Returns
+    ///
+    /// 返回读取出的字体
+    ///
+    /// # Errors
+    ///
+    /// 文件无法读取或不是受支持的字体格式时返回错误
+    pub fn from_file(path: &str, index: u32) -> Result<Self> {
+        let data = std::fs::read(path)?;
+        Self::from_bytes(&data, index)
+    }
+
+    /// 获取字体文件中包含的字体数量
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - TTF、OTF或TTC文件的内容
+    ///
+    /// # Returns
+    ///
+    /// TTC集合返回其中的字体数量，单个字体文件返回1
+    pub fn face_count(data: &[u8]) -> u32 {
+        if data.starts_with(TTC_TAG) {
+            read_u32(data, 8).unwrap_or(0)
+        } else {
+            1
+        }
+    }
+
+    /// 获取字体族名称
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    /// 获取字体样式名称
+    pub fn subfamily(&self) -> &str {
+        &self.subfamily
+    }
+
+    /// 判断是否为粗体
+    pub fn is_bold(&self) -> bool {
+        let subfamily = self.subfamily.to_ascii_lowercase();
+        subfamily.contains("bold") || subfamily.contains("heavy") || subfamily.contains("black")
+    }
+
+    /// 判断是否为斜体
+    pub fn is_italic(&self) -> bool {
+        let subfamily = self.subfamily.to_ascii_lowercase();
+        subfamily.contains("italic") || subfamily.contains("oblique")
+    }
+
+    /// 判断字体是否包含字符的字形
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - 字符
+    ///
+    /// # Returns
+    ///
+    /// 字符映射到非 `.notdef` 字形时返回true
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.get(&c).is_some_and(|&glyph| glyph != 0)
+    }
+
+    /// 获取字符的步进宽度
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - 字符
+    /// * `font_size` - 字号（像素）
+    ///
+    /// # Returns
+    ///
+    /// 字体不包含该字符时返回None
+    pub fn advance(&self, c: char, font_size: f32) -> Option<f32> {
+        let glyph = *self.glyphs.get(&c).filter(|&&glyph| glyph != 0)?;
+        Some(self.glyph_advance(glyph, font_size))
+    }
+
+    /// 获取 `.notdef` 字形的步进宽度，用于所有字体都不包含的字符
+    ///
+    /// # Arguments
+    ///
+    /// * `font_size` - 字号（像素）
+    pub fn notdef_advance(&self, font_size: f32) -> f32 {
+        self.glyph_advance(0, font_size)
+    }
+
+    /// 获取行高：上伸、下伸和行距之和
+    ///
+    /// # Arguments
+    ///
+    /// * `font_size` - 字号（像素）
+    pub fn line_height(&self, font_size: f32) -> f32 {
+        let units = self.ascender as f32 - self.descender as f32 + self.line_gap as f32;
+        units * font_size / self.units_per_em as f32
+    }
+
+    /// 获取上伸高度
+    ///
+    /// # Arguments
+    ///
+    /// * `font_size` - 字号（像素）
+    pub fn ascent(&self, font_size: f32) -> f32 {
+        self.ascender as f32 * font_size / self.units_per_em as f32
+    }
+
+    /// 获取下伸高度（正数）
+    ///
+    /// # Arguments
+    ///
+    /// * `font_size` - 字号（像素）
+    pub fn descent(&self, font_size: f32) -> f32 {
+        -(self.descender as f32) * font_size / self.units_per_em as f32
+    }
+
+    /// 按字形索引获取步进宽度
+    fn glyph_advance(&self, glyph: u16, font_size: f32) -> f32 {
+        let units = self.advances.get(glyph as usize).copied().unwrap_or(self.units_per_em);
+        units as f32 * font_size / self.units_per_em as f32
+    }
+}
+
+/// 获取字体在文件中的偏移，TTC集合按索引查找
+fn face_offset(data: &[u8], index: u32) -> Result<usize> {
+    if !data.starts_with(TTC_TAG) {
+        if index != 0 {
+            anyhow::bail!("单个字体文件只包含索引为0的字体");
+        }
+        return Ok(0);
+    }
+    let count = read_u32(data, 8)?;
+    if index >= count {
+        anyhow::bail!("字体集合只包含 {} 个字体", count);
+    }
+    Ok(read_u32(data, 12 + index as usize * 4)? as usize)
+}
+
+/// 读取表目录
+fn read_table_directory(data: &[u8], offset: usize) -> Result<HashMap<String, &[u8]>> {
+    let version = read_u32(data, offset)?;
+    // TrueType轮廓（0x00010000、`true`）或CFF轮廓（`OTTO`）
+    if !matches!(version, 0x0001_0000 | 0x7472_7565 | 0x4F54_544F) {
+        anyhow::bail!("不支持的字体格式");
+    }
+
+    let count = read_u16(data, offset + 4)? as usize;
+    let mut tables = HashMap::with_capacity(count);
+    for index in 0..count {
+        let record = offset + 12 + index * 16;
+        let tag = data.get(record..record + 4).ok_or_else(truncated)?;
+        let start = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        let table = data.get(start..start + length).ok_or_else(truncated)?;
+        tables.insert(String::from_utf8_lossy(tag).into_owned(), table);
+    }
+    Ok(tables)
+}
+
+/// 读取字符映射表
+///
+/// 优先使用覆盖完整Unicode的格式12子表，其次是只覆盖基本多文种平面的格式4子表
+pub(crate) fn read_cmap(cmap: &[u8]) -> Result<HashMap<char, u16>> {
+    let count = read_u16(cmap, 2)? as usize;
+    let mut best: Option<(u8, usize)> = None;
+    for index in 0..count {
+        let record = 4 + index * 8;
+        let platform = read_u16(cmap, record)?;
+        let encoding = read_u16(cmap, record + 2)?;
+        let offset = read_u32(cmap, record + 4)? as usize;
+        let format = read_u16(cmap, offset)?;
+        let priority = match (platform, encoding, format) {
+            (3, 10, 12) | (0, _, 12) => 2,
+            (3, 1, 4) | (0, _, 4) => 1,
+            _ => continue,
+        };
+        if best.is_none_or(|(best_priority, _)| priority > best_priority) {
+            best = Some((priority, offset));
+        }
+    }
+
+    let (_, offset) = best.ok_or_else(|| anyhow::anyhow!("字体缺少Unicode字符映射"))?;
+    let subtable = cmap.get(offset..).ok_or_else(truncated)?;
+    let mut glyphs = HashMap::new();
+    if read_u16(subtable, 0)? == 12 {
+        let groups = read_u32(subtable, 12)? as usize;
+        // 分组数超出子表的长度时数据不完整，避免按损坏的分组数循环
+        if groups.checked_mul(12).and_then(|size| size.checked_add(16)).is_none_or(|size| size > subtable.len()) {
+            return Err(truncated());
+        }
+        for group in 0..groups {
+            let record = 16 + group * 12;
+            let start = read_u32(subtable, record)?;
+            // 超出Unicode范围的码位没有对应的字符
+            let end = read_u32(subtable, record + 4)?.min(char::MAX as u32);
+            let start_glyph = read_u32(subtable, record + 8)?;
+            if end < start {
+                continue;
+            }
+            for code in start..=end {
+                // 字形编号超出u16范围时，之后的码位也都超出
+                let Some(glyph) = start_glyph.checked_add(code - start).and_then(|glyph| u16::try_from(glyph).ok()) else {
+                    break;
+                };
+                if let Some(c) = char::from_u32(code) {
+                    glyphs.insert(c, glyph);
+                }
+            }
+        }
+    } else {
+        let segments = read_u16(subtable, 6)? as usize / 2;
+        let end_codes = 14;
+        let start_codes = end_codes + segments * 2 + 2;
+        let deltas = start_codes + segments * 2;
+        let range_offsets = deltas + segments * 2;
+        for segment in 0..segments {
+            let end = read_u16(subtable, end_codes + segment * 2)?;
+            let start = read_u16(subtable, start_codes + segment * 2)?;
+            let delta = read_u16(subtable, deltas + segment * 2)?;
+            let range_offset_position = range_offsets + segment * 2;
+            let range_offset = read_u16(subtable, range_offset_position)? as usize;
+            for code in start..=end {
+                if code == 0xFFFF {
+                    break;
+                }
+                let glyph = if range_offset == 0 {
+                    code.wrapping_add(delta)
+                } else {
+                    // idRangeOffset 是相对于自身位置的偏移
+                    let position = range_offset_position + range_offset + (code - start) as usize * 2;
+                    match read_u16(subtable, position)? {
+                        0 => 0,
+                        glyph => glyph.wrapping_add(delta),
+                    }
+                };
+                if let Some(c) = char::from_u32(code as u32) {
+                    glyphs.insert(c, glyph);
+                }
+            }
+        }
+    }
+    Ok(glyphs)
+}
+
+/// 读取字体族名称和样式名称
+///
+/// 优先使用排版用名称（名称ID 16、17），其次是旧式名称（名称ID 1、2），
+/// 优先选择Windows平台的英文名称
+fn read_names(name: &[u8]) -> (String, String) {
+    let read = || -> Result<(String, String)> {
+        let count = read_u16(name, 2)? as usize;
+        let storage = read_u16(name, 4)? as usize;
+        // (名称ID, 优先级) -> 名称
+        let mut names: HashMap<u16, (u8, String)> = HashMap::new();
+        for index in 0..count {
+            let record = 6 + index * 12;
+            let platform = read_u16(name, record)?;
+            let language = read_u16(name, record + 4)?;
+            let name_id = read_u16(name, record + 6)?;
+            if !matches!(name_id, 1 | 2 | 16 | 17) {
+                continue;
+            }
+            let length = read_u16(name, record + 8)? as usize;
+            let offset = storage + read_u16(name, record + 10)? as usize;
+            let bytes = name.get(offset..offset + length).ok_or_else(truncated)?;
+            let (priority, text) = match platform {
+                0 | 3 => {
+                    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+                    (if language == 0x0409 { 3 } else { 2 }, String::from_utf16_lossy(&units))
+                }
+                1 => (1, bytes.iter().map(|&byte| byte as char).collect()),
+                _ => continue,
+            };
+            if names.get(&name_id).is_none_or(|(best, _)| priority > *best) {
+                names.insert(name_id, (priority, text));
+            }
+        }
+        let mut take = |preferred: u16, fallback: u16| {
+            names.remove(&preferred).or_else(|| names.remove(&fallback)).map(|(_, text)| text).unwrap_or_default()
+        };
+        Ok((take(16, 1), take(17, 2)))
+    };
+    read().unwrap_or_default()
+}
+
+/// 字体数据不完整时的错误
+fn truncated() -> anyhow::Error {
+    anyhow::anyhow!("字体文件数据不完整")
+}
+
+/// 读取大端序u16
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data.get(offset..offset + 2).ok_or_else(truncated)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// 读取大端序i16
+fn read_i16(data: &[u8], offset: usize) -> Result<i16> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+/// 读取大端序u32
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data.get(offset..offset + 4).ok_or_else(truncated)?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -0,0 +1,215 @@
+//! 脚注提取
+//! 
+//! 识别正文中的脚注标记（如 `[1]`、`①`）与章节末尾的脚注正文，以及行内的 `（注：…）` 注释，
+//! 将它们转换为带锚点的脚注块
+
+use crate::document::{Chapter, ContentBlock, ContentBlockType, FootnoteRef, TextStyle};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use regex::{Captures, Regex};
+
+lazy_static::lazy_static! {
+    /// 正文中的脚注标记
+    static ref REF_REGEX: Regex = Regex::new(r"\[(\d{1,3})\]|［(\d{1,3})］|([①-⑳])").unwrap();
+    /// 章节末尾的脚注正文行，如 `[1]…`、`①…`、`注1：…`
+    static ref BODY_REGEX: Regex = Regex::new(
+        r"^\s*(?:注\s*(?:\[(\d{1,3})\]|［(\d{1,3})］|([①-⑳])|(\d{1,3}))|\[(\d{1,3})\]|［(\d{1,3})］|([①-⑳]))\s*[：:、.．]?\s*(.+)$"
+    ).unwrap();
+    /// 脚注区的标题行，如 `注释：`
+    static ref HEADER_REGEX: Regex = Regex::new(r"^\s*(?:注释|注解|注|译注)\s*[：:]?\s*$").unwrap();
+    /// 行内注释，如 `（注：…）`
+    static ref INLINE_REGEX: Regex = Regex::new(r"[（(]\s*注\s*[：:]\s*([^（()）]+?)\s*[）)]").unwrap();
+}
+
+/// 章节末尾的一条脚注正文
+struct FootnoteBody {
+    /// 规范化后的标号
+    label: String,
+    /// 脚注文本
+    text: String,
+}
+
+/// 提取章节中的脚注
+/// 
+/// 章节末尾只由脚注正文组成的段落会被移除，并在章节末尾生成对应的脚注块；
+/// 只有当正文中至少引用了其中一条脚注时才会进行转换，以免误伤普通的编号列表。
+/// 行内注释会从正文中移除，同样生成脚注块。
+/// 章节末尾的作者的话不属于正文，脚注区在作者的话之前查找，脚注块也排在作者的话之前
+/// 
+/// # Arguments
+/// 
+/// * `chapter` - 需要处理的章节
+pub(crate) fn extract_footnotes(chapter: &mut Chapter) {
+    let styles = match chapter.content.first() {
+        Some(block) => block.styles.clone(),
+        None => return,
+    };
+    let mut footnotes = Vec::new();
+    let mut refs = Vec::new();
+
+    // 暂时移开章节末尾的作者的话，整章都是作者的话时不拆分
+    let note_start = chapter.content.iter().rposition(|block| block.block_type != ContentBlockType::AuthorNote).map_or(0, |index| index + 1);
+    let trailing_notes = if note_start > 0 { chapter.content.split_off(note_start) } else { Vec::new() };
+
+    // 章节末尾的脚注正文，只有被正文引用时才视为脚注
+    let (body_start, mut bodies) = trailing_bodies(&chapter.content);
+    let labels: HashSet<String> = bodies.iter().map(|body| body.label.clone()).collect();
+    let referenced = chapter.content[..body_start]
+        .iter()
+        .any(|block| REF_REGEX.captures_iter(&block.content).any(|caps| labels.contains(&marker_label(&caps))));
+
+    let labels = if referenced {
+        chapter.content.truncate(body_start);
+        labels
+    } else {
+        bodies.clear();
+        HashSet::new()
+    };
+
+    let mut inline_count = 0;
+    for (block_index, block) in chapter.content.iter_mut().enumerate() {
+        if block.block_type != ContentBlockType::Text {
+            continue;
+        }
+
+        // 提取行内注释，引用位置为注释被移除处
+        if INLINE_REGEX.is_match(&block.content) {
+            let mut text = String::with_capacity(block.content.len());
+            let mut last_end = 0;
+            for caps in INLINE_REGEX.captures_iter(&block.content) {
+                let whole = caps.get(0).unwrap();
+                text.push_str(&block.content[last_end..whole.start()]);
+                last_end = whole.end();
+
+                inline_count += 1;
+                let anchor: Cow<'static, str> = Cow::Owned(format!("{}-note-{}", chapter.id, inline_count));
+                refs.push(FootnoteRef {
+                    anchor: anchor.clone(),
+                    block_index,
+                    range: text.len()..text.len(),
+                });
+                footnotes.push(footnote_block(anchor, caps[1].to_string(), &styles));
+            }
+            text.push_str(&block.content[last_end..]);
+            block.content = Cow::Owned(text);
+        }
+
+        // 记录指向章节末尾脚注的标记
+        if !labels.is_empty() {
+            for caps in REF_REGEX.captures_iter(&block.content) {
+                let label = marker_label(&caps);
+                if labels.contains(&label) {
+                    refs.push(FootnoteRef {
+                        anchor: Cow::Owned(footnote_anchor(&chapter.id, &label)),
+                        block_index,
+                        range: caps.get(0).unwrap().range(),
+                    });
+                }
+            }
+        }
+    }
+
+    if footnotes.is_empty() && bodies.is_empty() {
+        chapter.content.extend(trailing_notes);
+        return;
+    }
+
+    // 行内注释按出现顺序排在末尾脚注之后
+    let mut blocks: Vec<ContentBlock> = bodies
+        .into_iter()
+        .map(|body| footnote_block(Cow::Owned(footnote_anchor(&chapter.id, &body.label)), body.text, &styles))
+        .collect();
+    blocks.append(&mut footnotes);
+
+    refs.sort_by_key(|footnote_ref| (footnote_ref.block_index, footnote_ref.range.start));
+    chapter.content.extend(blocks);
+    chapter.content.extend(trailing_notes);
+    chapter.footnote_refs = refs;
+}
+
+/// 找出章节末尾只由脚注正文组成的段落
+/// 
+/// # Arguments
+/// 
+/// * `blocks` - 章节内容块
+/// 
+/// # Returns
+/// 
+/// 返回脚注区的起始块索引和按顺序排列的脚注正文；没有脚注区时起始索引等于块数
+fn trailing_bodies(blocks: &[ContentBlock]) -> (usize, Vec<FootnoteBody>) {
+    let mut start = blocks.len();
+    let mut bodies = Vec::new();
+
+    // 至少保留一个正文块
+    while start > 1 {
+        let block = &blocks[start - 1];
+        if block.block_type != ContentBlockType::Text {
+            break;
+        }
+
+        let mut block_bodies = Vec::new();
+        let mut is_note_block = true;
+        for line in block.content.lines().filter(|line| !line.trim().is_empty()) {
+            if let Some(caps) = BODY_REGEX.captures(line) {
+                let label = (1..=7)
+                    .find_map(|group| caps.get(group))
+                    .map(|m| normalize_label(m.as_str()))
+                    .unwrap_or_default();
+                block_bodies.push(FootnoteBody {
+                    label,
+                    text: caps[8].trim().to_string(),
+                });
+            } else if !HEADER_REGEX.is_match(line) {
+                is_note_block = false;
+                break;
+            }
+        }
+
+        if !is_note_block || block_bodies.is_empty() {
+            break;
+        }
+        block_bodies.append(&mut bodies);
+        bodies = block_bodies;
+        start -= 1;
+    }
+
+    (start, bodies)
+}
+
+/// 获取正文脚注标记的规范化标号
+fn marker_label(caps: &Captures) -> String {
+    (1..=3)
+        .find_map(|group| caps.get(group))
+        .map(|m| normalize_label(m.as_str()))
+        .unwrap_or_default()
+}
+
+/// 规范化脚注标号
+/// 
+/// 带圈数字转换为阿拉伯数字，使 `①` 与 `[1]` 对应同一条脚注
+fn normalize_label(label: &str) -> String {
+    let mut chars = label.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c @ '①'..='⑳'), None) => (c as u32 - '①' as u32 + 1).to_string(),
+        _ => match label.trim_start_matches('0') {
+            "" => "0".to_string(),
+            trimmed => trimmed.to_string(),
+        },
+    }
+}
+
+/// 生成脚注锚点
+fn footnote_anchor(chapter_id: &str, label: &str) -> String {
+    format!("{}-fn-{}", chapter_id, label)
+}
+
+/// 创建脚注块
+fn footnote_block(anchor: Cow<'static, str>, text: String, styles: &TextStyle) -> ContentBlock {
+    ContentBlock {
+        block_type: ContentBlockType::Footnote { anchor },
+        content: Cow::Owned(text),
+        styles: styles.clone(),
+        metrics: None,
+        language: None,
+    }
+}
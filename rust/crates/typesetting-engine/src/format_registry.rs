@@ -0,0 +1,251 @@
+//! 文档解析器注册表
+//! 
+//! 定义统一的文档解析器接口，并按文件格式和MIME类型管理已注册的解析器。
+//! 下游crate可以实现 [`DocumentParser`] 并注册到 [`ParserRegistry`] 中，以支持自定义格式。
+
+use crate::prelude::*;
+use crate::document::DocumentModel;
+use crate::file_loader::{FileFormat, FileLoader};
+use crate::parser::ParserEngine;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 文档解析器
+/// 
+/// 将某种格式的原始字节内容解析为文档模型
+pub trait DocumentParser: Send + Sync {
+    /// 解析器名称
+    fn name(&self) -> &str;
+
+    /// 解析器支持的文件格式
+    fn formats(&self) -> Vec<FileFormat>;
+
+    /// 解析器支持的MIME类型
+    fn mime_types(&self) -> Vec<&str>;
+
+    /// 嗅探内容格式
+    /// 
+    /// # Arguments
+    /// 
+    /// * `bytes` - 文件开头的字节内容
+    /// 
+    /// # Returns
+    /// 
+    /// 如果内容看起来是本解析器支持的格式则返回true
+    fn sniff(&self, bytes: &[u8]) -> bool;
+
+    /// 解析整个文档
+    /// 
+    /// 格式本身声明了语言时（如EPUB的 `dc:language`）应写入文档元数据或内容块，
+    /// 加载器只会为没有指定语言的部分自动识别
+    /// 
+    /// # Arguments
+    /// 
+    /// * `bytes` - 文件的字节内容
+    /// 
+    /// # Returns
+    /// 
+    /// 返回解析后的文档模型
+    /// 
+    /// # Errors
+    /// 
+    /// 当内容无法解析时返回错误
+    fn parse(&self, bytes: &[u8]) -> Result<DocumentModel>;
+
+    /// 解析指定章节
+    /// 
+    /// # Arguments
+    /// 
+    /// * `bytes` - 文件的字节内容
+    /// * `chapter_index` - 要解析的章节索引
+    /// 
+    /// # Returns
+    /// 
+    /// 返回只包含指定章节的文档模型，索引超出范围时章节列表为空
+    /// 
+    /// # Errors
+    /// 
+    /// 当内容无法解析时返回错误
+    fn parse_chapter(&self, bytes: &[u8], chapter_index: usize) -> Result<DocumentModel>;
+}
+
+/// 纯文本解析器
+/// 
+/// 内置的txt格式解析器，基于 [`ParserEngine`] 实现
+#[derive(Default)]
+pub struct TxtParser;
+
+impl TxtParser {
+    /// 创建一个新的纯文本解析器实例
+    /// 
+    /// # Returns
+    /// 
+    /// 返回一个新的TxtParser实例
+    pub fn new() -> Self {
+        TxtParser
+    }
+}
+
+impl DocumentParser for TxtParser {
+    fn name(&self) -> &str {
+        "Plain Text"
+    }
+
+    fn formats(&self) -> Vec<FileFormat> {
+        vec![FileFormat::Txt]
+    }
+
+    fn mime_types(&self) -> Vec<&str> {
+        vec!["text/plain"]
+    }
+
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        // 文本文件中不应出现NUL字节
+        let head = &bytes[..bytes.len().min(4096)];
+        !head.contains(&0)
+    }
+
+    fn parse(&self, bytes: &[u8]) -> Result<DocumentModel> {
+        let content = FileLoader::decode_text(bytes);
+        Ok(ParserEngine::new().parse_txt(&content))
+    }
+
+    fn parse_chapter(&self, bytes: &[u8], chapter_index: usize) -> Result<DocumentModel> {
+        let content = FileLoader::decode_text(bytes);
+        Ok(ParserEngine::new().parse_txt_chapter(&content, chapter_index))
+    }
+}
+
+/// 支持的格式信息
+/// 
+/// 用于向上层列出注册表中可用的格式
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupportedFormat {
+    /// 解析器名称
+    pub name: String,
+    /// 文件格式
+    pub format: FileFormat,
+    /// MIME类型列表
+    pub mime_types: Vec<String>,
+}
+
+/// 解析器注册表
+/// 
+/// 按文件格式和MIME类型查找解析器，后注册的解析器会覆盖先注册的同格式解析器
+#[derive(Default)]
+pub struct ParserRegistry {
+    /// 已注册的解析器，按注册顺序排列
+    parsers: Vec<Arc<dyn DocumentParser>>,
+    /// 文件格式到解析器下标的映射
+    by_format: HashMap<FileFormat, usize>,
+    /// MIME类型到解析器下标的映射
+    by_mime: HashMap<String, usize>,
+}
+
+impl ParserRegistry {
+    /// 创建一个空的解析器注册表
+    /// 
+    /// # Returns
+    /// 
+    /// 返回一个新的ParserRegistry实例
+    pub fn new() -> Self {
+        ParserRegistry::default()
+    }
+
+    /// 创建一个包含内置解析器的注册表
+    /// 
+    /// # Returns
+    /// 
+    /// 返回一个已注册 [`TxtParser`] 的ParserRegistry实例
+    pub fn with_defaults() -> Self {
+        let mut registry = ParserRegistry::new();
+        registry.register(Arc::new(TxtParser::new()));
+        registry
+    }
+
+    /// 注册解析器
+    /// 
+    /// # Arguments
+    /// 
+    /// * `parser` - 要注册的解析器
+    pub fn register(&mut self, parser: Arc<dyn DocumentParser>) {
+        let index = self.parsers.len();
+        for format in parser.formats() {
+            self.by_format.insert(format, index);
+        }
+        for mime_type in parser.mime_types() {
+            self.by_mime.insert(mime_type.to_ascii_lowercase(), index);
+        }
+        self.parsers.push(parser);
+    }
+
+    /// 按文件格式查找解析器
+    /// 
+    /// # Arguments
+    /// 
+    /// * `format` - 文件格式
+    /// 
+    /// # Returns
+    /// 
+    /// 如果存在支持该格式的解析器则返回Some，否则返回None
+    pub fn parser_for_format(&self, format: &FileFormat) -> Option<Arc<dyn DocumentParser>> {
+        self.by_format.get(format).map(|&index| self.parsers[index].clone())
+    }
+
+    /// 按MIME类型查找解析器
+    /// 
+    /// # Arguments
+    /// 
+    /// * `mime_type` - MIME类型，忽略大小写和参数部分（如 `; charset=utf-8`）
+    /// 
+    /// # Returns
+    /// 
+    /// 如果存在支持该MIME类型的解析器则返回Some，否则返回None
+    pub fn parser_for_mime(&self, mime_type: &str) -> Option<Arc<dyn DocumentParser>> {
+        let essence = mime_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        self.by_mime.get(&essence).map(|&index| self.parsers[index].clone())
+    }
+
+    /// 根据内容嗅探解析器
+    /// 
+    /// 从最后注册的解析器开始尝试，使更具体的自定义格式优先于纯文本
+    /// 
+    /// # Arguments
+    /// 
+    /// * `bytes` - 文件开头的字节内容
+    /// 
+    /// # Returns
+    /// 
+    /// 返回第一个认为内容可以解析的解析器
+    pub fn sniff(&self, bytes: &[u8]) -> Option<Arc<dyn DocumentParser>> {
+        self.parsers.iter().rev().find(|parser| parser.sniff(bytes)).cloned()
+    }
+
+    /// 列出所有支持的格式
+    /// 
+    /// 被覆盖的格式只列出当前生效的解析器
+    /// 
+    /// # Returns
+    /// 
+    /// 返回按注册顺序排列的格式列表
+    pub fn supported_formats(&self) -> Vec<SupportedFormat> {
+        let mut formats = Vec::new();
+        for (index, parser) in self.parsers.iter().enumerate() {
+            let mime_types: Vec<String> = parser.mime_types()
+                .into_iter()
+                .map(|mime_type| mime_type.to_ascii_lowercase())
+                .filter(|mime_type| self.by_mime.get(mime_type) == Some(&index))
+                .collect();
+            for format in parser.formats() {
+                if self.by_format.get(&format) == Some(&index) {
+                    formats.push(SupportedFormat {
+                        name: parser.name().to_string(),
+                        format,
+                        mime_types: mime_types.clone(),
+                    });
+                }
+            }
+        }
+        formats
+    }
+}
@@ -0,0 +1,150 @@
+//! 断字
+//!
+//! 按TeX的Liang断字模式在拉丁字母、西里尔字母的单词内部找出可以加连字符断行的位置，
+//! 并找出源文本中软连字符（U+00AD）标出的断字位置
+
+use crate::language::{language_runs, primary_subtag};
+use hypher::{hyphenate_bounded, Lang};
+
+/// 软连字符
+pub const SOFT_HYPHEN: char = '\u{AD}';
+
+/// 断字设置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyphenationConfig {
+    /// 连字符之前至少保留的字母数
+    pub left_min: usize,
+    /// 连字符之后至少保留的字母数
+    pub right_min: usize,
+    /// 最多允许连续多少行以断字的连字符结尾
+    pub max_consecutive: usize,
+}
+
+impl HyphenationConfig {
+    /// 生成用于缓存的键
+    ///
+    /// # Returns
+    ///
+    /// 返回表示设置的字符串键
+    pub fn cache_key(&self) -> String {
+        format!("hyphenate-{}-{}-{}", self.left_min, self.right_min, self.max_consecutive)
+    }
+}
+
+impl Default for HyphenationConfig {
+    fn default() -> Self {
+        HyphenationConfig {
+            left_min: 2,
+            right_min: 3,
+            max_consecutive: 2,
+        }
+    }
+}
+
+/// 获取语言标签对应的断字模式
+fn pattern_language(language: &str) -> Option<Lang> {
+    match primary_subtag(language).to_ascii_lowercase().as_bytes() {
+        &[a, b] => Lang::from_iso([a, b]),
+        _ => None,
+    }
+}
+
+/// 判断是否有该语言的断字模式
+///
+/// # Arguments
+///
+/// * `language` - BCP 47 语言标签
+///
+/// # Returns
+///
+/// 支持英、德、法、俄、西、意、葡、荷、乌克兰语时返回true
+pub fn supports_hyphenation(language: &str) -> bool {
+    pattern_language(language).is_some()
+}
+
+/// 查找单词内部的断字位置
+///
+/// # Arguments
+///
+/// * `word` - 单词
+/// * `language` - BCP 47 语言标签
+/// * `config` - 断字设置
+///
+/// # Returns
+///
+/// 返回单词内可以断开的字节偏移，按升序排列；不支持该语言时返回空列表
+pub fn hyphenate_word(word: &str, language: &str, config: &HyphenationConfig) -> Vec<usize> {
+    let Some(lang) = pattern_language(language) else {
+        return Vec::new();
+    };
+    let mut offset = 0;
+    let mut points = Vec::new();
+    for syllable in hyphenate_bounded(word, lang, config.left_min.max(1), config.right_min.max(1)) {
+        offset += syllable.len();
+        if offset < word.len() {
+            points.push(offset);
+        }
+    }
+    points
+}
+
+/// 查找软连字符标出的断字位置
+///
+/// 软连字符后面是空白或文本末尾时不作为断字位置
+///
+/// # Arguments
+///
+/// * `text` - 文本
+///
+/// # Returns
+///
+/// 返回各软连字符之后的字节偏移，按升序排列
+pub fn soft_hyphens(text: &str) -> Vec<usize> {
+    text.char_indices()
+        .filter(|&(_, c)| c == SOFT_HYPHEN)
+        .map(|(index, c)| index + c.len_utf8())
+        .filter(|&offset| text[offset..].chars().next().is_some_and(|c| !c.is_whitespace()))
+        .collect()
+}
+
+/// 查找文本中所有的断字位置
+///
+/// 按语言片段分别使用各自语言的断字模式；已经含有软连字符的单词只在软连字符处断开
+///
+/// # Arguments
+///
+/// * `text` - 文本
+/// * `language` - 文本所在内容块的语言，证据不足的片段沿用该语言
+/// * `config` - 断字设置
+///
+/// # Returns
+///
+/// 返回可以加连字符断行的字节偏移，按升序排列
+pub fn hyphenation_points(text: &str, language: Option<&str>, config: &HyphenationConfig) -> Vec<usize> {
+    let mut points = soft_hyphens(text);
+    for run in language_runs(text, language) {
+        if !supports_hyphenation(&run.language) {
+            continue;
+        }
+        let mut word_start = None;
+        let run_text = &text[run.byte_range.clone()];
+        for (index, c) in run_text.char_indices().chain([(run_text.len(), ' ')]) {
+            let in_word = c.is_alphabetic() || c == SOFT_HYPHEN;
+            match (in_word, word_start) {
+                (true, None) => word_start = Some(index),
+                (false, Some(start)) => {
+                    let word = &run_text[start..index];
+                    if !word.contains(SOFT_HYPHEN) {
+                        let base = run.byte_range.start + start;
+                        points.extend(hyphenate_word(word, &run.language, config).into_iter().map(|offset| base + offset));
+                    }
+                    word_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    points.sort_unstable();
+    points.dedup();
+    points
+}
@@ -0,0 +1,142 @@
+//! 断字单元测试
+//!
+//! 测试断字模式、软连字符以及断行中断字的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::document::TextStyle;
+    use crate::hyphenate::*;
+    use crate::line_break::*;
+    use crate::measure::{ApproximateTextMeasurer, TextMeasurer};
+    use crate::optimal_break::TotalFitConfig;
+    use std::borrow::Cow;
+
+    /// 字号10的样式，半角字符宽5
+    fn style() -> TextStyle {
+        TextStyle {
+            font_size: 10.0,
+            font_family: Cow::Borrowed("serif"),
+            bold: false,
+            italic: false,
+        }
+    }
+
+    /// 用连字符标出单词的断字位置
+    fn syllables(word: &str, language: &str, config: &HyphenationConfig) -> String {
+        let mut result = String::new();
+        let mut last = 0;
+        for offset in hyphenate_word(word, language, config) {
+            result.push_str(&word[last..offset]);
+            result.push('-');
+            last = offset;
+        }
+        result.push_str(&word[last..]);
+        result
+    }
+
+    /// 按断字设置断行并返回各行文本及是否断字
+    fn lines<'a>(text: &'a str, max_width: f32, mode: &LineBreakMode, config: &HyphenationConfig) -> Vec<(&'a str, bool)> {
+        break_lines_hyphenated(text, &style(), &ApproximateTextMeasurer, max_width, mode, Some("en"), config)
+            .into_iter()
+            .map(|line| (&text[line.byte_range], line.hyphenated))
+            .collect()
+    }
+
+    /// 测试各语言的断字模式
+    #[test]
+    fn test_patterns() {
+        let config = HyphenationConfig::default();
+        assert_eq!(syllables("extraordinary", "en", &config), "ex-tra-or-di-nary");
+        assert_eq!(syllables("typesetting", "en-US", &config), "type-set-ting");
+        assert_eq!(syllables("Silbentrennung", "de", &config), "Sil-ben-tren-nung");
+        assert_eq!(syllables("anticonstitutionnellement", "fr", &config), "an-ti-cons-ti-tu-tion-nel-le-ment");
+        assert_eq!(syllables("достопримечательность", "ru", &config), "до-сто-при-ме-ча-тель-ность");
+        assert!(supports_hyphenation("de-AT"));
+        assert!(!supports_hyphenation("zh-Hans"));
+        assert!(hyphenate_word("extraordinary", "zh", &config).is_empty());
+    }
+
+    /// 测试连字符前后至少保留的字母数
+    #[test]
+    fn test_fragment_lengths() {
+        let config = HyphenationConfig {
+            left_min: 4,
+            right_min: 4,
+            ..HyphenationConfig::default()
+        };
+        assert_eq!(syllables("extraordinary", "en", &config), "extra-or-di-nary");
+        assert_eq!(syllables("hyphenation", "en", &config), "hyphen-ation");
+        // 太短的单词不断字
+        assert_eq!(syllables("paper", "en", &config), "paper");
+    }
+
+    /// 测试软连字符
+    #[test]
+    fn test_soft_hyphens() {
+        let text = "in\u{AD}ter\u{AD}na\u{AD}tion\u{AD} done";
+        assert_eq!(soft_hyphens(text), vec![4, 9, 13]);
+        // 含软连字符的单词只在软连字符处断开
+        assert_eq!(hyphenation_points(text, Some("en"), &HyphenationConfig::default()), vec![4, 9, 13]);
+        // 软连字符不占宽度
+        assert_eq!(ApproximateTextMeasurer.measure_text(text, &style()).width, 5.0 * 16.0);
+
+        // 不使用断字模式时也在软连字符处断开，行宽包含连字符
+        let result = break_lines(text, &style(), &ApproximateTextMeasurer, 40.0);
+        let texts: Vec<&str> = result.iter().map(|line| &text[line.byte_range.clone()]).collect();
+        assert_eq!(texts, vec!["in\u{AD}ter\u{AD}na\u{AD}", "tion\u{AD} ", "done"]);
+        assert!(result[0].hyphenated);
+        assert_eq!(result[0].width, 40.0);
+        assert!(!result[1].hyphenated);
+    }
+
+    /// 测试贪心断行和全段最优断行中的断字
+    #[test]
+    fn test_break_lines() {
+        let config = HyphenationConfig::default();
+        let text = "the extraordinary hyphenation";
+        assert_eq!(lines(text, 50.0, &LineBreakMode::Greedy, &config), vec![
+            ("the extra", true),
+            ("ordinary ", false),
+            ("hyphen", true),
+            ("ation", false),
+        ]);
+        let result = break_lines_hyphenated(text, &style(), &ApproximateTextMeasurer, 50.0, &LineBreakMode::Greedy, Some("en"), &config);
+        // 行宽包含行尾的连字符
+        assert_eq!(result[0].width, 50.0);
+
+        let total_fit = LineBreakMode::TotalFit(TotalFitConfig::default());
+        for (line, hyphenated) in lines(text, 50.0, &total_fit, &config) {
+            assert!(!hyphenated || !line.ends_with(' '));
+        }
+        // 中文不断字
+        assert!(hyphenation_points("排版引擎", Some("zh-Hans"), &config).is_empty());
+    }
+
+    /// 测试连续断字行数的上限
+    #[test]
+    fn test_consecutive_limit() {
+        let text = "an extraordinary hyphenation algorithm for typesetting engines";
+        for mode in [LineBreakMode::Greedy, LineBreakMode::TotalFit(TotalFitConfig::default())] {
+            for max_consecutive in [1, 2] {
+                let config = HyphenationConfig {
+                    max_consecutive,
+                    ..HyphenationConfig::default()
+                };
+                let result = lines(text, 60.0, &mode, &config);
+                let mut run = 0;
+                for (_, hyphenated) in &result {
+                    run = if *hyphenated { run + 1 } else { 0 };
+                    assert!(run <= max_consecutive, "{:?}", result);
+                }
+                assert!(result.iter().any(|(_, hyphenated)| *hyphenated));
+            }
+        }
+        // 只允许一行断字时第二行改在空格处断开
+        let config = HyphenationConfig {
+            max_consecutive: 1,
+            ..HyphenationConfig::default()
+        };
+        assert_eq!(lines(text, 60.0, &LineBreakMode::Greedy, &config)[..2], [("an extraor", true), ("dinary ", false)]);
+        assert_eq!(lines(text, 60.0, &LineBreakMode::Greedy, &HyphenationConfig::default())[1], ("dinary hy", true));
+    }
+}
@@ -0,0 +1,378 @@
+//! 语言识别
+//!
+//! 根据字符所属的文字系统识别文本的语言，中文再按简繁专用字区分 `zh-Hans` 和 `zh-Hant`，
+//! 拉丁字母文本按常用虚词区分英、法、德、西等语言。断行、断词、字体回退和引号处理都依赖识别结果
+
+use crate::chinese_convert::{is_simplified_char, is_traditional_char};
+use crate::document::{ContentBlock, ContentBlockType, DocumentModel};
+use crate::verse::is_han;
+use std::borrow::Cow;
+use std::ops::Range;
+use rayon::prelude::*;
+
+/// 各语言的常用虚词，用于区分使用拉丁字母的语言
+const LATIN_STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "of", "to", "is", "was", "that", "it", "he", "she", "you", "with", "for", "his", "her"]),
+    ("fr", &["le", "la", "les", "et", "est", "un", "une", "des", "du", "que", "dans", "pas", "je", "il", "elle"]),
+    ("de", &["der", "die", "das", "und", "ist", "nicht", "ein", "eine", "ich", "zu", "mit", "sie", "er", "den"]),
+    ("es", &["el", "la", "los", "las", "y", "es", "que", "un", "una", "en", "no", "por", "con", "del", "se"]),
+];
+
+/// 汉字、假名和韩文音节相对于字母的权重
+const SYLLABIC_WEIGHT: usize = 3;
+
+/// 识别文档语言时最多抽取的片段数
+const SAMPLE_COUNT: usize = 64;
+
+/// 识别文档语言时每个片段的字节数
+const SAMPLE_BYTES: usize = 1024;
+
+/// 文字系统
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    /// 汉字
+    Han,
+    /// 日文假名
+    Kana,
+    /// 韩文
+    Hangul,
+    /// 拉丁字母
+    Latin,
+    /// 西里尔字母
+    Cyrillic,
+    /// 希腊字母
+    Greek,
+    /// 阿拉伯字母
+    Arabic,
+    /// 希伯来字母
+    Hebrew,
+    /// 泰文
+    Thai,
+    /// 天城文
+    Devanagari,
+}
+
+impl Script {
+    /// 判断字符所属的文字系统
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - 字符
+    ///
+    /// # Returns
+    ///
+    /// 数字、标点、空白等不属于特定文字系统的字符返回None
+    pub fn of(c: char) -> Option<Self> {
+        let script = match c {
+            _ if is_han(c) || c == '々' || c == '〇' => Script::Han,
+            '\u{3041}'..='\u{309F}' | '\u{30A1}'..='\u{30FA}' | '\u{30FC}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => Script::Kana,
+            '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' => Script::Hangul,
+            'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' if c != '×' && c != '÷' => Script::Latin,
+            '\u{0400}'..='\u{04FF}' => Script::Cyrillic,
+            '\u{0370}'..='\u{03FF}' => Script::Greek,
+            '\u{0600}'..='\u{06FF}' | '\u{0750}'..='\u{077F}' => Script::Arabic,
+            '\u{0590}'..='\u{05FF}' => Script::Hebrew,
+            '\u{0E00}'..='\u{0E7F}' => Script::Thai,
+            '\u{0900}'..='\u{097F}' => Script::Devanagari,
+            _ => return None,
+        };
+        Some(script)
+    }
+
+    /// 判断两个文字系统是否属于同一种书写方式
+    ///
+    /// 日文混用汉字和假名，二者视为同一种书写方式
+    fn same_writing(self, other: Script) -> bool {
+        let group = |script| match script {
+            Script::Han | Script::Kana => Script::Han,
+            script => script,
+        };
+        group(self) == group(other)
+    }
+}
+
+/// 语言片段
+///
+/// 中英混排等文本中使用同一种语言的连续部分，片段之间的空白和标点归入前一个片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageRun {
+    /// 字节范围
+    pub byte_range: Range<usize>,
+    /// 语言标签
+    pub language: Cow<'static, str>,
+}
+
+/// 识别结果
+struct Guess {
+    /// 语言标签
+    tag: &'static str,
+    /// 占多数的文字系统
+    script: Script,
+    /// 是否有足够的证据，如中文文本中出现了简繁专用字
+    decisive: bool,
+}
+
+/// 识别文本的语言
+///
+/// # Arguments
+///
+/// * `text` - 需要识别的文本
+///
+/// # Returns
+///
+/// 返回 BCP 47 语言标签，如 `zh-Hans`、`zh-Hant`、`ja`、`ko`、`en`、`ru`，
+/// 文本中没有任何文字时返回None。无法区分简繁的中文视为简体，无法区分语言的拉丁字母文本视为英语
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    guess_language(text).map(|guess| guess.tag)
+}
+
+/// 识别文本中占多数的文字系统
+///
+/// # Arguments
+///
+/// * `text` - 需要识别的文本
+///
+/// # Returns
+///
+/// 文本中没有任何文字时返回None
+pub fn detect_script(text: &str) -> Option<Script> {
+    let counts = script_counts(text);
+    dominant_script(&counts)
+}
+
+/// 把文本按语言切分为片段
+///
+/// # Arguments
+///
+/// * `text` - 需要切分的文本
+/// * `default_language` - 所在文档或内容块的语言，证据不足的片段沿用该语言
+///
+/// # Returns
+///
+/// 返回按顺序排列的语言片段，文本中没有任何文字时返回空列表
+pub fn language_runs(text: &str, default_language: Option<&str>) -> Vec<LanguageRun> {
+    let mut runs: Vec<(Range<usize>, Script)> = Vec::new();
+    for (index, c) in text.char_indices() {
+        let end = index + c.len_utf8();
+        match (Script::of(c), runs.last_mut()) {
+            (Some(script), Some((range, last))) if script.same_writing(*last) => range.end = end,
+            (Some(script), _) => runs.push((index..end, script)),
+            (None, Some((range, _))) => range.end = end,
+            (None, None) => {}
+        }
+    }
+    // 开头的空白和标点归入第一个片段
+    if let Some((range, _)) = runs.first_mut() {
+        range.start = 0;
+    }
+
+    runs.into_iter()
+        .filter_map(|(byte_range, _)| {
+            let guess = guess_language(&text[byte_range.clone()])?;
+            Some(LanguageRun { language: resolve(guess, default_language), byte_range })
+        })
+        .collect()
+}
+
+/// 识别文档和内容块的语言
+///
+/// 导入器已经设置的文档语言和内容块语言保持不变。文档语言按均匀抽取的部分章节识别；
+/// 内容块只在识别出的语言与文档语言不同时记录，证据不足时沿用文档语言
+///
+/// # Arguments
+///
+/// * `document` - 需要识别的文档
+pub fn detect_document_language(document: &mut DocumentModel) {
+    if document.metadata.language.is_none() {
+        let stride = (document.chapters.len() / SAMPLE_COUNT).max(1);
+        let mut sample = String::new();
+        for chapter in document.chapters.iter().step_by(stride).take(SAMPLE_COUNT) {
+            let start = sample.len();
+            for block in &chapter.content {
+                sample.push_str(&block.content);
+                sample.push('\n');
+                if sample.len() - start >= SAMPLE_BYTES {
+                    break;
+                }
+            }
+        }
+        document.metadata.language = detect_language(&sample).map(Cow::Borrowed);
+    }
+
+    let document_language = document.metadata.language.as_deref();
+    document
+        .chapters
+        .par_iter_mut()
+        .for_each(|chapter| detect_block_languages(&mut chapter.content, document_language));
+}
+
+/// 按均匀抽取的片段识别整篇文本的语言
+///
+/// 从文本开头起每隔一段距离取一个片段，识别耗时与文本长度无关
+///
+/// # Arguments
+///
+/// * `text` - 需要识别的文本
+///
+/// # Returns
+///
+/// 返回 BCP 47 语言标签，文本中没有任何文字时返回None
+pub fn detect_sampled_language(text: &str) -> Option<&'static str> {
+    let stride = (text.len() / SAMPLE_COUNT).max(SAMPLE_BYTES);
+    let boundary = |index: usize| (index..=text.len()).find(|&index| text.is_char_boundary(index)).unwrap_or(text.len());
+    let mut sample = String::new();
+    for start in (0..text.len()).step_by(stride) {
+        sample.push_str(&text[boundary(start)..boundary(start + SAMPLE_BYTES)]);
+        sample.push('\n');
+    }
+    detect_language(&sample)
+}
+
+/// 识别内容块的语言
+///
+/// 导入器已经设置的内容块语言保持不变。只有主要文字系统与文档语言不同的内容块才需要识别，
+/// 识别出的语言与文档语言不同时记录在内容块中
+///
+/// # Arguments
+///
+/// * `blocks` - 需要识别的内容块
+/// * `document_language` - 文档语言
+pub fn detect_block_languages(blocks: &mut [ContentBlock], document_language: Option<&str>) {
+    let document_script = document_language.and_then(tag_script);
+    for block in blocks {
+        if block.language.is_some() || matches!(block.block_type, ContentBlockType::Image | ContentBlockType::Blank | ContentBlockType::SceneBreak) {
+            continue;
+        }
+        let same_script = match (document_script, detect_script(&block.content)) {
+            (_, None) => true,
+            (Some(document_script), Some(script)) => script.same_writing(document_script),
+            (None, Some(_)) => false,
+        };
+        if same_script {
+            continue;
+        }
+        if let Some(guess) = guess_language(&block.content) {
+            let language = resolve(guess, document_language);
+            if document_language != Some(language.as_ref()) {
+                block.language = Some(language);
+            }
+        }
+    }
+}
+
+/// 根据上下文语言确定最终的语言标签
+///
+/// 证据不足且与上下文语言使用同一种文字时沿用上下文语言，如繁体文档中没有简繁专用字的段落；
+/// 日文中只有汉字的片段也沿用日文
+fn resolve(guess: Guess, context: Option<&str>) -> Cow<'static, str> {
+    if let Some(context) = context {
+        let inherit = !guess.decisive || (primary_subtag(context) == "ja" && guess.script.same_writing(Script::Han));
+        if inherit && tag_script(context).is_some_and(|script| script.same_writing(guess.script)) {
+            return Cow::Owned(context.to_string());
+        }
+    }
+    Cow::Borrowed(guess.tag)
+}
+
+/// 获取语言标签的主标签，如 `zh-Hant` 的 `zh`
+pub(crate) fn primary_subtag(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// 获取语言通常使用的文字系统
+fn tag_script(tag: &str) -> Option<Script> {
+    let script = match primary_subtag(tag).to_ascii_lowercase().as_str() {
+        "zh" | "ja" => Script::Han,
+        "ko" => Script::Hangul,
+        "en" | "fr" | "de" | "es" | "it" | "pt" | "nl" | "vi" | "id" => Script::Latin,
+        "ru" | "uk" | "be" | "bg" | "sr" => Script::Cyrillic,
+        "el" => Script::Greek,
+        "ar" | "fa" => Script::Arabic,
+        "he" => Script::Hebrew,
+        "th" => Script::Thai,
+        "hi" => Script::Devanagari,
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// 识别文本的语言并给出证据是否充分
+fn guess_language(text: &str) -> Option<Guess> {
+    let counts = script_counts(text);
+    let script = dominant_script(&counts)?;
+    let count = |script: Script| counts.iter().find(|(s, _)| *s == script).map_or(0, |&(_, n)| n);
+
+    let guess = match script {
+        Script::Han | Script::Kana => {
+            let kana = count(Script::Kana);
+            if kana > 0 && kana * 10 >= kana + count(Script::Han) {
+                Guess { tag: "ja", script, decisive: true }
+            } else {
+                let simplified = text.chars().filter(|&c| is_simplified_char(c)).count();
+                let traditional = text.chars().filter(|&c| is_traditional_char(c)).count();
+                Guess {
+                    tag: if traditional > simplified { "zh-Hant" } else { "zh-Hans" },
+                    script: Script::Han,
+                    decisive: simplified != traditional,
+                }
+            }
+        }
+        Script::Hangul => Guess { tag: "ko", script, decisive: true },
+        Script::Cyrillic => Guess {
+            script,
+            tag: if text.chars().any(|c| matches!(c, 'є' | 'ї' | 'ґ' | 'Є' | 'Ї' | 'Ґ')) { "uk" } else { "ru" },
+            decisive: true,
+        },
+        Script::Greek => Guess { tag: "el", script, decisive: true },
+        Script::Arabic => Guess { tag: "ar", script, decisive: true },
+        Script::Hebrew => Guess { tag: "he", script, decisive: true },
+        Script::Thai => Guess { tag: "th", script, decisive: true },
+        Script::Devanagari => Guess { tag: "hi", script, decisive: true },
+        Script::Latin => guess_latin(text),
+    };
+    Some(guess)
+}
+
+/// 按常用虚词识别使用拉丁字母的语言
+fn guess_latin(text: &str) -> Guess {
+    let words: Vec<String> = text
+        .split(|c: char| Script::of(c) != Some(Script::Latin))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    let (tag, score) = LATIN_STOPWORDS
+        .iter()
+        .map(|(tag, stopwords)| (*tag, words.iter().filter(|word| stopwords.contains(&word.as_str())).count()))
+        .fold(("en", 0), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+    Guess { tag, script: Script::Latin, decisive: score > 0 }
+}
+
+/// 统计各文字系统的字符数
+fn script_counts(text: &str) -> Vec<(Script, usize)> {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    for script in text.chars().filter_map(Script::of) {
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+    counts
+}
+
+/// 获取占多数的文字系统，汉字和假名合并计算
+///
+/// 一个汉字、假名或韩文音节承载的信息大致相当于一个单词，按字母文字的三个字符计算
+fn dominant_script(counts: &[(Script, usize)]) -> Option<Script> {
+    let weight = |script: Script| match script {
+        Script::Han | Script::Kana | Script::Hangul => SYLLABIC_WEIGHT,
+        _ => 1,
+    };
+    let total = |script: Script| -> usize {
+        counts.iter().filter(|(s, _)| s.same_writing(script)).map(|&(s, n)| n * weight(s)).sum()
+    };
+    counts
+        .iter()
+        .map(|&(script, _)| script)
+        .max_by_key(|&script| (total(script), script == Script::Han))
+}
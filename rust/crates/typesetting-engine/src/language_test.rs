@@ -0,0 +1,92 @@
+//! 语言识别单元测试
+//!
+//! 测试文本、文档和内容块语言识别功能的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::language::*;
+    use crate::parser::ParserEngine;
+    use std::borrow::Cow;
+
+    /// 测试常见语言的识别
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language("这是一个简体中文的句子，我们来试一下。"), Some("zh-Hans"));
+        assert_eq!(detect_language("這是一個繁體中文的句子，我們來試一下。"), Some("zh-Hant"));
+        assert_eq!(detect_language("これは日本語の文章です。"), Some("ja"));
+        assert_eq!(detect_language("이것은 한국어 문장입니다."), Some("ko"));
+        assert_eq!(detect_language("The quick brown fox jumps over the lazy dog."), Some("en"));
+        assert_eq!(detect_language("Le chat est dans la maison."), Some("fr"));
+        assert_eq!(detect_language("Der Hund und die Katze schlafen."), Some("de"));
+        assert_eq!(detect_language("Это русский текст."), Some("ru"));
+        assert_eq!(detect_language("Це український текст, її."), Some("uk"));
+        assert_eq!(detect_language("123，……！"), None);
+        assert_eq!(detect_script("Hello, world 你好"), Some(Script::Latin));
+    }
+
+    /// 测试中英混排文本的语言片段
+    #[test]
+    fn test_language_runs() {
+        let text = "他说：Hello, world! 然后走了。";
+        let runs = language_runs(text, Some("zh-Hans"));
+        let slices: Vec<(&str, &str)> = runs.iter().map(|run| (&text[run.byte_range.clone()], run.language.as_ref())).collect();
+        assert_eq!(slices, vec![
+            ("他说：", "zh-Hans"),
+            ("Hello, world! ", "en"),
+            ("然后走了。", "zh-Hans"),
+        ]);
+
+        // 日文中只有汉字的片段沿用日文
+        let runs = language_runs("東京", Some("ja"));
+        assert_eq!(runs[0].language, "ja");
+    }
+
+    /// 测试文档和内容块的语言识别
+    #[test]
+    fn test_document_language() {
+        let content = "第一章 青杜\n这是一个简体中文的段落，我们来试一下。\n\nThe quick brown fox jumps over the lazy dog.\n\n“嗯。”";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        assert_eq!(document.metadata.language.as_deref(), Some("zh-Hans"));
+
+        let blocks = &document.chapters[0].content;
+        assert_eq!(blocks[0].language, None);
+        assert_eq!(blocks[1].language.as_deref(), Some("en"));
+        assert_eq!(document.block_language(&blocks[1]), Some("en"));
+        // 证据不足的段落沿用文档语言
+        assert_eq!(blocks[2].language, None);
+        assert_eq!(document.block_language(&blocks[2]), Some("zh-Hans"));
+
+        // 导入器指定的语言不会被覆盖
+        let mut document = parser.parse_txt("第一章 青杜\n山下有人。");
+        document.metadata.language = Some(Cow::Borrowed("zh-Hant"));
+        document.chapters[0].content[0].language = None;
+        detect_document_language(&mut document);
+        assert_eq!(document.metadata.language.as_deref(), Some("zh-Hant"));
+        assert_eq!(document.chapters[0].content[0].language, None);
+    }
+
+    /// 测试按抽样识别长文本的语言
+    #[test]
+    fn test_sampled_language() {
+        // 开头的英文只占很小一部分
+        let mut text = String::from("Foreword by the translator. ");
+        text.push_str(&"這是一個繁體中文的句子，我們來試一下。\n".repeat(20_000));
+        assert_eq!(detect_sampled_language(&text), Some("zh-Hant"));
+        assert_eq!(detect_sampled_language("The quick brown fox."), Some("en"));
+        assert_eq!(detect_sampled_language(""), None);
+    }
+
+    /// 测试只识别文字系统与文档语言不同的内容块
+    #[test]
+    fn test_block_languages() {
+        let parser = ParserEngine::new();
+        let mut blocks = parser.parse_txt("第一章 青杜\n山下有人。\n\nLe chat est dans la maison.\n\n“嗯。”").chapters.remove(0).content;
+        for block in &mut blocks {
+            block.language = None;
+        }
+        detect_block_languages(&mut blocks, Some("zh-Hans"));
+        let languages: Vec<Option<&str>> = blocks.iter().map(|block| block.language.as_deref()).collect();
+        assert_eq!(languages, vec![None, Some("fr"), None]);
+    }
+}
@@ -2,8 +2,15 @@
 //! 
 //! 负责计算内容在页面上的具体位置和分页逻辑
 
-use crate::document::{DocumentModel, ContentBlock, LayoutMetrics};
+use crate::document::{Chapter, DocumentModel, ContentBlock, ContentBlockType, LayoutMetrics, TextStyle};
+use crate::measure::{ApproximateTextMeasurer, TextMeasurer};
+use crate::line_break::{break_lines_hyphenated, break_lines_with, Line, LineBreakMode};
+use crate::hyphenate::HyphenationConfig;
+use crate::align::{align_lines, GlyphAdjustment, TextAlign, TextJustify};
+use serde::Serialize;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::{Arc, Mutex};
 
 /// 页面配置
@@ -57,23 +64,218 @@ impl PageConfig {
     }
 }
 
-/// 页面结构
+/// 场景分隔符样式
 /// 
-/// 表示一个页面，包含页面上的内容块和已使用的高度
+/// 定义场景分隔符在页面上的装饰符号和上下间距
 #[derive(Debug, Clone, PartialEq)]
+pub struct SceneBreakStyle {
+    /// 装饰符号，为None时保留原文中的分隔符
+    pub ornament: Option<Cow<'static, str>>,
+    /// 分隔符上方的间距（像素）
+    pub spacing_before: f32,
+    /// 分隔符下方的间距（像素）
+    pub spacing_after: f32,
+}
+
+impl SceneBreakStyle {
+    /// 生成用于缓存的键
+    /// 
+    /// # Returns
+    /// 
+    /// 返回表示样式的字符串键
+    pub fn cache_key(&self) -> String {
+        format!("{}-{}-{}",
+            self.ornament.as_deref().unwrap_or(""),
+            self.spacing_before, self.spacing_after)
+    }
+}
+
+impl Default for SceneBreakStyle {
+    fn default() -> Self {
+        SceneBreakStyle {
+            ornament: Some(Cow::Borrowed("⁂")),
+            spacing_before: 16.0,
+            spacing_after: 16.0,
+        }
+    }
+}
+
+/// 排版设置
+/// 
+/// 定义段落的断行方式、对齐方式、断字等排版参数
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TypographyConfig {
+    /// 断行方式，默认为贪心断行
+    pub line_break: LineBreakMode,
+    /// 对齐方式，默认为左对齐
+    pub align: TextAlign,
+    /// 两端对齐时分配剩余空间的位置
+    pub justify: TextJustify,
+    /// 断字设置，为None时只在软连字符处断字
+    pub hyphenation: Option<HyphenationConfig>,
+}
+
+impl TypographyConfig {
+    /// 生成用于缓存的键
+    /// 
+    /// # Returns
+    /// 
+    /// 返回表示设置的字符串键
+    pub fn cache_key(&self) -> String {
+        let hyphenation = self.hyphenation.as_ref().map_or_else(|| String::from("no-hyphenate"), HyphenationConfig::cache_key);
+        format!("{}-{:?}-{:?}-{}", self.line_break.cache_key(), self.align, self.justify, hyphenation)
+    }
+}
+
+/// 章节的起始位置
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ChapterStart {
+    /// 每章从新的一页开始
+    #[default]
+    NewPage,
+    /// 每章从下一个奇数页（右页）开始，必要时插入空白页
+    OddPage,
+    /// 接着上一章排，章节之间留出间距（像素）
+    Continuous {
+        /// 章节之间的间距（像素）
+        spacing: f32,
+    },
+}
+
+/// 分页设置
+/// 
+/// 定义段落跨页拆分时页首、页尾至少保留的行数，以及标题和章节的分页规则
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaginationConfig {
+    /// 孤行控制：段落在页尾至少保留的行数，不足时整段移到下一页
+    pub orphans: usize,
+    /// 寡行控制：段落在页首至少保留的行数，不足时从上一页多移几行过来
+    pub widows: usize,
+    /// 标题之后至少与标题同页的正文行数，放不下时标题移到下一页
+    pub min_lines_after_heading: usize,
+    /// 章节的起始位置
+    pub chapter_start: ChapterStart,
+}
+
+impl PaginationConfig {
+    /// 生成用于缓存的键
+    /// 
+    /// # Returns
+    /// 
+    /// 返回表示设置的字符串键
+    pub fn cache_key(&self) -> String {
+        format!("orphans{}-widows{}-heading{}-{:?}", self.orphans, self.widows, self.min_lines_after_heading, self.chapter_start)
+    }
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        PaginationConfig {
+            orphans: 2,
+            widows: 2,
+            min_lines_after_heading: 2,
+            chapter_start: ChapterStart::default(),
+        }
+    }
+}
+
+/// 行框
+/// 
+/// 页面上一行文本的位置和尺寸，坐标从页面左上角算起，前端可以直接按行框绘制
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineBox {
+    /// 所属内容块在页面内容块列表中的索引
+    pub block_index: usize,
+    /// 行在所属内容块文本中的字节范围
+    pub byte_range: Range<usize>,
+    /// 行左边缘的横坐标（像素），已包含左边距和对齐偏移
+    pub x: f32,
+    /// 行顶部的纵坐标（像素），已包含上边距
+    pub y: f32,
+    /// 基线的纵坐标（像素）
+    pub baseline: f32,
+    /// 行宽（像素）
+    pub width: f32,
+    /// 行高（像素）
+    pub height: f32,
+    /// 是否在单词内部断字，行尾需要显示一个连字符
+    pub hyphenated: bool,
+    /// 两端对齐时插入的字形间距，偏移是所属内容块文本中的字节偏移
+    pub adjustments: Vec<GlyphAdjustment>,
+}
+
+/// 内容块片段的接续标记
+/// 
+/// 段落跨页拆分后，各页上的片段据此判断是否需要首行缩进等
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct Continuation {
+    /// 是否接续上一页的同一段落，为true时首行不缩进
+    pub from_previous: bool,
+    /// 是否在下一页继续
+    pub to_next: bool,
+}
+
+/// 页面结构
+/// 
+/// 表示一个页面，包含页面上的内容块、各行的行框和已使用的高度
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Page {
-    /// 页面上的内容块列表
+    /// 页面上的内容块列表，跨页的段落在每页上各是一个片段
     pub blocks: Vec<ContentBlock>,
+    /// 各内容块的接续标记，与内容块列表一一对应
+    pub continuations: Vec<Continuation>,
+    /// 页面上的各行，按从上到下的顺序排列
+    pub lines: Vec<LineBox>,
     /// 页面已使用的高度（像素）
     pub used_height: f32,
 }
 
+impl Page {
+    /// 获取内容块的各行
+    /// 
+    /// # Arguments
+    /// 
+    /// * `block_index` - 内容块在页面内容块列表中的索引
+    /// 
+    /// # Returns
+    /// 
+    /// 返回该内容块在本页上的行框
+    pub fn block_lines(&self, block_index: usize) -> impl Iterator<Item = &LineBox> {
+        self.lines.iter().filter(move |line| line.block_index == block_index)
+    }
+
+    /// 获取一行的文本
+    /// 
+    /// # Arguments
+    /// 
+    /// * `line` - 本页的行框
+    /// 
+    /// # Returns
+    /// 
+    /// 返回该行在所属内容块中的文本，包含行尾的空格
+    pub fn line_text(&self, line: &LineBox) -> &str {
+        &self.blocks[line.block_index].content[line.byte_range.clone()]
+    }
+}
+
 /// 布局引擎
 /// 
 /// 负责将文档模型转换为页面结构，处理分页逻辑
 pub struct LayoutEngine {
     /// 页面配置
     page_config: PageConfig,
+    /// 场景分隔符样式
+    scene_break_style: SceneBreakStyle,
+    /// 是否隐藏作者的话和整章公告
+    hide_author_notes: bool,
+    /// 是否在章节开头排出章节标题
+    chapter_titles: bool,
+    /// 排版设置
+    typography: TypographyConfig,
+    /// 分页设置
+    pagination: PaginationConfig,
+    /// 文本测量器
+    measurer: Arc<dyn TextMeasurer>,
     /// 页面缓存，用于存储已布局的页面以避免重复计算
     page_cache: Arc<Mutex<HashMap<String, Vec<Page>>>>,
 }
@@ -91,10 +293,107 @@ impl LayoutEngine {
     pub fn new(page_config: PageConfig) -> Self {
         LayoutEngine { 
             page_config,
+            scene_break_style: SceneBreakStyle::default(),
+            hide_author_notes: false,
+            chapter_titles: false,
+            typography: TypographyConfig::default(),
+            pagination: PaginationConfig::default(),
+            measurer: Arc::new(ApproximateTextMeasurer),
             page_cache: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// 设置场景分隔符样式
+    /// 
+    /// # Arguments
+    /// 
+    /// * `style` - 场景分隔符样式
+    /// 
+    /// # Returns
+    /// 
+    /// 返回使用该样式的LayoutEngine实例
+    pub fn with_scene_break_style(mut self, style: SceneBreakStyle) -> Self {
+        self.scene_break_style = style;
+        self
+    }
+
+    /// 设置是否隐藏作者的话
+    /// 
+    /// 隐藏时作者的话不参与分页，整章公告不生成页面
+    /// 
+    /// # Arguments
+    /// 
+    /// * `hidden` - 是否隐藏
+    /// 
+    /// # Returns
+    /// 
+    /// 返回使用该设置的LayoutEngine实例
+    pub fn with_author_notes_hidden(mut self, hidden: bool) -> Self {
+        self.hide_author_notes = hidden;
+        self
+    }
+
+    /// 设置是否在章节开头排出章节标题
+    /// 
+    /// 章节标题作为标题块与之后的正文保持在同一页。接排章节时章节之间没有分页，
+    /// 无论是否设置都会排出章节标题
+    /// 
+    /// # Arguments
+    /// 
+    /// * `enabled` - 是否排出章节标题
+    /// 
+    /// # Returns
+    /// 
+    /// 返回使用该设置的LayoutEngine实例
+    pub fn with_chapter_titles(mut self, enabled: bool) -> Self {
+        self.chapter_titles = enabled;
+        self
+    }
+
+    /// 设置排版参数
+    /// 
+    /// # Arguments
+    /// 
+    /// * `typography` - 排版设置
+    /// 
+    /// # Returns
+    /// 
+    /// 返回使用该设置的LayoutEngine实例
+    pub fn with_typography(mut self, typography: TypographyConfig) -> Self {
+        self.typography = typography;
+        self
+    }
+
+    /// 设置分页参数
+    /// 
+    /// # Arguments
+    /// 
+    /// * `pagination` - 分页设置
+    /// 
+    /// # Returns
+    /// 
+    /// 返回使用该设置的LayoutEngine实例
+    pub fn with_pagination(mut self, pagination: PaginationConfig) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    /// 设置文本测量器
+    /// 
+    /// 默认使用按字符类别估算宽度的测量器，应用程序可以接入基于字体的测量器
+    /// 
+    /// # Arguments
+    /// 
+    /// * `measurer` - 文本测量器
+    /// 
+    /// # Returns
+    /// 
+    /// 返回使用该测量器的LayoutEngine实例
+    pub fn with_measurer(mut self, measurer: Arc<dyn TextMeasurer>) -> Self {
+        self.measurer = measurer;
+        self
+    }
+
     /// 从缓存中获取页面
     /// 
     /// # Arguments
@@ -132,6 +431,16 @@ impl LayoutEngine {
     fn generate_cache_key(&self, document: &DocumentModel) -> String {
         // 基于文档内容和页面配置生成缓存键
         let mut key = self.page_config.cache_key();
+        key.push_str(&self.scene_break_style.cache_key());
+        key.push_str(&self.typography.cache_key());
+        key.push_str(&self.pagination.cache_key());
+        key.push_str(&self.measurer.cache_key());
+        if self.hide_author_notes {
+            key.push_str("-hide-notes");
+        }
+        if self.shows_chapter_titles() {
+            key.push_str("-chapter-titles");
+        }
         for chapter in &document.chapters {
             key.push_str(&format!("{}{}", chapter.id, chapter.title));
         }
@@ -167,32 +476,8 @@ impl LayoutEngine {
         let mut current_page = self.create_empty_page();
         let chapter = &document.chapters[chapter_index];
         
-        for block in &chapter.content {
-            // 测量块的尺寸
-            let block_metrics = self.measure_block(block);
-            
-            // 检查当前页是否能容纳这个块
-            if self.can_fit_in_page(&block_metrics, &current_page) {
-                // 可以容纳，添加到当前页
-                current_page.used_height += block_metrics.height;
-                current_page.blocks.push(block.clone());
-            } else {
-                // 无法容纳，保存当前页并创建新页
-                if !current_page.blocks.is_empty() {
-                    pages.push(current_page);
-                }
-                current_page = self.create_empty_page();
-                
-                // 如果块太大无法适应空页面，需要拆分内容
-                if block_metrics.height > self.page_config.content_height() {
-                    // 对于过大的块进行拆分处理
-                    self.layout_large_block(block, &mut pages);
-                } else {
-                    // 添加块到新页
-                    current_page.used_height += block_metrics.height;
-                    current_page.blocks.push(block.clone());
-                }
-            }
+        if !(self.hide_author_notes && chapter.author_note) {
+            self.layout_blocks(&self.chapter_blocks(document, chapter), &mut pages, &mut current_page);
         }
         
         // 添加最后一页（如果有内容）
@@ -231,39 +516,11 @@ impl LayoutEngine {
         
         // 遍历所有章节和内容块
         for chapter in &document.chapters {
-            for block in &chapter.content {
-                // 测量块的尺寸
-                let block_metrics = self.measure_block(block);
-                
-                // 检查当前页是否能容纳这个块
-                if self.can_fit_in_page(&block_metrics, &current_page) {
-                    // 可以容纳，添加到当前页
-                    current_page.used_height += block_metrics.height;
-                    current_page.blocks.push(block.clone());
-                } else {
-                    // 无法容纳，保存当前页并创建新页
-                    if !current_page.blocks.is_empty() {
-                        pages.push(current_page);
-                    }
-                    current_page = self.create_empty_page();
-                    
-                    // 如果块太大无法适应空页面，需要拆分内容
-                    if block_metrics.height > self.page_config.content_height() {
-                        // 对于过大的块进行拆分处理
-                        self.layout_large_block(block, &mut pages);
-                    } else {
-                        // 添加块到新页
-                        current_page.used_height += block_metrics.height;
-                        current_page.blocks.push(block.clone());
-                    }
-                }
-            }
-            
-            // 在章节之间添加分页（如果当前页已经有内容）
-            if !current_page.blocks.is_empty() && current_page.used_height > 0.0 {
-                pages.push(current_page);
-                current_page = self.create_empty_page();
+            if self.hide_author_notes && chapter.author_note {
+                continue;
             }
+            self.start_chapter(&mut pages, &mut current_page);
+            self.layout_blocks(&self.chapter_blocks(document, chapter), &mut pages, &mut current_page);
         }
         
         // 添加最后一页（如果有内容）
@@ -277,42 +534,401 @@ impl LayoutEngine {
         pages
     }
     
-    /// 处理过大的内容块
+    /// 按分页设置确定新章节的起始位置
+    /// 
+    /// # Arguments
+    /// 
+    /// * `pages` - 页面列表的可变引用
+    /// * `current_page` - 当前正在填充的页面
+    fn start_chapter(&self, pages: &mut Vec<Page>, current_page: &mut Page) {
+        if !current_page.blocks.is_empty() {
+            if let ChapterStart::Continuous { spacing } = self.pagination.chapter_start {
+                current_page.used_height += spacing;
+                if current_page.used_height >= self.page_config.content_height() {
+                    pages.push(std::mem::replace(current_page, self.create_empty_page()));
+                }
+                return;
+            }
+            pages.push(std::mem::replace(current_page, self.create_empty_page()));
+        }
+        // 下一页是偶数页时插入空白页
+        if self.pagination.chapter_start == ChapterStart::OddPage && pages.len() % 2 == 1 {
+            pages.push(self.create_empty_page());
+        }
+    }
+    
+    /// 判断是否排出章节标题
+    fn shows_chapter_titles(&self) -> bool {
+        self.chapter_titles || matches!(self.pagination.chapter_start, ChapterStart::Continuous { .. })
+    }
+    
+    /// 获取章节中需要布局的内容块
     /// 
-    /// 当内容块太大无法适应单页时，将其拆分成多个较小的块
+    /// 排出章节标题时，章节标题作为标题块排在最前面，使其与之后的正文保持在同一页。
+    /// 隐藏作者的话时先去掉这些块，使场景分隔符能根据实际相邻的内容块布局
     /// 
     /// # Arguments
     /// 
-    /// * `block` - 需要拆分的内容块
+    /// * `document` - 章节所在的文档
+    /// * `chapter` - 需要布局的章节
+    /// 
+    /// # Returns
+    /// 
+    /// 返回需要布局的内容块
+    fn chapter_blocks(&self, document: &DocumentModel, chapter: &Chapter) -> Vec<ContentBlock> {
+        let mut blocks = Vec::with_capacity(chapter.content.len() + 1);
+        let title = chapter.title.trim();
+        if self.shows_chapter_titles() && !title.is_empty() {
+            let base = chapter.content.first().map(|block| &block.styles).or(document.styles.first());
+            blocks.push(ContentBlock {
+                block_type: ContentBlockType::Title,
+                content: Cow::Owned(title.to_string()),
+                styles: title_style(base),
+                metrics: None,
+                language: None,
+            });
+        }
+        blocks.extend(
+            chapter
+                .content
+                .iter()
+                .filter(|block| !(self.hide_author_notes && block.block_type == ContentBlockType::AuthorNote))
+                .cloned(),
+        );
+        blocks
+    }
+
+    /// 布局一个章节的内容块
+    /// 
+    /// 依次把内容块放入当前页。放不下的段落按行拆分，先填满当前页再接续到下一页；
+    /// 其他内容块整块移到新页，因此诗词块的诗句不会被重排，不超过一页的诗节也不会被拆开。
+    /// 标题与其后的正文保持在同一页，当前页放不下标题和规定行数的正文时标题移到新页。场景分隔符会与前后的内容块保持在同一页，
+    /// 使其不会成为页面的第一个或最后一个元素；位于章节开头或结尾的分隔符没有意义，会被忽略
+    /// 
+    /// # Arguments
+    /// 
+    /// * `blocks` - 章节内容块列表
     /// * `pages` - 页面列表的可变引用
-    fn layout_large_block(&self, block: &ContentBlock, pages: &mut Vec<Page>) {
-        // 按行拆分内容
-        let lines: Vec<&str> = block.content.lines().collect();
-        // 计算每页可以容纳的行数（基于字体大小和行高）
-        let lines_per_page = (self.page_config.content_height() / (block.styles.font_size * 1.2)) as usize;
-        
-        // 预分配容量以提高性能
-        let chunks_count = (lines.len() + lines_per_page - 1) / lines_per_page; // 向上取整
-        pages.reserve(chunks_count);
+    /// * `current_page` - 当前正在填充的页面
+    fn layout_blocks(&self, blocks: &[ContentBlock], pages: &mut Vec<Page>, current_page: &mut Page) {
+        let content_height = self.page_config.content_height();
         
-        // 分批处理行
-        for chunk in lines.chunks(lines_per_page) {
-            let mut new_page = self.create_empty_page();
-            // 使用更高效的方式连接字符串
-            let chunk_content = chunk.join("\n");
+        for (index, block) in blocks.iter().enumerate() {
+            if block.block_type == ContentBlockType::SceneBreak {
+                let next = match blocks.get(index + 1) {
+                    Some(next) if index > 0 && next.block_type != ContentBlockType::SceneBreak => next,
+                    _ => continue,
+                };
+                self.layout_scene_break(block, next, pages, current_page);
+                continue;
+            }
             
-            let new_block = ContentBlock {
-                block_type: block.block_type.clone(),
-                content: std::borrow::Cow::Owned(chunk_content),
-                styles: block.styles.clone(),
-                metrics: None,
-            };
+            // 标题与其后的正文保持在同一页
+            if block.block_type == ContentBlockType::Title && !current_page.blocks.is_empty() {
+                let needed = self.keep_with_next_height(&blocks[index..]);
+                if needed <= content_height && current_page.used_height + needed > content_height {
+                    pages.push(std::mem::replace(current_page, self.create_empty_page()));
+                }
+            }
+            
+            // 断行并测量块的尺寸
+            let lines = self.break_block(block);
+            let block_metrics = self.block_metrics(block, lines.len());
             
-            let block_metrics = self.measure_block(&new_block);
-            new_page.used_height = block_metrics.height;
-            new_page.blocks.push(new_block);
-            pages.push(new_page);
+            // 检查当前页是否能容纳这个块
+            if self.can_fit_in_page(&block_metrics, current_page) {
+                // 可以容纳，添加到当前页
+                self.push_block(current_page, block.clone(), &lines, Continuation::default());
+            } else if matches!(block.block_type, ContentBlockType::Text | ContentBlockType::AuthorNote) {
+                // 段落从当前页的剩余空间开始按行拆分
+                self.layout_split_block(block, &lines, pages, current_page);
+            } else {
+                // 无法容纳，保存当前页并创建新页
+                if !current_page.blocks.is_empty() {
+                    pages.push(std::mem::replace(current_page, self.create_empty_page()));
+                }
+                
+                // 如果块太大无法适应空页面，需要拆分内容
+                if block_metrics.height > content_height {
+                    self.layout_split_block(block, &lines, pages, current_page);
+                } else {
+                    // 添加块到新页
+                    self.push_block(current_page, block.clone(), &lines, Continuation::default());
+                }
+            }
+        }
+    }
+    
+    /// 计算标题需要与其后内容一起占用的高度
+    /// 
+    /// 包括连续的标题和空行，以及之后的正文在孤行、寡行控制下至少要放在同一页的行数；
+    /// 其后是诗词、图片等不拆分的内容块时计入整块的高度
+    /// 
+    /// # Arguments
+    /// 
+    /// * `blocks` - 从标题开始的内容块
+    /// 
+    /// # Returns
+    /// 
+    /// 返回需要留在同一页上的高度（像素）
+    fn keep_with_next_height(&self, blocks: &[ContentBlock]) -> f32 {
+        let mut height = 0.0;
+        for block in blocks {
+            match block.block_type {
+                ContentBlockType::Title | ContentBlockType::Blank => height += self.measure_block(block).height,
+                ContentBlockType::SceneBreak => break,
+                ContentBlockType::Text | ContentBlockType::AuthorNote => {
+                    let total = self.break_block(block).len();
+                    let min = self.pagination.min_lines_after_heading.min(total);
+                    let keep = (min..=total).find(|&available| self.split_count(0, total, available) >= min).unwrap_or(total);
+                    return height + keep as f32 * self.measurer.line_height(&block.styles);
+                }
+                _ => return height + self.measure_block(block).height,
+            }
+        }
+        height
+    }
+    
+    /// 布局场景分隔符
+    /// 
+    /// 当前页放不下分隔符和紧随其后的内容块时，把当前页的最后一个块连同分隔符一起移到新页
+    /// 
+    /// # Arguments
+    /// 
+    /// * `block` - 场景分隔符块
+    /// * `next` - 紧随分隔符之后的内容块
+    /// * `pages` - 页面列表的可变引用
+    /// * `current_page` - 当前正在填充的页面
+    fn layout_scene_break(&self, block: &ContentBlock, next: &ContentBlock, pages: &mut Vec<Page>, current_page: &mut Page) {
+        let content_height = self.page_config.content_height();
+        let scene_break = self.scene_break_block(block);
+        let break_lines = self.break_block(&scene_break);
+        let break_height = self.block_metrics(&scene_break, break_lines.len()).height;
+        let next_height = self.measure_block(next).height;
+        
+        // 上一个块恰好填满一页时，它所在的页已经被保存，重新打开该页
+        if current_page.blocks.is_empty() {
+            if let Some(last_page) = pages.pop() {
+                *current_page = last_page;
+            }
+        }
+        
+        // 分隔符需要和下一个块同页才不会落在页尾
+        let needed = break_height + next_height;
+        if !current_page.blocks.is_empty() && current_page.used_height + needed <= content_height {
+            self.push_block(current_page, scene_break, &break_lines, Continuation::default());
+            return;
+        }
+        
+        // 把上一个块带到新页，避免分隔符落在页首
+        let mut new_page = self.create_empty_page();
+        if let Some(previous) = current_page.blocks.last() {
+            let line_count = current_page.block_lines(current_page.blocks.len() - 1).count();
+            let previous_height = self.block_metrics(previous, line_count).height;
+            if previous_height + needed <= content_height {
+                self.move_last_block(current_page, &mut new_page);
+            }
+        }
+        
+        if new_page.blocks.is_empty() {
+            // 无法保持在一起时，尽量留在当前页，由后续内容决定是否换页
+            if !current_page.blocks.is_empty() && current_page.used_height + break_height <= content_height {
+                self.push_block(current_page, scene_break, &break_lines, Continuation::default());
+                return;
+            }
+        }
+        
+        if !current_page.blocks.is_empty() {
+            pages.push(std::mem::replace(current_page, self.create_empty_page()));
         }
+        self.push_block(&mut new_page, scene_break, &break_lines, Continuation::default());
+        *current_page = new_page;
+    }
+    
+    /// 按样式生成场景分隔符块
+    fn scene_break_block(&self, block: &ContentBlock) -> ContentBlock {
+        let content = match &self.scene_break_style.ornament {
+            Some(ornament) => ornament.clone(),
+            None => block.content.clone(),
+        };
+        ContentBlock {
+            block_type: ContentBlockType::SceneBreak,
+            content,
+            styles: block.styles.clone(),
+            metrics: None,
+            language: None,
+        }
+    }
+    
+    /// 按行拆分内容块
+    /// 
+    /// 在当前页的剩余空间放入尽可能多的行，其余的行接续到下一页，直到全部放完。
+    /// 拆分时遵守分页设置中的孤行、寡行控制。最后一个片段留在当前页，
+    /// 其后的内容块可以继续使用该页的剩余空间
+    /// 
+    /// # Arguments
+    /// 
+    /// * `block` - 需要拆分的内容块
+    /// * `lines` - 内容块的断行结果
+    /// * `pages` - 页面列表的可变引用
+    /// * `current_page` - 当前正在填充的页面
+    fn layout_split_block(&self, block: &ContentBlock, lines: &[Line], pages: &mut Vec<Page>, current_page: &mut Page) {
+        let content_height = self.page_config.content_height();
+        let line_height = self.measurer.line_height(&block.styles);
+        let mut first = 0;
+        while first < lines.len() {
+            // 当前页剩余空间可以容纳的行数
+            let available = ((content_height - current_page.used_height) / line_height).max(0.0) as usize;
+            let mut count = self.split_count(first, lines.len(), available);
+            if count == 0 {
+                if !current_page.blocks.is_empty() {
+                    pages.push(std::mem::replace(current_page, self.create_empty_page()));
+                    continue;
+                }
+                // 空页上无法满足孤行、寡行控制时照常拆分，至少放一行
+                count = available.clamp(1, lines.len() - first);
+            }
+            let last = lines.len().min(first + count);
+            let continuation = Continuation {
+                from_previous: first > 0,
+                to_next: last < lines.len(),
+            };
+            let (fragment, fragment_lines) = self.fragment(block, &lines[first..last]);
+            self.push_block(current_page, fragment, &fragment_lines, continuation);
+            if continuation.to_next {
+                pages.push(std::mem::replace(current_page, self.create_empty_page()));
+            }
+            first = last;
+        }
+    }
+    
+    /// 计算段落在当前页放入的行数
+    /// 
+    /// 放不下全部剩余的行时，先减少本页的行数使下一页至少有寡行控制的行数，
+    /// 段落开头在本页的行数少于孤行控制时整段移到下一页。
+    /// 段落短于两者之和时无法拆分，因此也整段移到下一页
+    /// 
+    /// # Arguments
+    /// 
+    /// * `first` - 本页的第一行在段落中的索引
+    /// * `total` - 段落的总行数
+    /// * `available` - 本页剩余空间可以容纳的行数
+    /// 
+    /// # Returns
+    /// 
+    /// 返回本页放入的行数，为0表示本页不放入该段落
+    fn split_count(&self, first: usize, total: usize, available: usize) -> usize {
+        let remaining = total - first;
+        if available >= remaining {
+            return remaining;
+        }
+        let mut count = available;
+        if remaining - count < self.pagination.widows {
+            count = remaining.saturating_sub(self.pagination.widows);
+        }
+        if first == 0 && count < self.pagination.orphans {
+            return 0;
+        }
+        count
+    }
+    
+    /// 取出内容块中若干连续的行作为片段
+    /// 
+    /// 沿用整块的断行结果，使拆分后各行的断点和对齐不变
+    /// 
+    /// # Arguments
+    /// 
+    /// * `block` - 内容块
+    /// * `lines` - 片段包含的行
+    /// 
+    /// # Returns
+    /// 
+    /// 返回片段内容块和以片段文本为准的断行结果；包含全部行时返回原内容块
+    fn fragment(&self, block: &ContentBlock, lines: &[Line]) -> (ContentBlock, Vec<Line>) {
+        let start = lines[0].byte_range.start;
+        let end = lines[lines.len() - 1].byte_range.end;
+        if start == 0 && end == block.content.len() {
+            return (block.clone(), lines.to_vec());
+        }
+        let fragment = ContentBlock {
+            block_type: block.block_type.clone(),
+            content: Cow::Owned(block.content[start..end].to_string()),
+            styles: block.styles.clone(),
+            metrics: None,
+            language: block.language.clone(),
+        };
+        let fragment_lines = lines.iter().map(|line| {
+            let mut line = line.clone();
+            line.byte_range = line.byte_range.start - start..line.byte_range.end - start;
+            for adjustment in &mut line.adjustments {
+                adjustment.offset -= start;
+            }
+            line
+        }).collect();
+        (fragment, fragment_lines)
+    }
+    
+    /// 把内容块放到页面上已使用高度之后，并按断行结果生成各行的行框
+    /// 
+    /// # Arguments
+    /// 
+    /// * `page` - 目标页面
+    /// * `block` - 内容块
+    /// * `lines` - 内容块的断行结果
+    /// * `continuation` - 内容块的接续标记
+    fn push_block(&self, page: &mut Page, block: ContentBlock, lines: &[Line], continuation: Continuation) {
+        let line_height = self.measurer.line_height(&block.styles);
+        let baseline = self.measurer.baseline(&block.styles);
+        let mut top = self.page_config.margin_top + page.used_height;
+        if block.block_type == ContentBlockType::SceneBreak {
+            top += self.scene_break_style.spacing_before;
+        }
+        let block_index = page.blocks.len();
+        for (index, line) in lines.iter().enumerate() {
+            let y = top + index as f32 * line_height;
+            page.lines.push(LineBox {
+                block_index,
+                byte_range: line.byte_range.clone(),
+                x: self.page_config.margin_left + line.x,
+                y,
+                baseline: y + baseline,
+                width: line.width,
+                height: line_height,
+                hyphenated: line.hyphenated,
+                adjustments: line.adjustments.clone(),
+            });
+        }
+        page.used_height += self.block_metrics(&block, lines.len()).height;
+        page.blocks.push(block);
+        page.continuations.push(continuation);
+    }
+    
+    /// 把页面的最后一个内容块连同其行框移到另一个页面已使用高度之后
+    /// 
+    /// # Arguments
+    /// 
+    /// * `from` - 原页面
+    /// * `to` - 目标页面
+    fn move_last_block(&self, from: &mut Page, to: &mut Page) {
+        let Some(block) = from.blocks.pop() else {
+            return;
+        };
+        let continuation = from.continuations.pop().unwrap_or_default();
+        let block_index = from.blocks.len();
+        let split = from.lines.iter().position(|line| line.block_index == block_index).unwrap_or(from.lines.len());
+        let mut lines = from.lines.split_off(split);
+        let height = self.block_metrics(&block, lines.len()).height;
+        from.used_height -= height;
+        let offset = to.used_height - from.used_height;
+        for line in &mut lines {
+            line.block_index = to.blocks.len();
+            line.y += offset;
+            line.baseline += offset;
+        }
+        to.lines.extend(lines);
+        to.used_height += height;
+        to.blocks.push(block);
+        to.continuations.push(continuation);
     }
     
     /// 创建空页面
@@ -323,13 +939,15 @@ impl LayoutEngine {
     fn create_empty_page(&self) -> Page {
         Page {
             blocks: Vec::new(),
+            continuations: Vec::new(),
+            lines: Vec::new(),
             used_height: 0.0,
         }
     }
     
     /// 测量块尺寸
     /// 
-    /// 根据内容和样式计算内容块的尺寸
+    /// 按排版设置断行，以行数乘以行高作为内容块的高度
     /// 
     /// # Arguments
     /// 
@@ -339,15 +957,59 @@ impl LayoutEngine {
     /// 
     /// 返回内容块的尺寸信息
     pub fn measure_block(&self, block: &ContentBlock) -> LayoutMetrics {
-        // 简化的测量逻辑 - 实际应该基于字体、文本内容等计算
-        let lines = block.content.lines().count().max(1) as f32;
-        // 使用字体大小和行高（1.2倍）计算高度
-        let height = lines * block.styles.font_size * 1.2; 
-        
-        LayoutMetrics {
+        self.block_metrics(block, self.break_block(block).len())
+    }
+    
+    /// 按行数计算块尺寸
+    fn block_metrics(&self, block: &ContentBlock, line_count: usize) -> LayoutMetrics {
+        let mut metrics = LayoutMetrics {
             width: self.page_config.content_width(),
-            height,
+            height: line_count as f32 * self.measurer.line_height(&block.styles),
+        };
+        if block.block_type == ContentBlockType::SceneBreak {
+            metrics.height += self.scene_break_style.spacing_before + self.scene_break_style.spacing_after;
         }
+        metrics
+    }
+    
+    /// 按内容宽度和排版设置中的断行方式、断字设置对内容块断行，并按对齐方式设置各行的位置
+    /// 
+    /// 诗词块的各行始终居中
+    /// 
+    /// # Arguments
+    /// 
+    /// * `block` - 需要断行的内容块
+    /// 
+    /// # Returns
+    /// 
+    /// 返回内容块的各行，包括行宽、水平偏移和两端对齐的字形间距
+    pub fn break_block(&self, block: &ContentBlock) -> Vec<Line> {
+        let content_width = self.page_config.content_width();
+        let mut lines = match &self.typography.hyphenation {
+            Some(hyphenation) => break_lines_hyphenated(
+                &block.content,
+                &block.styles,
+                self.measurer.as_ref(),
+                content_width,
+                &self.typography.line_break,
+                block.language.as_deref(),
+                hyphenation,
+            ),
+            None => break_lines_with(
+                &block.content,
+                &block.styles,
+                self.measurer.as_ref(),
+                content_width,
+                &self.typography.line_break,
+            ),
+        };
+        let align = if block.block_type == ContentBlockType::Verse {
+            TextAlign::Center
+        } else {
+            self.typography.align
+        };
+        align_lines(&block.content, &mut lines, content_width, align, self.typography.justify);
+        lines
     }
     
     /// 检查块是否能放入页面
@@ -365,4 +1027,23 @@ impl LayoutEngine {
     pub fn can_fit_in_page(&self, metrics: &LayoutMetrics, page: &Page) -> bool {
         page.used_height + metrics.height <= self.page_config.content_height()
     }
-}
\ No newline at end of file
+}
+
+/// 根据正文样式生成章节标题的样式
+/// 
+/// # Arguments
+/// 
+/// * `base` - 章节正文的样式，没有正文时使用默认字号
+/// 
+/// # Returns
+/// 
+/// 返回加粗、字号为正文1.5倍的样式
+fn title_style(base: Option<&TextStyle>) -> TextStyle {
+    let (font_size, font_family) = base.map_or((16.0, Cow::Borrowed("serif")), |style| (style.font_size, style.font_family.clone()));
+    TextStyle {
+        font_size: font_size * 1.5,
+        font_family,
+        bold: true,
+        italic: false,
+    }
+}
@@ -3,6 +3,7 @@
 //! 测试布局引擎相关功能的正确性
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use crate::layout::*;
     use crate::document::*;
@@ -59,6 +60,7 @@ mod tests {
                 title: "Test".into(),
                 author: "Author".into(),
                 created_at: "2023-01-01".into(),
+                language: None,
             },
             chapters: vec![],
             styles: vec![],
@@ -94,6 +96,7 @@ mod tests {
             content: "This is a test block.".into(),
             styles: style,
             metrics: None,
+            language: None,
         };
 
         let document = DocumentModel {
@@ -101,11 +104,15 @@ mod tests {
                 title: "Test".into(),
                 author: "Author".into(),
                 created_at: "2023-01-01".into(),
+                language: None,
             },
             chapters: vec![Chapter {
                 id: "1".into(),
                 title: "Test Chapter".into(),
+                number: None,
                 content: vec![block],
+                footnote_refs: vec![],
+                author_note: false,
             }],
             styles: vec![],
         };
@@ -141,6 +148,7 @@ mod tests {
             content: "Line 1\nLine 2\nLine 3".into(),
             styles: style,
             metrics: None,
+            language: None,
         };
 
         let metrics = engine.measure_block(&block);
@@ -164,6 +172,8 @@ mod tests {
         
         let page = Page {
             blocks: vec![],
+            continuations: vec![],
+            lines: vec![],
             used_height: 100.0,
         };
 
@@ -182,4 +192,492 @@ mod tests {
         // 大块不应该能适应
         assert!(!engine.can_fit_in_page(&metrics_large, &page));
     }
-}
\ No newline at end of file
+
+    /// 构建由若干单行段落和一个场景分隔符组成的文档
+    fn scene_break_document(lines_before: usize, lines_after: usize) -> DocumentModel {
+        let style = TextStyle {
+            font_size: 16.0,
+            font_family: "Arial".into(),
+            bold: false,
+            italic: false,
+        };
+        let text = |index: usize| ContentBlock {
+            block_type: ContentBlockType::Text,
+            content: format!("第{}段", index).into(),
+            styles: style.clone(),
+            metrics: None,
+            language: None,
+        };
+        
+        let mut content: Vec<ContentBlock> = (0..lines_before).map(text).collect();
+        content.push(ContentBlock {
+            block_type: ContentBlockType::SceneBreak,
+            content: "***".into(),
+            styles: style.clone(),
+            metrics: None,
+            language: None,
+        });
+        content.extend((lines_before..lines_before + lines_after).map(text));
+        
+        DocumentModel {
+            metadata: DocumentMetadata {
+                title: "Test".into(),
+                author: "Author".into(),
+                created_at: "2023-01-01".into(),
+                language: None,
+            },
+            chapters: vec![Chapter {
+                id: "chapter_0".into(),
+                title: "Chapter 1".into(),
+                number: None,
+                content,
+                footnote_refs: vec![],
+                author_note: false,
+            }],
+            styles: vec![style],
+        }
+    }
+
+    /// 构建由若干段落组成的文档
+    fn paragraph_document(paragraphs: Vec<String>) -> DocumentModel {
+        let mut document = scene_break_document(0, 0);
+        let style = document.styles[0].clone();
+        document.chapters[0].content = paragraphs.into_iter().map(|content| ContentBlock {
+            block_type: ContentBlockType::Text,
+            content: content.into(),
+            styles: style.clone(),
+            metrics: None,
+            language: None,
+        }).collect();
+        document
+    }
+
+    /// 测试放不下的段落先填满当前页再接续到下一页
+    #[test]
+    fn test_split_paragraph_fills_page() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        let engine = LayoutEngine::new(config.clone());
+        // 每行22个字，每页29行；长段落共28行
+        let long = "山下有人。".repeat(120);
+        let mut paragraphs: Vec<String> = (0..10).map(|index| format!("第{}段", index)).collect();
+        paragraphs.push(long.clone());
+        paragraphs.push("尾段".to_string());
+        let pages = engine.layout_document(&paragraph_document(paragraphs));
+
+        assert_eq!(pages.len(), 2);
+        for page in &pages {
+            assert_eq!(page.continuations.len(), page.blocks.len());
+            assert_line_boxes(page, &config);
+        }
+        // 第一页不留空白
+        assert_eq!(pages[0].blocks.len(), 11);
+        assert_eq!(pages[0].lines.len(), 29);
+        assert_eq!(pages[0].continuations[10], Continuation { from_previous: false, to_next: true });
+        // 第二页从接续的片段开始，尾段紧随其后
+        assert_eq!(pages[1].continuations[0], Continuation { from_previous: true, to_next: false });
+        assert_eq!(pages[1].block_lines(0).count(), 9);
+        assert_eq!(pages[1].blocks[1].content, "尾段");
+        assert_eq!(pages[1].continuations[1], Continuation::default());
+        assert_eq!(format!("{}{}", pages[0].blocks[10].content, pages[1].blocks[0].content), long);
+    }
+
+    /// 测试孤行和寡行控制
+    #[test]
+    fn test_widows_and_orphans() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        // 前面有若干单行段落，最后是一个有指定行数的段落；每行22个字，每页29行
+        let layout = |before: usize, lines: usize, pagination: PaginationConfig| {
+            let mut paragraphs: Vec<String> = (0..before).map(|index| format!("第{}段", index)).collect();
+            paragraphs.push("山".repeat(lines * 22));
+            let engine = LayoutEngine::new(config.clone()).with_pagination(pagination);
+            let pages = engine.layout_document(&paragraph_document(paragraphs));
+            assert_eq!(pages.len(), 2);
+            let last = pages[1].blocks.len() - 1;
+            (pages[0].lines.len() - before, pages[1].block_lines(last).count())
+        };
+        let relaxed = PaginationConfig { orphans: 1, widows: 1, ..PaginationConfig::default() };
+
+        // 页尾只剩一行时整段移到下一页
+        assert_eq!(layout(28, 5, PaginationConfig::default()), (0, 5));
+        assert_eq!(layout(28, 5, relaxed.clone()), (1, 4));
+        // 下一页只有一行时从本页多移一行过去
+        assert_eq!(layout(20, 10, PaginationConfig::default()), (8, 2));
+        assert_eq!(layout(20, 10, PaginationConfig { widows: 3, ..PaginationConfig::default() }), (7, 3));
+        assert_eq!(layout(20, 10, relaxed), (9, 1));
+        // 段落短于孤行和寡行之和时不拆分
+        assert_eq!(layout(27, 3, PaginationConfig::default()), (0, 3));
+    }
+
+    /// 测试标题与其后的正文保持在同一页
+    #[test]
+    fn test_title_keep_with_next() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        // 前面有若干单行段落，之后是标题和5行的正文；每页29行
+        let layout = |before: usize, pagination: PaginationConfig| {
+            let mut paragraphs: Vec<String> = (0..before).map(|index| format!("第{}段", index)).collect();
+            paragraphs.push("第一节".to_string());
+            paragraphs.push("山".repeat(5 * 22));
+            let mut document = paragraph_document(paragraphs);
+            document.chapters[0].content[before].block_type = ContentBlockType::Title;
+            let pages = LayoutEngine::new(config.clone()).with_pagination(pagination).layout_document(&document);
+            let title_page = pages.iter().position(|page| page.blocks.iter().any(|block| block.block_type == ContentBlockType::Title)).unwrap();
+            (title_page, pages[title_page].lines.len())
+        };
+
+        // 页尾放得下标题和两行正文时标题留在本页，正文拆分后本页排满
+        assert_eq!(layout(26, PaginationConfig::default()), (0, 29));
+        // 页尾只剩标题的位置时标题移到下一页
+        assert_eq!(layout(28, PaginationConfig::default()).0, 1);
+        assert_eq!(layout(27, PaginationConfig::default()).0, 1);
+        // 要求标题后至少有四行正文时，按寡行控制5行的正文只能整段跟随标题
+        let pagination = PaginationConfig { min_lines_after_heading: 4, ..PaginationConfig::default() };
+        assert_eq!(layout(24, pagination.clone()).0, 1);
+        assert_eq!(layout(23, pagination), (0, 29));
+    }
+
+    /// 测试章节的起始位置
+    #[test]
+    fn test_chapter_start() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        let mut document = paragraph_document(vec!["第一章正文".to_string()]);
+        for index in 1..3 {
+            let mut chapter = document.chapters[0].clone();
+            chapter.id = format!("chapter_{}", index).into();
+            document.chapters.push(chapter);
+        }
+        let layout = |chapter_start: ChapterStart| {
+            let pagination = PaginationConfig { chapter_start, ..PaginationConfig::default() };
+            LayoutEngine::new(config.clone()).with_pagination(pagination).layout_document(&document)
+        };
+
+        let pages = layout(ChapterStart::NewPage);
+        assert_eq!(pages.len(), 3);
+        assert!(pages.iter().all(|page| page.blocks.len() == 1));
+
+        // 第二、三章从第3、5页开始，之前插入空白页
+        let pages = layout(ChapterStart::OddPage);
+        let blocks: Vec<usize> = pages.iter().map(|page| page.blocks.len()).collect();
+        assert_eq!(blocks, vec![1, 0, 1, 0, 1]);
+
+        // 接排时每章开头排出章节标题，标题字号为正文的1.5倍
+        let pages = layout(ChapterStart::Continuous { spacing: 40.0 });
+        assert_eq!(pages.len(), 1);
+        assert_line_boxes(&pages[0], &config);
+        let types: Vec<&ContentBlockType> = pages[0].blocks.iter().map(|block| &block.block_type).collect();
+        assert_eq!(types, [&ContentBlockType::Title, &ContentBlockType::Text].repeat(3));
+        let chapter_height = 16.0 * 1.5 * 1.2 + 16.0 * 1.2;
+        assert_eq!(pages[0].lines[2].y, pages[0].lines[0].y + chapter_height + 40.0);
+        assert_eq!(pages[0].used_height, 3.0 * chapter_height + 2.0 * 40.0);
+    }
+
+    /// 测试解析出的章节标题参与分页并与正文保持在同一页
+    #[test]
+    fn test_parsed_chapter_titles() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        // 第一章占标题和25行，加上章节间距后页尾只放得下第二章的标题
+        let paragraphs: Vec<String> = (1..=25).map(|index| format!("第{}段。", index)).collect();
+        let content = format!("第一章 出发\n{}\n\n第二章 归来\n{}", paragraphs.join("\n\n"), "山".repeat(3 * 22));
+        let document = crate::parser::ParserEngine::new().parse_txt(&content);
+        assert_eq!(document.chapters.len(), 2);
+        
+        let pagination = PaginationConfig { chapter_start: ChapterStart::Continuous { spacing: 20.0 }, ..PaginationConfig::default() };
+        let pages = LayoutEngine::new(config.clone()).with_pagination(pagination).layout_document(&document);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].blocks[0].block_type, ContentBlockType::Title);
+        assert_eq!(pages[0].blocks[0].content, "第一章 出发");
+        assert!(pages[0].blocks[0].styles.bold);
+        // 第二章的标题不会单独留在页尾
+        assert_eq!(pages[0].blocks.last().unwrap().block_type, ContentBlockType::Text);
+        assert_eq!(pages[1].blocks[0].content, "第二章 归来");
+        assert_eq!(pages[1].blocks[1].content, "山".repeat(3 * 22));
+        
+        // 分页排版时需要设置才排出章节标题
+        let pages = LayoutEngine::new(config.clone()).layout_document(&document);
+        assert!(pages.iter().flat_map(|page| &page.blocks).all(|block| block.block_type == ContentBlockType::Text));
+        let pages = LayoutEngine::new(config).with_chapter_titles(true).layout_document(&document);
+        assert_eq!(pages.len(), 2);
+        assert!(pages.iter().all(|page| page.blocks[0].block_type == ContentBlockType::Title));
+    }
+
+    /// 检查页面的行框与内容块一致：各行按顺序覆盖内容块的文本，从上到下排列且不超出内容区域
+    fn assert_line_boxes(page: &Page, config: &PageConfig) {
+        for (index, block) in page.blocks.iter().enumerate() {
+            let text: String = page.block_lines(index).map(|line| page.line_text(line)).collect();
+            assert_eq!(text.replace('\n', ""), block.content.replace('\n', ""));
+        }
+        let mut bottom = config.margin_top;
+        for line in &page.lines {
+            assert!(line.y >= bottom - 0.01);
+            assert!(line.y < line.baseline && line.baseline < line.y + line.height);
+            assert!(line.x >= config.margin_left && line.x + line.width <= config.width - config.margin_right + 0.01);
+            bottom = line.y + line.height;
+        }
+        assert!(bottom <= config.margin_top + config.content_height() + 0.01);
+    }
+
+    /// 测试页面上的行框
+    #[test]
+    fn test_line_boxes() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 30.0,
+            margin_left: 25.0,
+            margin_right: 20.0,
+        };
+        let typography = TypographyConfig {
+            align: crate::align::TextAlign::Right,
+            ..TypographyConfig::default()
+        };
+        let engine = LayoutEngine::new(config.clone()).with_typography(typography);
+        let document = scene_break_document(3, 2);
+        let pages = engine.layout_document(&document);
+        assert_eq!(pages.len(), 1);
+        let page = &pages[0];
+        assert_line_boxes(page, &config);
+
+        // 每个单行段落一行，分隔符上下留出间距
+        assert_eq!(page.lines.len(), page.blocks.len());
+        let first = &page.lines[0];
+        assert_eq!(page.line_text(first), "第0段");
+        assert_eq!(first.y, 20.0);
+        assert_eq!(first.height, 16.0 * 1.2);
+        assert_eq!(first.baseline, 20.0 + 16.0 * 0.1 + 16.0 * 0.88);
+        // 右对齐的行靠右边距
+        assert_eq!(first.x + first.width, 400.0 - 20.0);
+        let scene_break = &page.lines[3];
+        assert_eq!(page.blocks[scene_break.block_index].block_type, ContentBlockType::SceneBreak);
+        assert_eq!(scene_break.y, 20.0 + 3.0 * 16.0 * 1.2 + SceneBreakStyle::default().spacing_before);
+        assert_eq!(page.lines.last().unwrap().y, 20.0 + page.used_height - 16.0 * 1.2);
+    }
+
+    /// 测试页面结构可以序列化，前端直接按引擎的行框绘制
+    #[test]
+    fn test_page_serializable() {
+        fn assert_serialize<T: serde::Serialize>() {}
+        assert_serialize::<Page>();
+        assert_serialize::<LineBox>();
+        assert_serialize::<Continuation>();
+        assert_serialize::<crate::align::GlyphAdjustment>();
+    }
+
+    /// 测试场景分隔符不会成为页面的第一个或最后一个元素
+    #[test]
+    fn test_scene_break_never_at_page_edge() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        
+        // 让分隔符依次落在第一页末尾附近的每个位置
+        for lines_before in 20..35 {
+            let engine = LayoutEngine::new(config.clone());
+            let pages = engine.layout_document(&scene_break_document(lines_before, 10));
+            
+            let mut scene_breaks = 0;
+            for page in &pages {
+                assert!(page.used_height <= config.content_height());
+                assert_line_boxes(page, &config);
+                assert_ne!(page.blocks.first().unwrap().block_type, ContentBlockType::SceneBreak);
+                assert_ne!(page.blocks.last().unwrap().block_type, ContentBlockType::SceneBreak);
+                scene_breaks += page.blocks.iter()
+                    .filter(|block| block.block_type == ContentBlockType::SceneBreak)
+                    .count();
+            }
+            assert_eq!(scene_breaks, 1);
+            
+            // 所有段落都按顺序保留
+            let texts: Vec<&ContentBlock> = pages.iter()
+                .flat_map(|page| page.blocks.iter())
+                .filter(|block| block.block_type == ContentBlockType::Text)
+                .collect();
+            assert_eq!(texts.len(), lines_before + 10);
+            assert_eq!(texts[lines_before].content, format!("第{}段", lines_before));
+        }
+    }
+
+    /// 测试场景分隔符的装饰符号和间距
+    #[test]
+    fn test_scene_break_style() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        let style = SceneBreakStyle {
+            ornament: Some("◇ ◇ ◇".into()),
+            spacing_before: 10.0,
+            spacing_after: 30.0,
+        };
+        let engine = LayoutEngine::new(config).with_scene_break_style(style);
+        
+        let pages = engine.layout_document(&scene_break_document(1, 1));
+        assert_eq!(pages.len(), 1);
+        let scene_break = &pages[0].blocks[1];
+        assert_eq!(scene_break.block_type, ContentBlockType::SceneBreak);
+        assert_eq!(scene_break.content, "◇ ◇ ◇");
+        assert_eq!(engine.measure_block(scene_break).height, 16.0 * 1.2 + 40.0);
+        
+        // 不设置装饰符号时保留原文中的分隔符
+        let engine = LayoutEngine::new(PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        }).with_scene_break_style(SceneBreakStyle {
+            ornament: None,
+            ..SceneBreakStyle::default()
+        });
+        let pages = engine.layout_document(&scene_break_document(1, 1));
+        assert_eq!(pages[0].blocks[1].content, "***");
+        
+        // 位于章节开头或结尾的分隔符被忽略（缓存键只包含章节标题，需要新的引擎）
+        let engine = LayoutEngine::new(PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        });
+        let pages = engine.layout_document(&scene_break_document(0, 1));
+        assert_eq!(pages[0].blocks.len(), 1);
+    }
+
+    /// 测试诗节不会被拆分到两页
+    #[test]
+    fn test_verse_kept_together() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        let engine = LayoutEngine::new(config);
+        
+        let mut document = scene_break_document(27, 0);
+        let chapter = &mut document.chapters[0];
+        chapter.content.pop();
+        let stanza = ContentBlock {
+            block_type: ContentBlockType::Verse,
+            content: "青杜山前水，\n玉京城外云。\n一朝仙路断，\n百世子孙勤。".into(),
+            styles: chapter.content[0].styles.clone(),
+            metrics: None,
+            language: None,
+        };
+        chapter.content.push(stanza.clone());
+        
+        let pages = engine.layout_document(&document);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].blocks, vec![stanza]);
+    }
+    
+    /// 测试诗词块的各行居中
+    #[test]
+    fn test_verse_centered() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        let engine = LayoutEngine::new(config.clone());
+        
+        let mut document = scene_break_document(1, 0);
+        let chapter = &mut document.chapters[0];
+        chapter.content[0].block_type = ContentBlockType::Verse;
+        chapter.content[0].content = "青杜山前水，\n玉京城外云。".into();
+        
+        let pages = engine.layout_document(&document);
+        let lines: Vec<&LineBox> = pages[0].lines.iter().filter(|line| line.block_index == 0).collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            assert!(line.width < config.content_width());
+            assert_eq!(line.x, config.margin_left + (config.content_width() - line.width) / 2.0);
+        }
+        // 普通文本仍按排版设置左对齐
+        assert!(pages[0].lines.iter().filter(|line| line.block_index == 1).all(|line| line.x == config.margin_left));
+    }
+
+    /// 测试隐藏作者的话
+    #[test]
+    fn test_author_notes_hidden() {
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        
+        let mut document = scene_break_document(2, 1);
+        document.chapters[0].content[3].block_type = ContentBlockType::AuthorNote;
+        let mut notice = document.chapters[0].clone();
+        notice.id = "chapter_1".into();
+        notice.title = "请假条".into();
+        notice.author_note = true;
+        document.chapters.push(notice);
+        
+        let pages = LayoutEngine::new(config.clone()).layout_document(&document);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].blocks.len(), 4);
+        
+        // 隐藏后场景分隔符位于章节末尾，也一并被忽略
+        let engine = LayoutEngine::new(config).with_author_notes_hidden(true);
+        let pages = engine.layout_document(&document);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].blocks.len(), 2);
+        assert!(pages[0].blocks.iter().all(|block| block.block_type == ContentBlockType::Text));
+        assert!(engine.layout_document_chapter(&document, 1).is_empty());
+    }
+}
@@ -7,15 +7,53 @@
 //! 排版引擎采用分层架构设计：
 //! 
 //! - [file_loader] - 文件加载器
+//! - [format_registry] - 文档解析器注册表
 //! - [parser] - 解析引擎
+//! - [footnote] - 脚注提取
+//! - [author_note] - 作者的话识别
+//! - [toc] - 目录识别
+//! - [verse] - 诗词识别
+//! - [numeral] - 中文数字解析
+//! - [sentence] - 分句
+//! - [word_segment] - 中文分词
+//! - [language] - 语言识别
 //! - [document] - 文档模型定义
+//! - [edition] - 版本合并
+//! - [font] - 字体读取
+//! - [measure] - 文本测量
+//! - [segment] - 字素簇和断行机会
+//! - [hyphenate] - 断字
+//! - [line_break] - 断行
+//! - [optimal_break] - 全段最优断行
+//! - [align] - 对齐
 //! - [layout] - 布局引擎
+//! - [chinese_convert] - 简繁转换
+//! - [mojibake] - 乱码修复
 
 mod file_loader;
+mod format_registry;
 mod parser;
+mod footnote;
+mod author_note;
+mod toc;
+mod verse;
+mod numeral;
+mod sentence;
+mod word_segment;
+mod language;
 mod document;
+mod edition;
+mod font;
+mod measure;
+mod segment;
+mod hyphenate;
+mod line_break;
+mod optimal_break;
+mod align;
 mod layout;
 mod async_ops;
+mod chinese_convert;
+mod mojibake;
 
 // 测试模块
 #[cfg(test)]
@@ -24,12 +62,54 @@ mod document_test;
 mod parser_test;
 #[cfg(test)]
 mod layout_test;
+#[cfg(test)]
+mod chinese_convert_test;
+#[cfg(test)]
+mod numeral_test;
+#[cfg(test)]
+mod sentence_test;
+#[cfg(test)]
+mod word_segment_test;
+#[cfg(test)]
+mod edition_test;
+#[cfg(test)]
+mod mojibake_test;
+#[cfg(test)]
+mod language_test;
+#[cfg(test)]
+mod measure_test;
+#[cfg(test)]
+mod segment_test;
+#[cfg(test)]
+mod hyphenate_test;
+#[cfg(test)]
+mod line_break_test;
+#[cfg(test)]
+mod optimal_break_test;
+#[cfg(test)]
+mod align_test;
 
 pub use file_loader::*;
+pub use format_registry::*;
 pub use parser::*;
+pub use toc::*;
+pub use numeral::*;
+pub use sentence::*;
+pub use word_segment::*;
+pub use language::*;
 pub use document::*;
+pub use edition::*;
+pub use font::*;
+pub use measure::*;
+pub use segment::*;
+pub use hyphenate::*;
+pub use line_break::*;
+pub use optimal_break::*;
+pub use align::*;
 pub use layout::*;
 pub use async_ops::*;
+pub use chinese_convert::*;
+pub use mojibake::*;
 
 /// 预导入模块
 /// 
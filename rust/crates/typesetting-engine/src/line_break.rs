@@ -0,0 +1,401 @@
+//! 断行
+//!
+//! 按可用宽度把文本填充成行：在UAX #14的断行机会上再加以中日文行首、行尾禁则，
+//! 并可以在单词内部的断字位置加连字符断开，放不下的片段按字素簇强制断开
+
+use crate::align::GlyphAdjustment;
+use crate::document::TextStyle;
+use crate::hyphenate::{hyphenation_points, soft_hyphens, HyphenationConfig};
+use crate::measure::TextMeasurer;
+use crate::optimal_break::TotalFitConfig;
+use crate::segment::{graphemes, line_break_opportunities, BreakOpportunity};
+use std::ops::Range;
+
+/// 一行文本
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    /// 行在源文本中的字节范围，包含行尾的空格，不包含换行符
+    pub byte_range: Range<usize>,
+    /// 行宽（像素），不计行尾的空格。全段最优断行中需要压缩空格的行会略大于可用宽度
+    pub width: f32,
+    /// 是否为段落的最后一行，即在换行符或文本末尾处结束
+    pub ends_paragraph: bool,
+    /// 是否在单词内部的断字位置断开，此时行尾需要显示一个连字符，行宽已包含连字符
+    pub hyphenated: bool,
+    /// 行相对于内容区域左边缘的水平偏移（像素），由对齐方式决定
+    pub x: f32,
+    /// 两端对齐时在字形之间插入的额外间距
+    pub adjustments: Vec<GlyphAdjustment>,
+}
+
+/// 判断字符是否不能出现在行首
+///
+/// # Arguments
+///
+/// * `c` - 字符
+///
+/// # Returns
+///
+/// 字符是句末点号、后半括号、后引号等不能位于行首的字符时返回true
+pub fn is_line_start_prohibited(c: char) -> bool {
+    matches!(c,
+        '。' | '，' | '、' | '．' | '：' | '；' | '！' | '？' | '）' | '」' | '』' | '】' | '〕' | '〉' | '》' | '〗' | '〙' | '〛'
+        | '｝' | '］' | '”' | '’' | '・' | '‥' | 'ー' | '々' | '〻' | 'ゝ' | 'ゞ' | 'ヽ' | 'ヾ'
+        | 'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' | 'っ' | 'ゃ' | 'ゅ' | 'ょ' | 'ゎ'
+        | 'ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ' | 'ッ' | 'ャ' | 'ュ' | 'ョ' | 'ヮ' | 'ヵ' | 'ヶ'
+        | '‼' | '⁇' | '⁈' | '⁉' | '%' | '‰' | '℃' | '％'
+        | ',' | '.' | '!' | '?' | ';' | ':' | ')' | ']' | '}')
+}
+
+/// 判断字符是否不能出现在行尾
+///
+/// # Arguments
+///
+/// * `c` - 字符
+///
+/// # Returns
+///
+/// 字符是前半括号、前引号等不能位于行尾的字符时返回true
+pub fn is_line_end_prohibited(c: char) -> bool {
+    matches!(c,
+        '（' | '「' | '『' | '【' | '〔' | '〈' | '《' | '〖' | '〘' | '〚' | '｛' | '［' | '“' | '‘'
+        | '(' | '[' | '{' | '$' | '￥' | '＄' | '£')
+}
+
+/// 判断字符是否为行尾可以悬挂的半角空白
+pub(crate) fn is_space(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+/// 判断字符是否为换行符
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{B}' | '\u{C}' | '\u{85}' | '\u{2028}' | '\u{2029}')
+}
+
+/// 获取断行机会
+///
+/// 在UAX #14的断行机会中去掉违反行首、行尾禁则的位置
+///
+/// # Arguments
+///
+/// * `text` - 需要断行的文本
+///
+/// # Returns
+///
+/// 返回断行机会，按偏移升序排列，文本末尾是强制断行
+pub fn break_opportunities(text: &str) -> Vec<BreakOpportunity> {
+    line_break_opportunities(text)
+        .into_iter()
+        .filter(|opportunity| {
+            if opportunity.mandatory {
+                return true;
+            }
+            let before = text[..opportunity.offset].chars().next_back();
+            let after = text[opportunity.offset..].chars().next();
+            !before.is_some_and(is_line_end_prohibited) && !after.is_some_and(is_line_start_prohibited)
+        })
+        .collect()
+}
+
+/// 断行方式
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum LineBreakMode {
+    /// 贪心断行：每行放入尽可能多的内容，速度最快
+    #[default]
+    Greedy,
+    /// 全段最优断行（Knuth–Plass）：使整段各行的疏密总体最均匀
+    TotalFit(TotalFitConfig),
+}
+
+impl LineBreakMode {
+    /// 生成用于缓存的键
+    ///
+    /// # Returns
+    ///
+    /// 返回表示断行方式的字符串键
+    pub fn cache_key(&self) -> String {
+        match self {
+            LineBreakMode::Greedy => String::from("greedy"),
+            LineBreakMode::TotalFit(config) => format!("total-fit-{}", config.cache_key()),
+        }
+    }
+}
+
+/// 把文本按可用宽度断行
+///
+/// 换行符是强制断行，空行也占一行。贪心地在每行放入尽可能多的内容；
+/// 放不下的单个片段（如超长的单词）按字素簇强制断开
+///
+/// # Arguments
+///
+/// * `text` - 需要断行的文本
+/// * `style` - 文本样式
+/// * `measurer` - 文本测量器
+/// * `max_width` - 可用宽度（像素）
+///
+/// # Returns
+///
+/// 返回断行结果，至少包含一行
+pub fn break_lines<M: TextMeasurer + ?Sized>(text: &str, style: &TextStyle, measurer: &M, max_width: f32) -> Vec<Line> {
+    break_lines_with(text, style, measurer, max_width, &LineBreakMode::Greedy)
+}
+
+/// 按指定的断行方式把文本按可用宽度断行
+///
+/// 全段最优断行找不到可行的方案时（如某个片段比可用宽度还宽），该段退回贪心断行。
+/// 只在软连字符处断字，不使用断字模式
+///
+/// # Arguments
+///
+/// * `text` - 需要断行的文本
+/// * `style` - 文本样式
+/// * `measurer` - 文本测量器
+/// * `max_width` - 可用宽度（像素）
+/// * `mode` - 断行方式
+///
+/// # Returns
+///
+/// 返回断行结果，至少包含一行
+pub fn break_lines_with<M: TextMeasurer + ?Sized>(
+    text: &str,
+    style: &TextStyle,
+    measurer: &M,
+    max_width: f32,
+    mode: &LineBreakMode,
+) -> Vec<Line> {
+    let limit = HyphenationConfig::default().max_consecutive;
+    LineBreaker::new(text, style, measurer, max_width, soft_hyphens(text), limit).break_lines(mode)
+}
+
+/// 按指定的断行方式和断字设置把文本按可用宽度断行
+///
+/// 除软连字符外，还按文本语言的断字模式在单词内部断字
+///
+/// # Arguments
+///
+/// * `text` - 需要断行的文本
+/// * `style` - 文本样式
+/// * `measurer` - 文本测量器
+/// * `max_width` - 可用宽度（像素）
+/// * `mode` - 断行方式
+/// * `language` - 文本的语言，为None时按文本识别
+/// * `hyphenation` - 断字设置
+///
+/// # Returns
+///
+/// 返回断行结果，至少包含一行
+pub fn break_lines_hyphenated<M: TextMeasurer + ?Sized>(
+    text: &str,
+    style: &TextStyle,
+    measurer: &M,
+    max_width: f32,
+    mode: &LineBreakMode,
+    language: Option<&str>,
+    hyphenation: &HyphenationConfig,
+) -> Vec<Line> {
+    let hyphens = hyphenation_points(text, language, hyphenation);
+    LineBreaker::new(
+        text,
+        style,
+        measurer,
+        max_width,
+        hyphens,
+        hyphenation.max_consecutive,
+    )
+    .break_lines(mode)
+}
+
+/// 断行的状态
+pub(crate) struct LineBreaker<'a, M: TextMeasurer + ?Sized> {
+    /// 源文本
+    pub(crate) text: &'a str,
+    /// 文本样式
+    pub(crate) style: &'a TextStyle,
+    /// 文本测量器
+    pub(crate) measurer: &'a M,
+    /// 可用宽度
+    pub(crate) max_width: f32,
+    /// 断字位置，按升序排列
+    pub(crate) hyphens: Vec<usize>,
+    /// 最多允许连续断字的行数
+    pub(crate) hyphen_limit: usize,
+    /// 已断出的行
+    pub(crate) lines: Vec<Line>,
+}
+
+impl<'a, M: TextMeasurer + ?Sized> LineBreaker<'a, M> {
+    /// 创建断行状态
+    fn new(
+        text: &'a str,
+        style: &'a TextStyle,
+        measurer: &'a M,
+        max_width: f32,
+        hyphens: Vec<usize>,
+        hyphen_limit: usize,
+    ) -> Self {
+        LineBreaker {
+            text,
+            style,
+            measurer,
+            max_width,
+            hyphens,
+            hyphen_limit,
+            lines: Vec::new(),
+        }
+    }
+}
+
+impl<M: TextMeasurer + ?Sized> LineBreaker<'_, M> {
+    /// 按断行方式对整个文本断行
+    fn break_lines(mut self, mode: &LineBreakMode) -> Vec<Line> {
+        let text = self.text;
+        let mut start = 0;
+        let mut opportunities = Vec::new();
+        for opportunity in break_opportunities(text) {
+            if !opportunity.mandatory {
+                opportunities.push(opportunity.offset);
+                continue;
+            }
+            let end = start
+                + text[start..opportunity.offset]
+                    .trim_end_matches(is_line_terminator)
+                    .len();
+            opportunities.extend(
+                self.hyphens
+                    .iter()
+                    .copied()
+                    .filter(|&offset| start < offset && offset < end),
+            );
+            opportunities.retain(|&offset| offset < end);
+            opportunities.sort_unstable();
+            opportunities.dedup();
+            opportunities.push(end);
+            match mode {
+                LineBreakMode::TotalFit(config)
+                    if self.fill_total_fit(start, &opportunities, config) => {}
+                _ => self.fill(start, &opportunities),
+            }
+            if let Some(line) = self.lines.last_mut() {
+                line.ends_paragraph = true;
+            }
+            opportunities.clear();
+            start = opportunity.offset;
+        }
+        if text.is_empty() {
+            self.push(0..0, 0.0);
+            self.lines[0].ends_paragraph = true;
+        }
+        self.lines
+    }
+
+    /// 填充两个强制断行之间的文本
+    ///
+    /// `opportunities` 是其中的断行机会，最后一个是文本的结尾
+    fn fill(&mut self, start: usize, opportunities: &[usize]) {
+        let end = opportunities.last().copied().unwrap_or(start);
+        let mut line_start = start;
+        // 当前行含行尾空格的宽度和不含行尾空格的宽度
+        let mut width = 0.0;
+        let mut trimmed_width = 0.0;
+        // 当前行内的断行机会及在此处断行时的行宽
+        let mut candidates: Vec<(usize, f32)> = Vec::new();
+        let mut segment_start = start;
+        for &segment_end in opportunities {
+            let (segment_width, segment_trimmed) = self.measure(segment_start..segment_end);
+            while line_start < segment_start && width + segment_trimmed > self.max_width {
+                // 连续断字的行数达到上限时改在之前不断字的位置断行，找不到时仍然断字
+                let (offset, line_width) = candidates
+                    .iter()
+                    .rev()
+                    .find(|&&(offset, _)| self.can_break_at(offset))
+                    .copied()
+                    .unwrap_or((segment_start, trimmed_width));
+                self.push(line_start..offset, line_width);
+                line_start = offset;
+                width = self.measure(line_start..segment_start).0;
+                candidates.retain(|&(candidate, _)| candidate > offset);
+                for candidate in candidates.iter_mut() {
+                    candidate.1 = self.measure(line_start..candidate.0).1;
+                }
+            }
+            if line_start == segment_start && segment_trimmed > self.max_width {
+                (line_start, width) = self.force_break(segment_start..segment_end);
+                trimmed_width = self.measure(line_start..segment_end).1;
+                candidates.clear();
+            } else {
+                trimmed_width = width + segment_trimmed;
+                width += segment_width;
+            }
+            candidates.push((segment_end, trimmed_width));
+            segment_start = segment_end;
+        }
+        self.push(line_start..end, trimmed_width);
+    }
+
+    /// 把放不下的片段按字素簇强制断开
+    ///
+    /// 返回最后一行未满的部分的起点和宽度
+    fn force_break(&mut self, range: Range<usize>) -> (usize, f32) {
+        let mut line_start = range.start;
+        let mut offset = range.start;
+        let mut width = 0.0;
+        for grapheme in graphemes(&self.text[range]) {
+            let grapheme_width = self.measurer.measure_text(grapheme, self.style).width;
+            if offset > line_start && width + grapheme_width > self.max_width && !grapheme.starts_with(is_space) {
+                self.push(line_start..offset, width);
+                line_start = offset;
+                width = 0.0;
+            }
+            width += grapheme_width;
+            offset += grapheme.len();
+        }
+        (line_start, width)
+    }
+
+    /// 测量文本，返回含行尾空格的宽度和在结尾处断行时的行宽
+    ///
+    /// 行宽不计行尾的空格，在断字位置断行时包含连字符
+    pub(crate) fn measure(&self, range: Range<usize>) -> (f32, f32) {
+        let hyphen = if self.is_hyphen(range.end) {
+            self.measurer.char_width('-', self.style)
+        } else {
+            0.0
+        };
+        let text = &self.text[range];
+        let trimmed = text.trim_end_matches(is_space);
+        (
+            self.measurer.measure_text(text, self.style).width,
+            self.measurer.measure_text(trimmed, self.style).width + hyphen,
+        )
+    }
+
+    /// 判断偏移是否为断字位置
+    pub(crate) fn is_hyphen(&self, offset: usize) -> bool {
+        self.hyphens.binary_search(&offset).is_ok()
+    }
+
+    /// 判断能否在偏移处断行：连续断字的行数达到上限时不能再断字
+    fn can_break_at(&self, offset: usize) -> bool {
+        !self.is_hyphen(offset)
+            || self
+                .lines
+                .iter()
+                .rev()
+                .take_while(|line| line.hyphenated)
+                .count()
+                < self.hyphen_limit
+    }
+
+    /// 添加一行
+    pub(crate) fn push(&mut self, byte_range: Range<usize>, width: f32) {
+        let hyphenated = self.is_hyphen(byte_range.end);
+        self.lines.push(Line {
+            byte_range,
+            width,
+            ends_paragraph: false,
+            hyphenated,
+            x: 0.0,
+            adjustments: Vec::new(),
+        });
+    }
+}
@@ -0,0 +1,99 @@
+//! 断行单元测试
+//!
+//! 测试按宽度断行和中日文禁则处理的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::document::TextStyle;
+    use crate::layout::{LayoutEngine, PageConfig};
+    use crate::line_break::*;
+    use crate::measure::ApproximateTextMeasurer;
+    use crate::parser::ParserEngine;
+    use std::borrow::Cow;
+
+    /// 使用估算测量器断行，全角字符宽10，半角字符宽5
+    fn lines(text: &str, max_width: f32) -> Vec<&str> {
+        let style = TextStyle {
+            font_size: 10.0,
+            font_family: Cow::Borrowed("serif"),
+            bold: false,
+            italic: false,
+        };
+        break_lines(text, &style, &ApproximateTextMeasurer, max_width)
+            .into_iter()
+            .map(|line| &text[line.byte_range])
+            .collect()
+    }
+
+    /// 测试行首和行尾禁则
+    #[test]
+    fn test_kinsoku() {
+        // 句号不能位于行首，前一个字随之换行
+        assert_eq!(lines("一二三四五。六七", 50.0), vec!["一二三四", "五。六七"]);
+        assert_eq!(lines("「一二三四」", 40.0), vec!["「一二三", "四」"]);
+        // 前括号不能位于行尾
+        assert_eq!(lines("一二三四「五六」", 50.0), vec!["一二三四", "「五六」"]);
+        // 省略号和破折号不拆开
+        assert_eq!(lines("一二三四五……", 55.0), vec!["一二三四", "五……"]);
+        assert_eq!(lines("一二三四五——六", 55.0), vec!["一二三四五", "——六"]);
+    }
+
+    /// 测试中文中夹杂的西文单词
+    #[test]
+    fn test_latin_words() {
+        assert_eq!(lines("中文English单词", 60.0), vec!["中文English", "单词"]);
+        assert_eq!(lines("中文English单词", 40.0), vec!["中文", "English", "单词"]);
+        assert_eq!(lines("hello world foo", 55.0), vec!["hello world ", "foo"]);
+        assert_eq!(lines("well-known fact", 40.0), vec!["well-", "known ", "fact"]);
+        // 单词过长时按字符强制断开
+        assert_eq!(lines("abcdefghij", 20.0), vec!["abcd", "efgh", "ij"]);
+    }
+
+    /// 测试行宽和强制断行
+    #[test]
+    fn test_line_widths() {
+        let style = TextStyle {
+            font_size: 10.0,
+            font_family: Cow::Borrowed("serif"),
+            bold: false,
+            italic: false,
+        };
+        let result = break_lines("hello world foo\n\n中文", &style, &ApproximateTextMeasurer, 55.0);
+        let widths: Vec<f32> = result.iter().map(|line| line.width).collect();
+        // 行尾空格不计入行宽，空行也占一行
+        assert_eq!(widths, vec![55.0, 15.0, 0.0, 20.0]);
+        assert_eq!(result[3].byte_range, 17..23);
+        assert_eq!(lines("", 50.0), vec![""]);
+        let offsets: Vec<usize> = break_opportunities("中文ab cd").iter().map(|opportunity| opportunity.offset).collect();
+        assert_eq!(offsets, vec![3, 6, 9, 11]);
+    }
+
+    /// 测试分页使用断行结果拆分过长的段落
+    #[test]
+    fn test_paginate_long_paragraph() {
+        let paragraph = "山下有人。".repeat(600);
+        let document = ParserEngine::new().parse_txt(&format!("第一章 青杜\n{}", paragraph));
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        let engine = LayoutEngine::new(config.clone());
+        let pages = engine.layout_document(&document);
+
+        // 每行最多22个字，每页29行
+        let lines = engine.break_block(&document.chapters[0].content[0]);
+        assert!(lines.len() >= 3000usize.div_ceil(22));
+        assert_eq!(pages.len(), lines.len().div_ceil(29));
+        assert!(pages.iter().all(|page| page.used_height <= config.content_height()));
+        // 拆分后每页的行框沿用整段的断行结果
+        let boxes: Vec<&str> = pages.iter().flat_map(|page| page.lines.iter().map(|line| page.line_text(line))).collect();
+        let expected: Vec<&str> = lines.iter().map(|line| &document.chapters[0].content[0].content[line.byte_range.clone()]).collect();
+        assert_eq!(boxes, expected);
+        let joined: String = pages.iter().flat_map(|page| page.blocks.iter().map(|block| block.content.as_ref())).collect();
+        assert_eq!(joined, paragraph);
+    }
+}
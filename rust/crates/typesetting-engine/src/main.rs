@@ -50,6 +50,7 @@ fn main() -> Result<()> {
         println!("\n--- Page {} ---", i + 1);
         println!("Used height: {}", page.used_height);
         println!("Blocks: {}", page.blocks.len());
+        println!("Lines: {}", page.lines.len());
         for (j, block) in page.blocks.iter().enumerate() {
             // 只显示内容的前100个字符以避免输出过多，确保在字符边界上截断
             let content_preview = if block.content.chars().count() > 100 {
@@ -0,0 +1,294 @@
+//! 文本测量
+//!
+//! 定义平台无关的文本测量接口，并提供按字符类别估算的测量器和基于字体文件的测量器。
+//! 布局引擎通过 [`TextMeasurer`] 测量内容块，应用程序可以接入自己的字体度量
+
+use crate::document::{ContentBlock, LayoutMetrics, TextStyle};
+use crate::font::Font;
+use crate::hyphenate::SOFT_HYPHEN;
+use crate::line_break::break_lines;
+use crate::segment::{graphemes, spacing_chars};
+use crate::prelude::*;
+
+/// 文本测量接口
+///
+/// 实现者只需提供单字符宽度和行高，单行文本和内容块的测量有默认实现
+pub trait TextMeasurer: Send + Sync {
+    /// 获取字符的步进宽度
+    ///
+    /// # Arguments
+    ///
+    /// * `c` - 字符
+    /// * `style` - 文本样式
+    ///
+    /// # Returns
+    ///
+    /// 返回字符的步进宽度（像素）
+    fn char_width(&self, c: char, style: &TextStyle) -> f32;
+
+    /// 获取行高
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - 文本样式
+    ///
+    /// # Returns
+    ///
+    /// 返回一行文本占用的高度（像素）
+    fn line_height(&self, style: &TextStyle) -> f32;
+
+    /// 获取基线位置
+    ///
+    /// 默认把行高多出字号的部分平分到上下两侧，上伸高度取0.88倍字号
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - 文本样式
+    ///
+    /// # Returns
+    ///
+    /// 返回基线到行顶部的距离（像素）
+    fn baseline(&self, style: &TextStyle) -> f32 {
+        (self.line_height(style) - style.font_size) / 2.0 + style.font_size * 0.88
+    }
+
+    /// 测量单行文本
+    ///
+    /// 按字素簇累加宽度，表情符号序列等组合只计算一次，控制字符和软连字符不占宽度
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 不含换行符的文本
+    /// * `style` - 文本样式
+    ///
+    /// # Returns
+    ///
+    /// 返回文本不换行时的宽度和一行的高度
+    fn measure_text(&self, text: &str, style: &TextStyle) -> LayoutMetrics {
+        LayoutMetrics {
+            width: graphemes(text)
+                .flat_map(spacing_chars)
+                .filter(|&c| !c.is_control() && c != SOFT_HYPHEN)
+                .map(|c| self.char_width(c, style))
+                .sum(),
+            height: self.line_height(style),
+        }
+    }
+
+    /// 测量内容块
+    ///
+    /// 按可用宽度断行后，以行数乘以行高作为高度
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - 需要测量的内容块
+    /// * `max_width` - 可用宽度（像素）
+    ///
+    /// # Returns
+    ///
+    /// 返回内容块占满可用宽度时的尺寸
+    fn measure_block(&self, block: &ContentBlock, max_width: f32) -> LayoutMetrics {
+        let lines = break_lines(&block.content, &block.styles, self, max_width);
+        LayoutMetrics {
+            width: max_width,
+            height: lines.len() as f32 * self.line_height(&block.styles),
+        }
+    }
+
+    /// 获取测量器的缓存键
+    ///
+    /// 测量结果不同的测量器必须返回不同的缓存键，布局缓存依赖于此
+    fn cache_key(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+}
+
+/// 估算测量器
+///
+/// 没有字体文件时使用：全角字符宽度为一个字号，半角字符为半个字号，组合附加符号不占宽度，
+/// 行高为字号的1.2倍
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApproximateTextMeasurer;
+
+impl TextMeasurer for ApproximateTextMeasurer {
+    fn char_width(&self, c: char, style: &TextStyle) -> f32 {
+        if is_combining(c) {
+            0.0
+        } else if is_wide(c) {
+            style.font_size
+        } else {
+            style.font_size * 0.5
+        }
+    }
+
+    fn line_height(&self, style: &TextStyle) -> f32 {
+        style.font_size * 1.2
+    }
+}
+
+/// 判断字符是否为全角字符（东亚宽字符）
+///
+/// # Arguments
+///
+/// * `c` - 字符
+///
+/// # Returns
+///
+/// 字符在排版中占一个字号宽度时返回true
+pub(crate) fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F      // 谚文字母
+        | 0x2E80..=0x303E    // 部首、CJK符号和标点
+        | 0x3041..=0x33FF    // 假名、注音、兼容字符
+        | 0x3400..=0x4DBF    // CJK扩展A
+        | 0x4E00..=0x9FFF    // CJK统一表意文字
+        | 0xA000..=0xA4CF    // 彝文
+        | 0xAC00..=0xD7A3    // 谚文音节
+        | 0xF900..=0xFAFF    // CJK兼容表意文字
+        | 0xFE30..=0xFE4F    // CJK兼容形式
+        | 0xFF00..=0xFF60    // 全角形式
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F  // 表情符号
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD) // CJK扩展B及以后
+}
+
+/// 判断字符是否为不占宽度的组合字符
+fn is_combining(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036F
+        | 0x0E31 | 0x0E34..=0x0E3A | 0x0E47..=0x0E4E
+        | 0x200B..=0x200D
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F)
+}
+
+/// 字体测量器
+///
+/// 按文本样式的字体族、粗体和斜体选择字体，逐字符累加字形步进宽度。
+/// 所选字体不包含的字符依次回退到其他已加载的字体
+#[derive(Debug, Clone, Default)]
+pub struct FontTextMeasurer {
+    /// 已加载的字体，第一个字体为默认字体
+    fonts: Vec<Font>,
+}
+
+impl FontTextMeasurer {
+    /// 创建一个没有字体的测量器
+    ///
+    /// # Returns
+    ///
+    /// 返回一个新的FontTextMeasurer实例
+    pub fn new() -> Self {
+        FontTextMeasurer { fonts: Vec::new() }
+    }
+
+    /// 添加字体
+    ///
+    /// # Arguments
+    ///
+    /// * `font` - 字体，先添加的字体在回退时优先使用
+    pub fn add_font(&mut self, font: Font) {
+        self.fonts.push(font);
+    }
+
+    /// 从字体文件内容加载字体，TTC集合中的所有字体都会被加载
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - TTF、OTF或TTC文件的内容
+    ///
+    /// # Returns
+    ///
+    /// 返回加载的字体数量
+    ///
+    /// # Errors
+    ///
+    /// 文件不是受支持的字体格式时返回错误
+    pub fn load_bytes(&mut self, data: &[u8]) -> Result<usize> {
+        let count = Font::face_count(data);
+        for index in 0..count {
+            self.fonts.push(Font::from_bytes(data, index)?);
+        }
+        Ok(count as usize)
+    }
+
+    /// 从字体文件加载字体，TTC集合中的所有字体都会被加载
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 字体文件路径
+    ///
+    /// # Returns
+    ///
+    /// 返回加载的字体数量
+    ///
+    /// # Errors
+    ///
+    /// 文件无法读取或不是受支持的字体格式时返回错误
+    pub fn load_file(&mut self, path: &str) -> Result<usize> {
+        let data = std::fs::read(path)?;
+        self.load_bytes(&data)
+    }
+
+    /// 获取已加载的字体
+    pub fn fonts(&self) -> &[Font] {
+        &self.fonts
+    }
+
+    /// 选择样式对应的首选字体
+    ///
+    /// 字体族可以是逗号分隔的列表，依次查找；同一字体族优先选择粗体、斜体都匹配的字体。
+    /// 没有匹配的字体族时使用默认字体
+    fn primary_font(&self, style: &TextStyle) -> Option<&Font> {
+        style
+            .font_family
+            .split(',')
+            .map(|family| family.trim().trim_matches(|c| c == '"' || c == '\''))
+            .find_map(|family| {
+                self.fonts
+                    .iter()
+                    .filter(|font| font.family().eq_ignore_ascii_case(family))
+                    .max_by_key(|font| (font.is_bold() == style.bold) as u8 * 2 + (font.is_italic() == style.italic) as u8)
+            })
+            .or_else(|| self.fonts.first())
+    }
+}
+
+impl TextMeasurer for FontTextMeasurer {
+    fn char_width(&self, c: char, style: &TextStyle) -> f32 {
+        let Some(primary) = self.primary_font(style) else {
+            return ApproximateTextMeasurer.char_width(c, style);
+        };
+        primary
+            .advance(c, style.font_size)
+            .or_else(|| self.fonts.iter().find_map(|font| font.advance(c, style.font_size)))
+            .unwrap_or_else(|| primary.notdef_advance(style.font_size))
+    }
+
+    fn line_height(&self, style: &TextStyle) -> f32 {
+        match self.primary_font(style) {
+            Some(font) => font.line_height(style.font_size),
+            None => ApproximateTextMeasurer.line_height(style),
+        }
+    }
+
+    fn baseline(&self, style: &TextStyle) -> f32 {
+        match self.primary_font(style) {
+            Some(font) => {
+                let ascent = font.ascent(style.font_size);
+                (font.line_height(style.font_size) - ascent - font.descent(style.font_size)) / 2.0 + ascent
+            }
+            None => ApproximateTextMeasurer.baseline(style),
+        }
+    }
+
+    fn cache_key(&self) -> String {
+        let mut key = String::from("font");
+        for font in &self.fonts {
+            key.push_str(&format!("-{}:{}", font.family(), font.subfamily()));
+        }
+        key
+    }
+}
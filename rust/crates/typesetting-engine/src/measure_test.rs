@@ -0,0 +1,238 @@
+//! 文本测量单元测试
+//!
+//! 测试字体读取、字体测量器和估算测量器的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::document::{ContentBlock, ContentBlockType, TextStyle};
+    use crate::font::Font;
+    use crate::layout::{LayoutEngine, PageConfig};
+    use crate::measure::*;
+    use std::borrow::Cow;
+    use std::sync::Arc;
+
+    /// 构造一个只包含 `A` 和 `中` 两个字形的字体
+    ///
+    /// unitsPerEm为1000，上伸800、下伸-200，`.notdef` 宽500，`A` 宽600，`中` 宽 `wide_advance`
+    fn build_font(family: &str, subfamily: &str, wide_advance: u16, base: usize) -> Vec<u8> {
+        let mut head = vec![0u8; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+
+        let mut hhea = vec![0u8; 36];
+        hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+        hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+        hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+
+        let mut maxp = vec![0u8; 6];
+        maxp[0..4].copy_from_slice(&0x0000_5000u32.to_be_bytes());
+        maxp[4..6].copy_from_slice(&3u16.to_be_bytes());
+
+        let hmtx: Vec<u8> = [500u16, 600, wide_advance].iter().flat_map(|advance| [advance.to_be_bytes(), [0, 0]].concat()).collect();
+
+        // 格式4子表：A -> 1，中 -> 2
+        let segments: [(u16, u16, u16); 3] = [(0x41, 0x41, 1), (0x4E2D, 0x4E2D, 2), (0xFFFF, 0xFFFF, 0)];
+        let mut subtable = Vec::new();
+        for value in [4u16, 0, 0, segments.len() as u16 * 2, 0, 0, 0] {
+            subtable.extend_from_slice(&value.to_be_bytes());
+        }
+        segments.iter().for_each(|(_, end, _)| subtable.extend_from_slice(&end.to_be_bytes()));
+        subtable.extend_from_slice(&[0, 0]);
+        segments.iter().for_each(|(start, _, _)| subtable.extend_from_slice(&start.to_be_bytes()));
+        segments.iter().for_each(|(start, _, glyph)| subtable.extend_from_slice(&glyph.wrapping_sub(*start).to_be_bytes()));
+        segments.iter().for_each(|_| subtable.extend_from_slice(&[0, 0]));
+        let length = subtable.len() as u16;
+        subtable[2..4].copy_from_slice(&length.to_be_bytes());
+        let mut cmap = Vec::new();
+        for value in [0u16, 1, 3, 1] {
+            cmap.extend_from_slice(&value.to_be_bytes());
+        }
+        cmap.extend_from_slice(&12u32.to_be_bytes());
+        cmap.extend_from_slice(&subtable);
+
+        let strings: Vec<Vec<u8>> = [family, subfamily].iter().map(|text| text.encode_utf16().flat_map(u16::to_be_bytes).collect()).collect();
+        let mut name = Vec::new();
+        for value in [0u16, 2, 30] {
+            name.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut offset = 0;
+        for (index, string) in strings.iter().enumerate() {
+            for value in [3u16, 1, 0x0409, index as u16 + 1, string.len() as u16, offset] {
+                name.extend_from_slice(&value.to_be_bytes());
+            }
+            offset += string.len() as u16;
+        }
+        strings.iter().for_each(|string| name.extend_from_slice(string));
+
+        let tables = [("cmap", cmap), ("head", head), ("hhea", hhea), ("hmtx", hmtx), ("maxp", maxp), ("name", name)];
+        let mut font = Vec::new();
+        font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        for value in [tables.len() as u16, 0, 0, 0] {
+            font.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut position = base + 12 + tables.len() * 16;
+        for (tag, data) in &tables {
+            font.extend_from_slice(tag.as_bytes());
+            font.extend_from_slice(&0u32.to_be_bytes());
+            font.extend_from_slice(&(position as u32).to_be_bytes());
+            font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            position += data.len();
+        }
+        tables.iter().for_each(|(_, data)| font.extend_from_slice(data));
+        font
+    }
+
+    /// 构造样式
+    fn style(family: &'static str, font_size: f32, bold: bool) -> TextStyle {
+        TextStyle {
+            font_size,
+            font_family: Cow::Borrowed(family),
+            bold,
+            italic: false,
+        }
+    }
+
+    /// 测试读取字体度量
+    #[test]
+    fn test_font_metrics() {
+        let data = build_font("Test Sans", "Regular", 1000, 0);
+        assert_eq!(Font::face_count(&data), 1);
+
+        let font = Font::from_bytes(&data, 0).unwrap();
+        assert_eq!(font.family(), "Test Sans");
+        assert_eq!(font.subfamily(), "Regular");
+        assert!(!font.is_bold());
+        assert_eq!(font.advance('A', 20.0), Some(12.0));
+        assert_eq!(font.advance('中', 20.0), Some(20.0));
+        assert_eq!(font.advance('B', 20.0), None);
+        assert_eq!(font.notdef_advance(20.0), 10.0);
+        assert_eq!(font.line_height(20.0), 20.0);
+        assert_eq!(font.ascent(20.0), 16.0);
+        assert_eq!(font.descent(20.0), 4.0);
+
+        assert!(Font::from_bytes(&data, 1).is_err());
+        assert!(Font::from_bytes(b"not a font", 0).is_err());
+        assert!(Font::from_bytes(&data[..40], 0).is_err());
+    }
+
+    /// 测试读取字体集合并按粗体选择字体
+    #[test]
+    fn test_font_collection() {
+        let regular_len = build_font("Test Serif", "Regular", 1000, 0).len();
+        let mut data = b"ttcf".to_vec();
+        data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        data.extend_from_slice(&2u32.to_be_bytes());
+        data.extend_from_slice(&20u32.to_be_bytes());
+        data.extend_from_slice(&(20 + regular_len as u32).to_be_bytes());
+        data.extend(build_font("Test Serif", "Regular", 1000, 20));
+        data.extend(build_font("Test Serif", "Bold", 1100, 20 + regular_len));
+
+        let mut measurer = FontTextMeasurer::new();
+        assert_eq!(measurer.load_bytes(&data).unwrap(), 2);
+        assert!(measurer.fonts()[1].is_bold());
+
+        assert_eq!(measurer.measure_text("中中", &style("Test Serif", 10.0, false)).width, 20.0);
+        assert_eq!(measurer.measure_text("中中", &style("Test Serif", 10.0, true)).width, 22.0);
+        // 字体族列表中没有已加载的字体时使用默认字体
+        assert_eq!(measurer.measure_text("中", &style("Missing, \"Test Serif\"", 10.0, true)).width, 11.0);
+        assert_eq!(measurer.measure_text("中", &style("Missing", 10.0, true)).width, 10.0);
+    }
+
+    /// 测试缺失字形时回退到其他字体
+    #[test]
+    fn test_font_fallback() {
+        let mut measurer = FontTextMeasurer::new();
+        measurer.load_bytes(&build_font("Latin", "Regular", 1000, 0)).unwrap();
+        measurer.load_bytes(&build_font("Wide", "Regular", 1200, 0)).unwrap();
+
+        let style = style("Wide", 10.0, false);
+        assert_eq!(measurer.char_width('A', &style), 6.0);
+        assert_eq!(measurer.char_width('中', &style), 12.0);
+        // 所有字体都不包含的字符使用 .notdef 宽度
+        assert_eq!(measurer.char_width('あ', &style), 5.0);
+        assert_ne!(measurer.cache_key(), ApproximateTextMeasurer.cache_key());
+    }
+
+    /// 测试估算测量器和长段落折行
+    #[test]
+    fn test_measure_long_paragraph() {
+        let body = style("serif", 16.0, false);
+        assert_eq!(ApproximateTextMeasurer.measure_text("中文ab", &body).width, 48.0);
+        assert_eq!(ApproximateTextMeasurer.measure_text("e\u{301}", &body).width, 8.0);
+
+        let block = ContentBlock {
+            block_type: ContentBlockType::Text,
+            content: Cow::Owned("中".repeat(3000)),
+            styles: body,
+            metrics: None,
+            language: None,
+        };
+        let config = PageConfig {
+            width: 400.0,
+            height: 600.0,
+            margin_top: 20.0,
+            margin_bottom: 20.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+        };
+        let engine = LayoutEngine::new(config.clone());
+        let metrics = engine.measure_block(&block);
+        let content_width: f32 = config.content_width();
+        // 每行只能放下整数个字
+        let lines = (3000.0 / (content_width / 16.0).floor()).ceil();
+        assert_eq!(metrics.width, content_width);
+        assert_eq!(metrics.height, lines * 16.0 * 1.2);
+
+        // 接入字体测量器后按字体的步进宽度和行高测量
+        let mut measurer = FontTextMeasurer::new();
+        measurer.load_bytes(&build_font("Test Sans", "Regular", 500, 0)).unwrap();
+        let engine = LayoutEngine::new(config).with_measurer(Arc::new(measurer));
+        let metrics = engine.measure_block(&block);
+        assert_eq!(metrics.height, (3000.0 / (content_width / 8.0).floor()).ceil() * 16.0);
+    }
+
+    /// 构造只有一个格式12子表的字符映射表
+    fn format12_cmap(groups: &[(u32, u32, u32)], declared_groups: u32) -> Vec<u8> {
+        let mut cmap = Vec::new();
+        for value in [0u16, 1, 3, 10] {
+            cmap.extend_from_slice(&value.to_be_bytes());
+        }
+        cmap.extend_from_slice(&12u32.to_be_bytes());
+        cmap.extend_from_slice(&12u16.to_be_bytes());
+        cmap.extend_from_slice(&[0, 0]);
+        cmap.extend_from_slice(&(16 + groups.len() as u32 * 12).to_be_bytes());
+        cmap.extend_from_slice(&0u32.to_be_bytes());
+        cmap.extend_from_slice(&declared_groups.to_be_bytes());
+        for (start, end, glyph) in groups {
+            for value in [start, end, glyph] {
+                cmap.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        cmap
+    }
+
+    /// 测试损坏的格式12字符映射表
+    #[test]
+    fn test_format12_cmap() {
+        let groups = [
+            (0x41, 0x42, 1),
+            // 起止颠倒的分组被跳过
+            (0x50, 0x4F, 3),
+            // 超出Unicode范围的部分被截断
+            (0x10FFFE, u32::MAX, 4),
+            // 字形编号超出u16范围的码位被忽略
+            (0x4E00, 0x4E02, 0xFFFF),
+            (0x4E10, 0x4E11, u32::MAX),
+        ];
+        let glyphs = crate::font::read_cmap(&format12_cmap(&groups, groups.len() as u32)).unwrap();
+        assert_eq!(glyphs.len(), 2 + 2 + 1);
+        assert_eq!(glyphs[&'B'], 2);
+        assert_eq!(glyphs[&char::MAX], 5);
+        assert_eq!(glyphs[&'\u{4E00}'], 0xFFFF);
+        assert!(!glyphs.contains_key(&'\u{4E01}'));
+        assert!(!glyphs.contains_key(&'\u{4E10}'));
+
+        // 分组数超出数据长度时返回错误
+        assert!(crate::font::read_cmap(&format12_cmap(&groups, u32::MAX)).is_err());
+        assert!(crate::font::read_cmap(&format12_cmap(&groups[..1], 2)).is_err());
+    }
+}
@@ -0,0 +1,402 @@
+//! 乱码修复
+//!
+//! 识别导入前就被错误解码的文本，如UTF-8文本被当作GBK读取后再次保存（`鍙戠敓`），
+//! 或者GBK、UTF-8文本被当作Latin-1读取（`ÖÐÎÄ`、`æ–°`）。
+//! 能够无损还原的片段通过重新编码再解码修复，已经丢失字节的片段只报告不修改
+
+use crate::document::{ContentBlock, DocumentModel};
+use crate::parser::chapter_number;
+use crate::verse::is_han;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// UTF-8被当作GBK读取时最常出现的字符，修复失败的片段中大量出现时视为乱码
+const GBK_MOJIBAKE_MARKERS: &str = "涓鍙鍏瀹鏉杩蹇鐒鏈鍚鍒瀛闂鏃鎬鏄鎵鍑閲闈鎴鍘鍗鐢鐏杞鍐璧鍔鍥锛銆鈥";
+
+/// Windows-1252 中 0x80-0x9F 对应的字符，未定义的位置保留C1控制字符
+const CP1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// GBK中的单字节欧元符号（0x80）
+const GBK_EURO: char = '€';
+
+/// 连续修复的最大轮数，用于处理被错误转换了两次的文本
+const MAX_REPAIR_PASSES: usize = 3;
+
+lazy_static::lazy_static! {
+    /// GBK双字节编码到字符的映射
+    static ref GBK_DECODE: HashMap<u16, char> = gbk_entries().collect();
+    /// 字符到GBK双字节编码的映射
+    static ref GBK_ENCODE: HashMap<char, u16> = gbk_entries().map(|(code, c)| (c, code)).collect();
+    /// UTF-8被当作Latin-1读取后的典型字符组合，如 `Ã©`、`â€`
+    static ref LATIN1_UTF8_REGEX: Regex = Regex::new(r"[Â-ô][\u0080-¿€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ]").unwrap();
+    /// GBK被当作Latin-1读取后的典型字符组合：连续的高位字符
+    static ref LATIN1_GBK_REGEX: Regex = Regex::new(r"[¡-þ]{4,}").unwrap();
+}
+
+/// 乱码修复结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct MojibakeRepair<'a> {
+    /// 修复后的文本，没有修复任何片段时借用原文
+    pub text: Cow<'a, str>,
+    /// 修复的乱码片段数
+    pub repaired: usize,
+    /// 无法修复的乱码片段数
+    pub unrepaired: usize,
+}
+
+impl MojibakeRepair<'_> {
+    /// 判断文本是否没有乱码
+    ///
+    /// # Returns
+    ///
+    /// 既没有修复也没有无法修复的片段时返回true
+    pub fn is_clean(&self) -> bool {
+        self.repaired == 0 && self.unrepaired == 0
+    }
+}
+
+/// 含有乱码的内容块
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MojibakeBlock {
+    /// 章节索引
+    pub chapter_index: usize,
+    /// 内容块在章节中的索引，章节标题为None
+    pub block_index: Option<usize>,
+    /// 是否已完全修复
+    pub repaired: bool,
+}
+
+/// 文档乱码修复报告
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MojibakeReport {
+    /// 含有乱码的章节标题和内容块
+    pub blocks: Vec<MojibakeBlock>,
+    /// 乱码已全部修复的章节ID
+    pub fixed: Vec<Cow<'static, str>>,
+    /// 仍有无法修复的乱码的章节ID
+    pub unfixed: Vec<Cow<'static, str>>,
+}
+
+/// 判断文本中是否含有乱码
+///
+/// # Arguments
+///
+/// * `text` - 需要检查的文本
+///
+/// # Returns
+///
+/// 含有可修复或无法修复的乱码时返回true
+pub fn detect_mojibake(text: &str) -> bool {
+    !repair_mojibake(text).is_clean()
+}
+
+/// 修复文本中的乱码
+///
+/// 按片段处理，只有重新编码后能完整解码、且解码结果是正常文字的片段才会被替换，
+/// 因此正常的中文、英文和带重音字母的文字不受影响
+///
+/// # Arguments
+///
+/// * `text` - 需要修复的文本
+///
+/// # Returns
+///
+/// 返回修复结果
+pub fn repair_mojibake(text: &str) -> MojibakeRepair<'_> {
+    let mut repaired = 0;
+    let mut current: Cow<str> = Cow::Borrowed(text);
+    for _ in 0..MAX_REPAIR_PASSES {
+        let (next, count) = repair_pass(&current);
+        if count == 0 {
+            break;
+        }
+        repaired += count;
+        current = Cow::Owned(next);
+    }
+
+    let unrepaired = count_garbled(&current);
+    MojibakeRepair { text: current, repaired, unrepaired }
+}
+
+/// 修复文档中的乱码
+///
+/// 修复元数据、章节标题和全部内容块。标题被修复的章节会重新解析章节序号，
+/// 含有脚注引用的内容块按引用分段修复，使引用的字节范围保持有效
+///
+/// # Arguments
+///
+/// * `document` - 需要修复的文档
+///
+/// # Returns
+///
+/// 返回修复报告
+pub fn repair_document_mojibake(document: &mut DocumentModel) -> MojibakeReport {
+    let metadata = &mut document.metadata;
+    for field in [&mut metadata.title, &mut metadata.author] {
+        if let Cow::Owned(text) = repair_mojibake(field).text {
+            *field = Cow::Owned(text);
+        }
+    }
+
+    let mut report = MojibakeReport::default();
+    for (chapter_index, chapter) in document.chapters.iter_mut().enumerate() {
+        let mut found = false;
+        let mut fixed = true;
+
+        let repair = repair_mojibake(&chapter.title);
+        if !repair.is_clean() {
+            found = true;
+            fixed &= repair.unrepaired == 0;
+            report.blocks.push(MojibakeBlock { chapter_index, block_index: None, repaired: repair.unrepaired == 0 });
+            if let Cow::Owned(title) = repair.text {
+                chapter.number = chapter_number(&title);
+                chapter.title = Cow::Owned(title);
+            }
+        }
+
+        for (block_index, block) in chapter.content.iter_mut().enumerate() {
+            let mut ranges: Vec<&mut std::ops::Range<usize>> = chapter.footnote_refs
+                .iter_mut()
+                .filter(|footnote_ref| footnote_ref.block_index == block_index)
+                .map(|footnote_ref| &mut footnote_ref.range)
+                .collect();
+            let (repaired, unrepaired) = repair_block(block, &mut ranges);
+            if repaired > 0 || unrepaired > 0 {
+                found = true;
+                fixed &= unrepaired == 0;
+                report.blocks.push(MojibakeBlock { chapter_index, block_index: Some(block_index), repaired: unrepaired == 0 });
+            }
+        }
+
+        match (found, fixed) {
+            (true, true) => report.fixed.push(chapter.id.clone()),
+            (true, false) => report.unfixed.push(chapter.id.clone()),
+            _ => {}
+        }
+    }
+    report
+}
+
+/// 修复单个内容块
+///
+/// 修复会改变文本长度，按脚注标记分段修复并更新标记的字节范围
+///
+/// # Returns
+///
+/// 返回修复和无法修复的片段数
+fn repair_block(block: &mut ContentBlock, ranges: &mut [&mut std::ops::Range<usize>]) -> (usize, usize) {
+    ranges.sort_by_key(|range| range.start);
+
+    let mut text = String::with_capacity(block.content.len());
+    let mut repaired = 0;
+    let mut unrepaired = 0;
+    let mut last_end = 0;
+    let mut push_repaired = |text: &mut String, part: &str| {
+        let repair = repair_mojibake(part);
+        repaired += repair.repaired;
+        unrepaired += repair.unrepaired;
+        text.push_str(&repair.text);
+    };
+    for range in ranges.iter_mut() {
+        push_repaired(&mut text, &block.content[last_end..range.start]);
+        let start = text.len();
+        text.push_str(&block.content[range.start..range.end]);
+        last_end = range.end;
+        **range = start..text.len();
+    }
+    push_repaired(&mut text, &block.content[last_end..]);
+
+    if repaired > 0 {
+        block.content = Cow::Owned(text);
+    }
+    (repaired, unrepaired)
+}
+
+/// 进行一轮修复
+///
+/// 含有Latin-1以外字符的文本按UTF-8被当作GBK读取处理，否则按被当作Latin-1读取处理
+///
+/// # Returns
+///
+/// 返回修复后的文本和修复的片段数
+fn repair_pass(text: &str) -> (String, usize) {
+    let gbk_source = text.chars().any(|c| latin1_byte(c).is_none());
+    let mut result = String::with_capacity(text.len());
+    let mut repaired = 0;
+
+    for (segment, in_segment) in segments(text, |c| if gbk_source { is_gbk_char(c) } else { latin1_byte(c).is_some() }) {
+        let fixed = if !in_segment || segment.is_ascii() {
+            None
+        } else if gbk_source {
+            repair_gbk_segment(segment)
+        } else {
+            repair_latin1_segment(segment)
+        };
+        match fixed {
+            Some(fixed) => {
+                result.push_str(&fixed);
+                repaired += 1;
+            }
+            None => result.push_str(segment),
+        }
+    }
+    (result, repaired)
+}
+
+/// 修复被当作GBK读取的UTF-8片段
+fn repair_gbk_segment(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    for c in segment.chars() {
+        if c == GBK_EURO {
+            bytes.push(0x80);
+        } else {
+            bytes.extend_from_slice(&GBK_ENCODE.get(&c)?.to_be_bytes());
+        }
+    }
+    let decoded = String::from_utf8(bytes).ok()?;
+    decoded.chars().all(is_cjk_text).then_some(decoded)
+}
+
+/// 修复被当作Latin-1或Windows-1252读取的UTF-8或GBK片段
+fn repair_latin1_segment(segment: &str) -> Option<String> {
+    let bytes: Vec<u8> = segment.chars().map(latin1_byte).collect::<Option<_>>()?;
+    if let Ok(decoded) = std::str::from_utf8(&bytes) {
+        return (decoded != segment && !decoded.chars().any(|c| ('\u{80}'..='\u{9F}').contains(&c)))
+            .then(|| decoded.to_string());
+    }
+
+    if !common_gb2312(&bytes) {
+        return None;
+    }
+    let decoded = decode_gbk(&bytes)?;
+    decoded.chars().all(is_cjk_text).then_some(decoded)
+}
+
+/// 判断按GBK解码的字节是否像正常的中文文本
+///
+/// 带重音的拉丁字母和后面的字母常能组成GBK编码（如 `Jürgen` 中的 `ür` 对应 `黵`），
+/// 但这些编码的尾字节是ASCII字母，落在GB2312之外。因此要求双字节编码都在GB2312中，
+/// 至少有两个汉字，且汉字大部分是一级常用字
+fn common_gb2312(bytes: &[u8]) -> bool {
+    let mut han = 0;
+    let mut common = 0;
+    let mut iter = bytes.iter();
+    while let Some(&lead) = iter.next() {
+        if lead <= 0x80 {
+            continue;
+        }
+        let Some(&trail) = iter.next() else {
+            return false;
+        };
+        if !(0xA1..=0xF7).contains(&lead) || !(0xA1..=0xFE).contains(&trail) {
+            return false;
+        }
+        if lead >= 0xB0 {
+            han += 1;
+            common += (lead <= 0xD7) as usize;
+        }
+    }
+    han >= 2 && common * 5 >= han * 4
+}
+
+/// 统计仍无法修复的乱码片段
+fn count_garbled(text: &str) -> usize {
+    // 已经丢失的字节：替换字符和 `锟斤拷`（替换字符的UTF-8编码被当作GBK读取）
+    let lost = segments(text, |c| c == '\u{FFFD}').filter(|&(_, in_segment)| in_segment).count()
+        + text.matches("锟斤拷").count();
+
+    let gbk = segments(text, is_gbk_char)
+        .filter(|&(segment, in_segment)| {
+            let markers = segment.chars().filter(|&c| GBK_MOJIBAKE_MARKERS.contains(c)).count();
+            in_segment && markers >= 3 && markers * 10 >= segment.chars().count()
+        })
+        .count();
+
+    let latin1 = if LATIN1_UTF8_REGEX.find_iter(text).nth(1).is_some() || LATIN1_GBK_REGEX.is_match(text) { 1 } else { 0 };
+
+    lost + gbk + latin1
+}
+
+/// 按字符是否满足条件把文本划分为连续的片段
+///
+/// # Returns
+///
+/// 依次返回片段文本以及片段中的字符是否满足条件
+fn segments<F: Fn(char) -> bool>(text: &str, predicate: F) -> impl Iterator<Item = (&str, bool)> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let in_segment = predicate(first);
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| predicate(c) != in_segment)
+            .map_or(rest.len(), |(index, _)| index);
+        let (segment, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some((segment, in_segment))
+    })
+}
+
+/// 获取字符在Latin-1或Windows-1252中的字节
+fn latin1_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        0..=0xFF => Some(c as u8),
+        _ => CP1252_HIGH.iter().position(|&high| high == c).map(|index| 0x80 + index as u8),
+    }
+}
+
+/// 判断字符是否为GBK中的非ASCII字符
+fn is_gbk_char(c: char) -> bool {
+    c == GBK_EURO || (!c.is_ascii() && GBK_ENCODE.contains_key(&c))
+}
+
+/// 按GBK解码字节
+///
+/// 存在未定义的编码或不完整的双字节时返回None
+fn decode_gbk(bytes: &[u8]) -> Option<String> {
+    let mut decoded = String::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+    while let Some(&byte) = iter.next() {
+        if byte < 0x80 {
+            decoded.push(byte as char);
+        } else if byte == 0x80 {
+            decoded.push(GBK_EURO);
+        } else {
+            let trail = *iter.next()?;
+            decoded.push(*GBK_DECODE.get(&u16::from_be_bytes([byte, trail]))?);
+        }
+    }
+    Some(decoded)
+}
+
+/// 判断字符是否属于正常的中文文本：ASCII、汉字、假名以及中文标点和全角字符
+fn is_cjk_text(c: char) -> bool {
+    c.is_ascii()
+        || is_han(c)
+        || matches!(c, '\u{2000}'..='\u{206F}' | '\u{3000}'..='\u{30FF}' | '\u{FF00}'..='\u{FFEF}')
+}
+
+/// 读取GBK编码表
+///
+/// 每行为首字节和依次对应尾字节 0x40-0x7E、0x80-0xFE 的字符，U+FFFD 表示未定义
+fn gbk_entries() -> impl Iterator<Item = (u16, char)> {
+    include_str!("../data/encoding/gbk.txt")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (lead, chars) = line.split_once('\t')?;
+            let lead = u8::from_str_radix(lead, 16).ok()?;
+            Some(
+                (0x40..=0xFEu8)
+                    .filter(|&trail| trail != 0x7F)
+                    .zip(chars.chars())
+                    .filter(|&(_, c)| c != '\u{FFFD}')
+                    .map(move |(trail, c)| (u16::from_be_bytes([lead, trail]), c)),
+            )
+        })
+        .flatten()
+}
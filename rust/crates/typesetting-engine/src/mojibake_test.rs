@@ -0,0 +1,94 @@
+//! 乱码修复单元测试
+//!
+//! 测试乱码识别和修复功能的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::mojibake::*;
+    use crate::parser::ParserEngine;
+
+    /// 测试被当作Latin-1或Windows-1252读取的文本
+    #[test]
+    fn test_repair_latin1() {
+        // UTF-8被当作Latin-1读取
+        let garbled: String = "陆江仙睁开眼。".bytes().map(|byte| byte as char).collect();
+        let repair = repair_mojibake(&garbled);
+        assert_eq!(repair.text, "陆江仙睁开眼。");
+        assert_eq!(repair.unrepaired, 0);
+
+        // UTF-8被当作Windows-1252读取
+        assert_eq!(repair_mojibake("Chapter 1: ä¸\u{AD}æ–‡").text, "Chapter 1: 中文");
+
+        // GBK被当作Latin-1读取，这也是文件加载器无法按UTF-8解码时的结果
+        assert_eq!(repair_mojibake("ÖÐÎÄÂÒÂë").text, "中文乱码");
+    }
+
+    /// 测试被当作GBK读取的UTF-8文本
+    #[test]
+    fn test_repair_gbk() {
+        let repair = repair_mojibake("鎴戜滑娌℃湁鍥炲幓");
+        assert_eq!(repair.text, "我们没有回去");
+        assert_eq!(repair.repaired, 1);
+
+        // ASCII和正常的中文片段保持不变
+        assert_eq!(repair_mojibake("1. 浠栦滑鐭ラ亾鎬庝箞鍥炲幓").text, "1. 他们知道怎么回去");
+    }
+
+    /// 测试正常文本不会被误修复
+    #[test]
+    fn test_clean_text_untouched() {
+        for text in [
+            "陆江仙睁开眼，山下传来钟声。“走吧！”",
+            "Café au lait, naïve Müller.",
+            "A谋B",
+            "ÆSIR — “quoted” text…",
+        ] {
+            let repair = repair_mojibake(text);
+            assert!(repair.is_clean(), "{}", text);
+            assert_eq!(repair.text, text);
+        }
+    }
+
+    /// 测试带重音字母的德语和法语文本不会被当作GBK乱码
+    #[test]
+    fn test_accented_latin_untouched() {
+        for text in [
+            "Jürgen Müller kam.",
+            "Crème brûlée",
+            "Der Bürgermeister öffnete die Tür für Frau Schäfer.",
+            "Hélène préférait le thé à la crème glacée.",
+            "Ça été très complété, n'est-ce pas? Où êtes-vous?",
+        ] {
+            let repair = repair_mojibake(text);
+            assert_eq!(repair.text, text);
+            assert_eq!(repair.repaired, 0, "{}", text);
+            assert!(!detect_mojibake(text), "{}", text);
+        }
+    }
+
+    /// 测试已经丢失字节的乱码只报告不修改
+    #[test]
+    fn test_unrepairable_mojibake() {
+        let text = "闄嗘睙浠欑潄寮�鐪硷紝灞变笅浼犳潵閽熷０銆傗";
+        let repair = repair_mojibake(text);
+        assert_eq!(repair.text, text);
+        assert!(repair.unrepaired > 0);
+        assert!(detect_mojibake("锟斤拷锟斤拷"));
+        assert!(!detect_mojibake("正常的文字"));
+    }
+
+    /// 测试修复文档并报告修复结果
+    #[test]
+    fn test_repair_document() {
+        let content = "第一章 青杜\n鎴戜滑娌℃湁鍥炲幓\n\n第二章 镜中\n闄嗘睙浠欑潄寮�鐪硷紝灞变笅浼犳潵閽熷０銆傗\n\n第三章 符箓\n古镜微微发亮。";
+        let parser = ParserEngine::new();
+        let mut document = parser.parse_txt(content);
+
+        let report = repair_document_mojibake(&mut document);
+        assert_eq!(report.fixed, vec!["chapter_0"]);
+        assert_eq!(report.unfixed, vec!["chapter_1"]);
+        assert_eq!(report.blocks.len(), 2);
+        assert_eq!(document.chapters[0].content[0].content, "我们没有回去");
+        assert_eq!(document.chapters[2].content[0].content, "古镜微微发亮。");
+    }
+}
@@ -0,0 +1,95 @@
+//! 中文数字解析
+//!
+//! 解析章节标题中常见的数字写法：小写数字（`一千零二十三`）、大写数字（`壹佰`）、
+//! `〇` 占位的逐位写法（`一〇二三`）、`廿`、`卅` 等合文，以及半角和全角阿拉伯数字
+
+/// 解析中文数字或阿拉伯数字
+///
+/// # Arguments
+///
+/// * `text` - 数字文本，如 `一千零二十三`、`1023`、`１０２３`、`壹佰`、`廿三`
+///
+/// # Returns
+///
+/// 返回解析出的数值，文本为空、包含无法识别的字符或数值超出u32范围时返回None
+pub fn parse_chinese_numeral(text: &str) -> Option<u32> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    // 没有单位时按逐位写法解析，如 `1023`、`一〇二三`
+    if text.chars().all(|c| digit_value(c).is_some()) {
+        return text
+            .chars()
+            .try_fold(0u32, |value, c| value.checked_mul(10)?.checked_add(digit_value(c)?));
+    }
+
+    let mut total: u32 = 0;
+    // 万以下的部分
+    let mut section: u32 = 0;
+    // 尚未乘以单位的数字，连续的数字按逐位写法累加，如 `10万`
+    let mut digit: Option<u32> = None;
+
+    for c in text.chars() {
+        if let Some(value) = digit_value(c) {
+            digit = Some(digit.unwrap_or(0).checked_mul(10)?.checked_add(value)?);
+            continue;
+        }
+        match c {
+            '十' | '拾' | '百' | '佰' | '千' | '仟' => {
+                let unit = match c {
+                    '十' | '拾' => 10,
+                    '百' | '佰' => 100,
+                    _ => 1000,
+                };
+                // `十三` 中省略了开头的 `一`
+                let value = digit.take().unwrap_or(1);
+                section = section.checked_add(value.checked_mul(unit)?)?;
+            }
+            '廿' | '卅' | '卌' => {
+                let value = match c {
+                    '廿' => 20,
+                    '卅' => 30,
+                    _ => 40,
+                };
+                section = section.checked_add(value)?;
+                digit = None;
+            }
+            '万' | '萬' | '亿' | '億' => {
+                let unit = if matches!(c, '万' | '萬') { 10_000 } else { 100_000_000 };
+                let value = section.checked_add(digit.take().unwrap_or(0))?;
+                // `一亿二千万` 中万以上的部分已经计入total，需要整体乘以亿
+                let value = if unit == 100_000_000 { total.checked_add(value)? } else { value };
+                let scaled = value.max(1).checked_mul(unit)?;
+                total = if unit == 100_000_000 { scaled } else { total.checked_add(scaled)? };
+                section = 0;
+            }
+            _ => return None,
+        }
+    }
+
+    total.checked_add(section)?.checked_add(digit.unwrap_or(0))
+}
+
+/// 获取单个数字字符的值
+///
+/// `零`、`〇` 为0，`两` 为2，同时支持大写数字和全角阿拉伯数字
+fn digit_value(c: char) -> Option<u32> {
+    let value = match c {
+        '0'..='9' => c as u32 - '0' as u32,
+        '０'..='９' => c as u32 - '０' as u32,
+        '零' | '〇' => 0,
+        '一' | '壹' | '幺' => 1,
+        '二' | '贰' | '貳' | '两' | '兩' => 2,
+        '三' | '叁' | '參' => 3,
+        '四' | '肆' => 4,
+        '五' | '伍' => 5,
+        '六' | '陆' | '陸' => 6,
+        '七' | '柒' => 7,
+        '八' | '捌' => 8,
+        '九' | '玖' => 9,
+        _ => return None,
+    };
+    Some(value)
+}
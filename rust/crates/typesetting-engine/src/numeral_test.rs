@@ -0,0 +1,75 @@
+//! 中文数字解析单元测试
+//! 
+//! 测试中文数字和章节序号解析的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::numeral::*;
+    use crate::parser::*;
+
+    /// 测试小写中文数字
+    #[test]
+    fn test_lowercase_numerals() {
+        assert_eq!(parse_chinese_numeral("一"), Some(1));
+        assert_eq!(parse_chinese_numeral("十"), Some(10));
+        assert_eq!(parse_chinese_numeral("十三"), Some(13));
+        assert_eq!(parse_chinese_numeral("二十"), Some(20));
+        assert_eq!(parse_chinese_numeral("一百零五"), Some(105));
+        assert_eq!(parse_chinese_numeral("一千零二十三"), Some(1023));
+        assert_eq!(parse_chinese_numeral("两千"), Some(2000));
+        assert_eq!(parse_chinese_numeral("一万二千三百四十五"), Some(12345));
+        assert_eq!(parse_chinese_numeral("十万"), Some(100000));
+        assert_eq!(parse_chinese_numeral("一亿二千万"), Some(120000000));
+    }
+
+    /// 测试逐位写法、合文和大写数字
+    #[test]
+    fn test_other_numeral_forms() {
+        assert_eq!(parse_chinese_numeral("一〇二三"), Some(1023));
+        assert_eq!(parse_chinese_numeral("零"), Some(0));
+        assert_eq!(parse_chinese_numeral("廿三"), Some(23));
+        assert_eq!(parse_chinese_numeral("卅"), Some(30));
+        assert_eq!(parse_chinese_numeral("壹佰"), Some(100));
+        assert_eq!(parse_chinese_numeral("贰拾叁"), Some(23));
+        assert_eq!(parse_chinese_numeral("1023"), Some(1023));
+        assert_eq!(parse_chinese_numeral("１０２３"), Some(1023));
+        // 阿拉伯数字与单位混用时连续的数字整体乘以单位
+        assert_eq!(parse_chinese_numeral("10万"), Some(100_000));
+        assert_eq!(parse_chinese_numeral("12百"), Some(1200));
+        assert_eq!(parse_chinese_numeral("3万5千"), Some(35_000));
+        assert_eq!(parse_chinese_numeral("一千零二十三"), Some(1023));
+    }
+
+    /// 测试无法解析的输入
+    #[test]
+    fn test_invalid_numerals() {
+        assert_eq!(parse_chinese_numeral(""), None);
+        assert_eq!(parse_chinese_numeral("第一"), None);
+        assert_eq!(parse_chinese_numeral("12a"), None);
+        assert_eq!(parse_chinese_numeral("99999999999"), None);
+        assert_eq!(parse_chinese_numeral("五十亿"), None);
+        assert_eq!(parse_chinese_numeral("99999999999万"), None);
+    }
+
+    /// 测试章节序号和规范化标题
+    #[test]
+    fn test_chapter_numbers() {
+        assert_eq!(chapter_number("第一千零二十三章 归来"), Some(1023));
+        assert_eq!(chapter_number("第1023章"), Some(1023));
+        assert_eq!(chapter_number("第１０２３章 归来"), Some(1023));
+        assert_eq!(chapter_number("第壹佰章"), Some(100));
+        assert_eq!(chapter_number("序章"), None);
+        
+        let content = "第一千零二十三章　归来\n内容\n\n第１０２４章\n内容\n\n# 尾声\n内容";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        
+        let numbers: Vec<Option<u32>> = document.chapters.iter().map(|chapter| chapter.number).collect();
+        assert_eq!(numbers, vec![Some(1023), Some(1024), None]);
+        assert_eq!(document.chapters[0].normalized_title(), "第1023章 归来");
+        assert_eq!(document.chapters[1].normalized_title(), "第1024章");
+        assert_eq!(document.chapters[2].normalized_title(), "尾声");
+        assert_eq!(document.chapter_index_by_number(1024), Some(1));
+        assert_eq!(document.chapter_index_by_number(1), None);
+    }
+}
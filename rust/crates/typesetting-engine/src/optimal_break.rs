@@ -0,0 +1,287 @@
+//! 全段最优断行
+//!
+//! Knuth–Plass算法：把每个断行机会视为候选断点，为每一行按伸缩率计算劣度，
+//! 在整段范围内选择总扣分最小的断点组合，避免贪心断行留下的过松的行
+
+use crate::line_break::{is_space, LineBreaker};
+use crate::measure::{is_wide, TextMeasurer};
+
+/// 全段最优断行参数
+///
+/// 默认值与TeX的默认设置相当
+#[derive(Debug, Clone, PartialEq)]
+pub struct TotalFitConfig {
+    /// 空格可拉伸的宽度，相对于空格宽度的比例
+    pub space_stretch: f32,
+    /// 空格可压缩的宽度，相对于空格宽度的比例
+    pub space_shrink: f32,
+    /// 中日文字之间可拉伸的宽度，相对于字号的比例
+    pub cjk_stretch: f32,
+    /// 可接受的最大劣度，劣度为 `100 × |伸缩率|³`
+    pub tolerance: f32,
+    /// 每行的基础扣分，值越大越倾向于减少行数
+    pub line_penalty: f32,
+    /// 在连字符处断行的惩罚
+    pub hyphen_penalty: f32,
+    /// 连续两行都在连字符处断开的额外扣分
+    pub double_hyphen_demerits: f32,
+    /// 相邻两行疏密等级相差超过一级的额外扣分
+    pub fitness_demerits: f32,
+}
+
+impl TotalFitConfig {
+    /// 生成用于缓存的键
+    ///
+    /// # Returns
+    ///
+    /// 返回表示参数的字符串键
+    pub fn cache_key(&self) -> String {
+        format!("{}-{}-{}-{}-{}-{}-{}-{}",
+            self.space_stretch, self.space_shrink, self.cjk_stretch, self.tolerance,
+            self.line_penalty, self.hyphen_penalty, self.double_hyphen_demerits, self.fitness_demerits)
+    }
+}
+
+impl Default for TotalFitConfig {
+    fn default() -> Self {
+        TotalFitConfig {
+            space_stretch: 0.5,
+            space_shrink: 0.33,
+            cjk_stretch: 0.1,
+            tolerance: 200.0,
+            line_penalty: 10.0,
+            hyphen_penalty: 50.0,
+            double_hyphen_demerits: 10000.0,
+            fitness_demerits: 10000.0,
+        }
+    }
+}
+
+/// 两个相邻断行机会之间的片段
+#[derive(Debug, Clone, Copy, Default)]
+struct Segment {
+    /// 宽度
+    width: f32,
+    /// 可拉伸宽度
+    stretch: f32,
+    /// 可压缩宽度
+    shrink: f32,
+    /// 行尾空格的宽度、可拉伸宽度和可压缩宽度，在此处断行时不计入
+    trailing_width: f32,
+    trailing_stretch: f32,
+    trailing_shrink: f32,
+    /// 与下一个片段之间的可拉伸宽度（两侧都是中日文字时）
+    gap_stretch: f32,
+    /// 是否以连字符结尾
+    hyphen: bool,
+    /// 是否在断字位置结束
+    discretionary: bool,
+}
+
+/// 候选断点
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    /// 下一行开始的片段索引
+    position: usize,
+    /// 所结束的行的疏密等级：0紧、1正常、2松、3很松
+    fitness: usize,
+    /// 到此为止的总扣分
+    demerits: f32,
+    /// 上一个断点
+    previous: Option<usize>,
+    /// 是否在连字符处断开
+    hyphenated: bool,
+    /// 到此为止连续断字的行数
+    hyphen_run: usize,
+}
+
+/// 劣度上限
+const MAX_BADNESS: f32 = 10000.0;
+
+impl<M: TextMeasurer + ?Sized> LineBreaker<'_, M> {
+    /// 用全段最优断行填充两个强制断行之间的文本
+    ///
+    /// 先在容许劣度内查找，找不到时放开劣度限制再找一次
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - 文本起点
+    /// * `opportunities` - 断行机会，最后一个是文本的结尾
+    /// * `config` - 断行参数
+    ///
+    /// # Returns
+    ///
+    /// 没有不超出可用宽度的方案时返回false，此时不添加任何行
+    pub(crate) fn fill_total_fit(&mut self, start: usize, opportunities: &[usize], config: &TotalFitConfig) -> bool {
+        let segments = self.segments(start, opportunities, config);
+        for tolerance in [config.tolerance, f32::INFINITY] {
+            if let Some(breaks) = find_breaks(&segments, self.max_width, config, tolerance, self.hyphen_limit) {
+                let mut line_start = start;
+                for position in breaks {
+                    let end = opportunities[position - 1];
+                    let width = self.measure(line_start..end).1;
+                    self.push(line_start..end, width);
+                    line_start = end;
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 测量各个片段的宽度和伸缩量
+    fn segments(&self, start: usize, opportunities: &[usize], config: &TotalFitConfig) -> Vec<Segment> {
+        let mut segments: Vec<Segment> = Vec::with_capacity(opportunities.len());
+        let mut segment_start = start;
+        for &end in opportunities {
+            let text = &self.text[segment_start..end];
+            let (width, trimmed_width) = self.measure(segment_start..end);
+            let trimmed = text.trim_end_matches(is_space);
+            let mut segment = Segment {
+                width,
+                trailing_width: width - trimmed_width,
+                hyphen: trimmed.ends_with(['-', '\u{2010}']) || self.is_hyphen(end),
+                discretionary: self.is_hyphen(end),
+                ..Segment::default()
+            };
+            for (offset, c) in text.char_indices() {
+                if c == ' ' {
+                    let space = self.measurer.char_width(c, self.style);
+                    segment.stretch += space * config.space_stretch;
+                    segment.shrink += space * config.space_shrink;
+                    if offset >= trimmed.len() {
+                        segment.trailing_stretch += space * config.space_stretch;
+                        segment.trailing_shrink += space * config.space_shrink;
+                    }
+                }
+            }
+            if let Some(previous) = segments.last_mut() {
+                let joined = self.text[..segment_start].chars().next_back().is_some_and(is_wide) && text.chars().next().is_some_and(is_wide);
+                if joined {
+                    previous.gap_stretch = self.style.font_size * config.cjk_stretch;
+                }
+            }
+            segments.push(segment);
+            segment_start = end;
+        }
+        segments
+    }
+}
+
+/// 查找总扣分最小的断点组合
+///
+/// # Arguments
+///
+/// * `segments` - 片段
+/// * `max_width` - 可用宽度
+/// * `config` - 断行参数
+/// * `tolerance` - 可接受的最大劣度
+/// * `hyphen_limit` - 最多允许连续断字的行数
+///
+/// # Returns
+///
+/// 返回每一行结束的片段位置（不含开头），没有可行方案时返回None
+fn find_breaks(segments: &[Segment], max_width: f32, config: &TotalFitConfig, tolerance: f32, hyphen_limit: usize) -> Option<Vec<usize>> {
+    // 前缀和，便于计算任意一行的宽度和伸缩量
+    let mut width = vec![0.0; segments.len() + 1];
+    let mut stretch = vec![0.0; segments.len() + 1];
+    let mut shrink = vec![0.0; segments.len() + 1];
+    let mut gap = vec![0.0; segments.len() + 1];
+    for (index, segment) in segments.iter().enumerate() {
+        width[index + 1] = width[index] + segment.width;
+        stretch[index + 1] = stretch[index] + segment.stretch;
+        shrink[index + 1] = shrink[index] + segment.shrink;
+        gap[index + 1] = gap[index] + segment.gap_stretch;
+    }
+
+    let mut nodes = vec![Node {
+        position: 0,
+        fitness: 1,
+        demerits: 0.0,
+        previous: None,
+        hyphenated: false,
+        hyphen_run: 0,
+    }];
+    let mut active = vec![0];
+    for end in 1..=segments.len() {
+        let last = &segments[end - 1];
+        let is_final = end == segments.len();
+        let penalty = if last.hyphen && !is_final { config.hyphen_penalty } else { 0.0 };
+        let discretionary = last.discretionary && !is_final;
+        let mut best: [Option<(f32, usize)>; 4] = [None; 4];
+
+        active.retain(|&index| {
+            let node = &nodes[index];
+            let start = node.position;
+            let natural = width[end] - width[start] - last.trailing_width;
+            let line_stretch = stretch[end] - stretch[start] - last.trailing_stretch + gap[end - 1] - gap[start];
+            let line_shrink = shrink[end] - shrink[start] - last.trailing_shrink;
+            let ratio = if natural > max_width {
+                if line_shrink > 0.0 { (max_width - natural) / line_shrink } else { f32::NEG_INFINITY }
+            } else if is_final {
+                // 最后一行不需要撑满
+                0.0
+            } else if natural < max_width {
+                if line_stretch > 0.0 { (max_width - natural) / line_stretch } else { f32::INFINITY }
+            } else {
+                0.0
+            };
+            // 过满的行：之后更长的行也放不下，不再作为起点
+            if ratio < -1.0 {
+                return false;
+            }
+            let badness = (100.0 * ratio.abs().powi(3)).min(MAX_BADNESS);
+            if badness > tolerance || (discretionary && node.hyphen_run >= hyphen_limit) {
+                return true;
+            }
+
+            let mut demerits = (config.line_penalty + badness).powi(2) + penalty.powi(2);
+            let fitness: usize = match ratio {
+                r if r < -0.5 => 0,
+                r if r <= 0.5 => 1,
+                r if r <= 1.0 => 2,
+                _ => 3,
+            };
+            if last.hyphen && !is_final && node.hyphenated {
+                demerits += config.double_hyphen_demerits;
+            }
+            if fitness.abs_diff(node.fitness) > 1 {
+                demerits += config.fitness_demerits;
+            }
+            let total = node.demerits + demerits;
+            if best[fitness].is_none_or(|(best_total, _)| total < best_total) {
+                best[fitness] = Some((total, index));
+            }
+            true
+        });
+
+        for (fitness, candidate) in best.into_iter().enumerate() {
+            if let Some((demerits, previous)) = candidate {
+                nodes.push(Node {
+                    position: end,
+                    fitness,
+                    demerits,
+                    previous: Some(previous),
+                    hyphenated: last.hyphen && !is_final,
+                    hyphen_run: if discretionary { nodes[previous].hyphen_run + 1 } else { 0 },
+                });
+                active.push(nodes.len() - 1);
+            }
+        }
+        if active.is_empty() {
+            return None;
+        }
+    }
+
+    let mut current = active
+        .into_iter()
+        .filter(|&index| nodes[index].position == segments.len())
+        .min_by(|&a, &b| nodes[a].demerits.total_cmp(&nodes[b].demerits))?;
+    let mut breaks = Vec::new();
+    while let Some(previous) = nodes[current].previous {
+        breaks.push(nodes[current].position);
+        current = previous;
+    }
+    breaks.reverse();
+    Some(breaks)
+}
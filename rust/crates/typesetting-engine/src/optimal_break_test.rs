@@ -0,0 +1,92 @@
+//! 全段最优断行单元测试
+//!
+//! 测试Knuth–Plass断行和排版设置中断行方式选择的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::document::{ContentBlock, ContentBlockType, TextStyle};
+    use crate::layout::{LayoutEngine, PageConfig, TypographyConfig};
+    use crate::line_break::*;
+    use crate::measure::ApproximateTextMeasurer;
+    use crate::optimal_break::*;
+    use std::borrow::Cow;
+
+    /// 字号10的样式，半角字符宽5，全角字符宽10
+    fn style() -> TextStyle {
+        TextStyle {
+            font_size: 10.0,
+            font_family: Cow::Borrowed("serif"),
+            bold: false,
+            italic: false,
+        }
+    }
+
+    /// 按指定方式断行并返回各行文本
+    fn lines<'a>(text: &'a str, max_width: f32, mode: &LineBreakMode) -> Vec<&'a str> {
+        break_lines_with(text, &style(), &ApproximateTextMeasurer, max_width, mode)
+            .into_iter()
+            .map(|line| &text[line.byte_range])
+            .collect()
+    }
+
+    /// 测试最优断行避免贪心断行留下的过松的行
+    #[test]
+    fn test_total_fit_balances_lines() {
+        let text = "a a bb dddd a dddd ffffff eeeee";
+        let total_fit = LineBreakMode::TotalFit(TotalFitConfig::default());
+        assert_eq!(lines(text, 60.0, &LineBreakMode::Greedy), vec!["a a bb dddd ", "a dddd ", "ffffff eeeee"]);
+        // 第一行压缩空格，第二行不再只有半行
+        assert_eq!(lines(text, 60.0, &total_fit), vec!["a a bb dddd a ", "dddd ffffff ", "eeeee"]);
+
+        // 不允许压缩时每行都不超出可用宽度
+        let rigid = LineBreakMode::TotalFit(TotalFitConfig {
+            space_shrink: 0.0,
+            ..TotalFitConfig::default()
+        });
+        let result = break_lines_with(text, &style(), &ApproximateTextMeasurer, 60.0, &rigid);
+        assert!(result.iter().all(|line| line.width <= 60.0));
+    }
+
+    /// 测试强制断行、禁则和无可行方案时的退回
+    #[test]
+    fn test_total_fit_fallback() {
+        let total_fit = LineBreakMode::TotalFit(TotalFitConfig::default());
+        assert_eq!(lines("aa bb\ncc", 60.0, &total_fit), vec!["aa bb", "cc"]);
+        assert_eq!(lines("", 60.0, &total_fit), vec![""]);
+        assert_eq!(lines("一二三四五。六七", 50.0, &total_fit), vec!["一二三四", "五。六七"]);
+        // 单词比可用宽度还宽时退回贪心断行并强制断开
+        assert_eq!(lines("abcdefghij ab", 20.0, &total_fit), vec!["abcd", "efgh", "ij ", "ab"]);
+    }
+
+    /// 测试通过排版设置选择断行方式
+    #[test]
+    fn test_typography_config() {
+        let config = PageConfig {
+            width: 80.0,
+            height: 200.0,
+            margin_top: 10.0,
+            margin_bottom: 10.0,
+            margin_left: 10.0,
+            margin_right: 10.0,
+        };
+        let block = ContentBlock {
+            block_type: ContentBlockType::Text,
+            content: Cow::Borrowed("a a bb dddd a dddd ffffff eeeee"),
+            styles: style(),
+            metrics: None,
+            language: None,
+        };
+        let greedy = LayoutEngine::new(config.clone()).break_block(&block);
+        assert_eq!(greedy[1].byte_range, 12..19);
+
+        let typography = TypographyConfig {
+            line_break: LineBreakMode::TotalFit(TotalFitConfig::default()),
+            ..TypographyConfig::default()
+        };
+        assert_ne!(typography.cache_key(), TypographyConfig::default().cache_key());
+        let engine = LayoutEngine::new(config).with_typography(typography);
+        let optimal = engine.break_block(&block);
+        assert_eq!(optimal[1].byte_range, 14..26);
+        assert_eq!(engine.measure_block(&block).height, 3.0 * 12.0);
+    }
+}
@@ -3,10 +3,18 @@
 //! 负责将原始文本内容解析为结构化的文档模型
 
 use crate::document::{DocumentModel, DocumentMetadata, Chapter, ContentBlock, ContentBlockType, TextStyle};
+use crate::footnote::extract_footnotes;
+use crate::author_note::detect_author_notes;
+use crate::language::{detect_block_languages, detect_sampled_language};
+use crate::numeral::parse_chinese_numeral;
+use crate::toc::detect_toc;
+use crate::verse::detect_verse;
 use std::mem;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::{Arc, Mutex};
+use rayon::prelude::*;
 use regex::Regex;
 
 /// 解析引擎
@@ -90,40 +98,15 @@ impl ParserEngine {
     /// 
     /// 返回解析后的DocumentModel实例，只包含指定章节
     pub fn parse_txt_chapter(&self, content: &str, chapter_index: usize) -> DocumentModel {
-        let all_chapters = self.parse_all_chapters(content);
+        let language = detect_sampled_language(content);
+        let mut all_chapters = self.parse_all_chapters(content, language);
         
         if chapter_index < all_chapters.len() {
-            let chapter = all_chapters[chapter_index].clone();
-            DocumentModel {
-                metadata: DocumentMetadata {
-                    title: Cow::Borrowed("Sample Document"),
-                    author: Cow::Borrowed("Unknown"),
-                    created_at: Cow::Owned(chrono::Utc::now().to_rfc3339()),
-                },
-                chapters: vec![chapter],
-                styles: vec![TextStyle {
-                    font_size: 16.0,
-                    font_family: Cow::Borrowed("Arial"),
-                    bold: false,
-                    italic: false,
-                }],
-            }
+            let chapter = all_chapters.swap_remove(chapter_index);
+            create_document(vec![chapter], language)
         } else {
             // 如果索引超出范围，返回空文档
-            DocumentModel {
-                metadata: DocumentMetadata {
-                    title: Cow::Borrowed("Sample Document"),
-                    author: Cow::Borrowed("Unknown"),
-                    created_at: Cow::Owned(chrono::Utc::now().to_rfc3339()),
-                },
-                chapters: vec![],
-                styles: vec![TextStyle {
-                    font_size: 16.0,
-                    font_family: Cow::Borrowed("Arial"),
-                    bold: false,
-                    italic: false,
-                }],
-            }
+            create_document(vec![], language)
         }
     }
 
@@ -132,150 +115,60 @@ impl ParserEngine {
     /// # Arguments
     /// 
     /// * `content` - 需要解析的文本内容
+    /// * `language` - 文档语言
     /// 
     /// # Returns
     /// 
     /// 返回解析后的章节列表
-    fn parse_all_chapters(&self, content: &str) -> Vec<Chapter> {
-        // 按行分割内容
-        let lines: Vec<&str> = content.lines().collect();
+    fn parse_all_chapters(&self, content: &str, language: Option<&str>) -> Vec<Chapter> {
+        // 按行分割内容，跳过开头的目录
+        let lines = content_lines(content);
         
-        // 章节列表
-        let mut chapters = Vec::new();
+        let raw_chapters = collect_raw_chapters(&lines);
+        finish_chapters(raw_chapters, content, language)
+    }
 
-        // 当前章节的文本块列表
-        let mut blocks = Vec::new();
-        
-        // 当前正在累积的段落内容 - 预分配容量以提高性能
-        let mut current_paragraph = String::with_capacity(1024);
-        
-        // 默认样式 - 创建一次，通过引用使用
-        let default_style = TextStyle {
-            // 字体大小
-            font_size: 16.0,
-            // 字体名称
-            font_family: Cow::Borrowed("Arial"),
-            // 粗体
-            bold: false,
-            // 斜体
-            italic: false,
-        };
-        
-        // 章节标题的正则表达式 - 使用 lazy_static 避免重复编译
-        lazy_static::lazy_static! {
-            static ref CHAPTER_REGEX: Regex = Regex::new(r"^第[一二三四五六七八九十百千\d]+章").unwrap();
-        }
+    /// 并行解析所有章节但不进行缓存
+    /// 
+    /// 先快速预扫描出所有章节标题所在的行，再在标题边界处把文本切分为若干块，
+    /// 交给线程池分别解析，最后按原顺序拼接。由于每一块都从章节标题开始，
+    /// 块与块之间不存在未结束的段落，因此结果与串行解析完全一致。
+    /// 
+    /// # Arguments
+    /// 
+    /// * `content` - 需要解析的文本内容
+    /// * `language` - 文档语言
+    /// 
+    /// # Returns
+    /// 
+    /// 返回解析后的章节列表
+    fn parse_all_chapters_parallel(&self, content: &str, language: Option<&str>) -> Vec<Chapter> {
+        let lines = content_lines(content);
         
-        // 存储当前章节标题
-        let mut current_chapter_title = Cow::Borrowed("全文"); // 默认标题
+        // 预扫描：找出所有章节标题所在的行号
+        let heading_indices: Vec<usize> = lines
+            .par_iter()
+            .enumerate()
+            .filter(|(_, line)| is_chapter_heading(line))
+            .map(|(index, _)| index)
+            .collect();
         
-        for line in lines.iter() {
-            // 检查是否为章节标题
-            if line.starts_with("# ") || CHAPTER_REGEX.is_match(line.trim()) {
-                // 如果有累积的段落内容，添加到当前章节
-                if !current_paragraph.is_empty() {
-                    let block = ContentBlock {
-                        block_type: ContentBlockType::Text,
-                        content: Cow::Owned(mem::take(&mut current_paragraph)),
-                        styles: default_style.clone(),
-                        metrics: None,
-                    };
-                    // 重新分配容量
-                    current_paragraph.reserve(1024);
-                    blocks.push(block);
-                }
-                
-                // 如果有已有的章节内容，保存为一个章节
-                if !blocks.is_empty() {
-                    let chapter = Chapter {
-                        id: Cow::Owned(format!("chapter_{}", chapters.len())),
-                        title: mem::take(&mut current_chapter_title), // 使用实际的章节标题
-                        content: mem::take(&mut blocks),
-                    };
-                    
-                    chapters.push(chapter);
-                } else if !chapters.is_empty() {
-                    // 特殊情况：如果blocks为空但已有章节，说明是连续的章节标题
-                    // 在这种情况下，我们仍然需要更新当前章节标题
-                    current_chapter_title = if let Some(stripped) = line.strip_prefix("# ") {
-                        Cow::Owned(stripped.to_string()) // 移除 "# " 前缀
-                    } else {
-                        Cow::Owned(line.trim().to_string()) // 使用整行作为章节标题
-                    };
-                    continue;
-                }
-                
-                // 提取新的章节标题
-                current_chapter_title = if let Some(stripped) = line.strip_prefix("# ") {
-                    Cow::Owned(stripped.to_string()) // 移除 "# " 前缀
-                } else {
-                    Cow::Owned(line.trim().to_string()) // 使用整行作为章节标题
-                };
-            } else if line.trim().is_empty() {
-                // 空行表示段落结束
-                if !current_paragraph.is_empty() {
-                    let block = ContentBlock {
-                        block_type: if current_paragraph.trim().is_empty() {
-                            ContentBlockType::Blank
-                        } else {
-                            ContentBlockType::Text
-                        },
-                        content: Cow::Owned(mem::take(&mut current_paragraph)),
-                        styles: default_style.clone(),
-                        metrics: None,
-                    };
-                    // 重新分配容量
-                    current_paragraph.reserve(1024);
-                    blocks.push(block);
-                }
-            } else {
-                // 普通文本行，添加到当前段落
-                if !current_paragraph.is_empty() {
-                    current_paragraph.push('\n');
-                }
-                current_paragraph.push_str(line);
-            }
+        // 章节太少时并行没有收益，直接串行解析
+        if heading_indices.len() < PARALLEL_MIN_HEADINGS {
+            return finish_chapters(collect_raw_chapters(&lines), content, language);
         }
         
-        // 处理最后的段落
-        if !current_paragraph.is_empty() {
-            let block = ContentBlock {
-                block_type: ContentBlockType::Text,
-                content: Cow::Owned(mem::take(&mut current_paragraph)),
-                styles: default_style.clone(),
-                metrics: None,
-            };
-            blocks.push(block);
-        }
+        let ranges = split_at_headings(lines.len(), &heading_indices, rayon::current_num_threads() * 4);
         
-        // 添加最后一个章节
-        if !blocks.is_empty() {
-            let chapter = Chapter {
-                id: Cow::Owned(format!("chapter_{}", chapters.len())),
-                title: current_chapter_title,
-                content: blocks,
-            };
-            
-            chapters.push(chapter);
-        } else if chapters.is_empty() {
-            // 如果没有任何内容，创建一个默认章节
-            let block = ContentBlock {
-                block_type: ContentBlockType::Text,
-                content: Cow::Owned(content.to_string()),
-                styles: default_style.clone(),
-                metrics: None,
-            };
-            
-            let chapter = Chapter {
-                id: Cow::Borrowed("chapter_0"),
-                title: Cow::Borrowed("全文"),
-                content: vec![block],
-            };
-            
-            chapters.push(chapter);
-        }
+        let raw_chapters: Vec<RawChapter> = ranges
+            .into_par_iter()
+            .map(|range| collect_raw_chapters(&lines[range]))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flatten()
+            .collect();
         
-        chapters
+        finish_chapters(raw_chapters, content, language)
     }
 
     /// 解析文本内容为文档模型
@@ -290,26 +183,542 @@ impl ParserEngine {
     /// 
     /// 返回解析后的DocumentModel实例
     pub fn parse_txt(&self, content: &str) -> DocumentModel {
-        let chapters = self.parse_all_chapters(content);
+        let language = detect_sampled_language(content);
+        let chapters = self.parse_all_chapters(content, language);
         
         // 缓存所有章节
         for chapter in &chapters {
             self.cache_chapter(chapter.clone());
         }
         
-        DocumentModel {
-            metadata: DocumentMetadata {
-                title: Cow::Borrowed("Sample Document"),
-                author: Cow::Borrowed("Unknown"),
-                created_at: Cow::Owned(chrono::Utc::now().to_rfc3339()),
-            },
-            chapters,
-            styles: vec![TextStyle {
-                font_size: 16.0,
-                font_family: Cow::Borrowed("Arial"),
-                bold: false,
-                italic: false,
-            }],
+        create_document(chapters, language)
+    }
+
+    /// 并行解析文本内容为文档模型
+    /// 
+    /// 适用于章节数量很多的大型文档，解析结果与 [`ParserEngine::parse_txt`] 完全相同
+    /// 
+    /// # Arguments
+    /// 
+    /// * `content` - 需要解析的文本内容
+    /// 
+    /// # Returns
+    /// 
+    /// 返回解析后的DocumentModel实例
+    pub fn parse_txt_parallel(&self, content: &str) -> DocumentModel {
+        let language = detect_sampled_language(content);
+        let chapters = self.parse_all_chapters_parallel(content, language);
+        
+        // 缓存所有章节
+        for chapter in &chapters {
+            self.cache_chapter(chapter.clone());
+        }
+        
+        create_document(chapters, language)
+    }
+}
+
+impl Default for ParserEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 启用并行解析所需的最少章节标题数
+const PARALLEL_MIN_HEADINGS: usize = 16;
+
+/// 没有章节标题的文本自动分章时，每一部分的目标字数
+const PART_TARGET_CHARS: usize = 5000;
+
+/// 自动分章生成的标题中，开头文字的最大字数
+const PART_TITLE_CHARS: usize = 10;
+
+lazy_static::lazy_static! {
+    /// 章节标题的正则表达式 - 使用 lazy_static 避免重复编译
+    static ref CHAPTER_REGEX: Regex = Regex::new(r"^第([零〇一二两三四五六七八九十百千万廿卅壹贰叁肆伍陆柒捌玖拾佰仟兩貳參陸萬\d]+)章").unwrap();
+    /// 诗词标记中需要去除的标签，如 `<stanza>`、`<v>`
+    static ref POEM_TAG_REGEX: Regex = Regex::new(r"</?[A-Za-z][^>]*>").unwrap();
+    /// 场景分隔行的正则表达式：只由装饰符号组成的一行，线条类符号至少连续出现三个
+    static ref SCENE_BREAK_REGEX: Regex = Regex::new(
+        r"^(?:[※＊*◇◆○●☆★♢♦❖✻✽⁂§#＃·•]+|[—―─━\-－=＝~～]{3,})$"
+    ).unwrap();
+}
+
+/// 尚未分配ID的章节
+/// 
+/// 分块解析时各块独立产生，拼接后再统一编号
+struct RawChapter {
+    /// 章节标题
+    title: Cow<'static, str>,
+    /// 章节内容块列表
+    content: Vec<ContentBlock>,
+    /// 是否来自章节标题，文本开头标题之前的内容没有标题
+    from_heading: bool,
+}
+
+/// 判断一行文本是否为章节标题
+pub(crate) fn is_chapter_heading(line: &str) -> bool {
+    line.starts_with("# ") || CHAPTER_REGEX.is_match(line.trim())
+}
+
+/// 判断一行文本是否为场景分隔符
+/// 
+/// 符号之间的空白会被忽略，因此 `* * *` 与 `***` 等价
+fn is_scene_break(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    !compact.is_empty() && SCENE_BREAK_REGEX.is_match(&compact)
+}
+
+/// 获取标题中的 `第N章` 部分
+/// 
+/// # Returns
+/// 
+/// 标题不以 `第N章` 开头时返回None
+pub(crate) fn chapter_label(title: &str) -> Option<&str> {
+    CHAPTER_REGEX.find(title).map(|m| m.as_str())
+}
+
+/// 获取标题中的章节序号
+/// 
+/// # Arguments
+/// 
+/// * `title` - 章节标题，如 `第一千零二十三章 归来`、`第１０２３章`
+/// 
+/// # Returns
+/// 
+/// 标题不以 `第N章` 开头或序号无法解析时返回None
+pub fn chapter_number(title: &str) -> Option<u32> {
+    CHAPTER_REGEX
+        .captures(title.trim())
+        .and_then(|caps| parse_chinese_numeral(&caps[1]))
+}
+
+/// 按行分割文本，并去除开头的目录
+/// 
+/// 目录中的每一行都会匹配章节标题，如果不去除会产生大量空章节或错误的标题
+fn content_lines(content: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = content.lines().collect();
+    if let Some(toc) = detect_toc(&lines) {
+        lines.drain(toc.line_range);
+    }
+    lines
+}
+
+/// 从章节标题行中提取标题文本
+pub(crate) fn heading_title(line: &str) -> Cow<'static, str> {
+    if let Some(stripped) = line.strip_prefix("# ") {
+        Cow::Owned(stripped.to_string()) // 移除 "# " 前缀
+    } else {
+        Cow::Owned(line.trim().to_string()) // 使用整行作为章节标题
+    }
+}
+
+/// 默认文本样式
+fn default_text_style() -> TextStyle {
+    TextStyle {
+        // 字体大小
+        font_size: 16.0,
+        // 字体名称
+        font_family: Cow::Borrowed("Arial"),
+        // 粗体
+        bold: false,
+        // 斜体
+        italic: false,
+    }
+}
+
+/// 用解析出的章节和识别出的文档语言构建文档模型
+fn create_document(chapters: Vec<Chapter>, language: Option<&'static str>) -> DocumentModel {
+    DocumentModel {
+        metadata: DocumentMetadata {
+            title: Cow::Borrowed("Sample Document"),
+            author: Cow::Borrowed("Unknown"),
+            created_at: Cow::Owned(chrono::Utc::now().to_rfc3339()),
+            language: language.map(Cow::Borrowed),
+        },
+        chapters,
+        styles: vec![default_text_style()],
+    }
+}
+
+/// 在章节标题处把行区间切分为若干块
+/// 
+/// 除第一块外，每一块都从一个章节标题行开始，块的大小尽量接近 `lines_len / target_chunks`
+/// 
+/// # Arguments
+/// 
+/// * `lines_len` - 总行数
+/// * `heading_indices` - 章节标题所在的行号（升序）
+/// * `target_chunks` - 期望的块数
+/// 
+/// # Returns
+/// 
+/// 返回按顺序排列的行区间列表
+fn split_at_headings(lines_len: usize, heading_indices: &[usize], target_chunks: usize) -> Vec<Range<usize>> {
+    let target_size = (lines_len / target_chunks.max(1)).max(1);
+    let mut ranges = Vec::with_capacity(target_chunks + 1);
+    let mut chunk_start = 0;
+    
+    for &index in heading_indices {
+        if index - chunk_start >= target_size {
+            ranges.push(chunk_start..index);
+            chunk_start = index;
+        }
+    }
+    ranges.push(chunk_start..lines_len);
+    
+    ranges
+}
+
+/// 逐行扫描，收集章节标题和内容块
+/// 
+/// 章节标题之后没有任何内容时，该标题会被下一个标题取代
+/// 
+/// # Arguments
+/// 
+/// * `lines` - 按行分割的文本内容
+/// 
+/// # Returns
+/// 
+/// 返回尚未编号的章节列表
+fn collect_raw_chapters(lines: &[&str]) -> Vec<RawChapter> {
+    // 章节列表
+    let mut chapters = Vec::new();
+
+    // 当前章节的文本块列表
+    let mut blocks = Vec::new();
+    
+    // 当前正在累积的段落内容 - 预分配容量以提高性能
+    let mut current_paragraph = String::with_capacity(1024);
+    
+    // 默认样式 - 创建一次，通过引用使用
+    let default_style = default_text_style();
+    
+    // 存储当前章节标题
+    let mut current_chapter_title = Cow::Borrowed("全文"); // 默认标题
+    let mut current_from_heading = false;
+    
+    // 是否位于 `<poem>` 标记内
+    let mut in_poem = false;
+    
+    for &line in lines.iter() {
+        let mut line = line;
+        
+        // 未闭合的诗词标记在下一个章节标题处结束
+        if in_poem && is_chapter_heading(line) {
+            push_paragraph(&mut blocks, &mut current_paragraph, ContentBlockType::Verse, &default_style);
+            in_poem = false;
+        }
+        
+        // 显式的诗词标记，每个诗节生成一个诗词块；开始和结束标记可以与诗句在同一行
+        if !in_poem {
+            if let Some(rest) = line.trim().strip_prefix("<poem") {
+                push_paragraph(&mut blocks, &mut current_paragraph, ContentBlockType::Text, &default_style);
+                in_poem = true;
+                line = rest.split_once('>').map_or("", |(_, rest)| rest);
+            }
         }
+        if in_poem {
+            let (verse, after) = match line.find("</poem") {
+                Some(index) => (&line[..index], Some(line[index..].split_once('>').map_or("", |(_, rest)| rest))),
+                None => (line, None),
+            };
+            let verse_line = POEM_TAG_REGEX.replace_all(verse, "");
+            if verse_line.trim().is_empty() {
+                // 空行和 `<stanza>` 等标签分隔诗节
+                push_paragraph(&mut blocks, &mut current_paragraph, ContentBlockType::Verse, &default_style);
+            } else {
+                if !current_paragraph.is_empty() {
+                    current_paragraph.push('\n');
+                }
+                current_paragraph.push_str(verse_line.trim());
+            }
+            match after {
+                Some(after) => {
+                    push_paragraph(&mut blocks, &mut current_paragraph, ContentBlockType::Verse, &default_style);
+                    in_poem = false;
+                    // 结束标记之后的文字按普通文本处理
+                    line = after;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                }
+                None => continue,
+            }
+        }
+        
+        // 检查是否为章节标题
+        if is_chapter_heading(line) {
+            // 如果有累积的段落内容，添加到当前章节
+            if !current_paragraph.is_empty() {
+                let block = ContentBlock {
+                    block_type: ContentBlockType::Text,
+                    content: Cow::Owned(mem::take(&mut current_paragraph)),
+                    styles: default_style.clone(),
+                    metrics: None,
+                    language: None,
+                };
+                // 重新分配容量
+                current_paragraph.reserve(1024);
+                blocks.push(block);
+            }
+            
+            // 如果有已有的章节内容，保存为一个章节
+            // 否则说明是连续的章节标题，只需更新当前章节标题
+            if !blocks.is_empty() {
+                chapters.push(RawChapter {
+                    title: mem::take(&mut current_chapter_title), // 使用实际的章节标题
+                    content: mem::take(&mut blocks),
+                    from_heading: current_from_heading,
+                });
+            }
+            
+            // 提取新的章节标题
+            current_chapter_title = heading_title(line);
+            current_from_heading = true;
+        } else if is_scene_break(line) {
+            // 场景分隔符单独成块，并结束之前的段落
+            push_paragraph(&mut blocks, &mut current_paragraph, ContentBlockType::Text, &default_style);
+            blocks.push(ContentBlock {
+                block_type: ContentBlockType::SceneBreak,
+                content: Cow::Owned(line.trim().to_string()),
+                styles: default_style.clone(),
+                metrics: None,
+                language: None,
+            });
+        } else if line.trim().is_empty() {
+            // 空行表示段落结束
+            if !current_paragraph.is_empty() {
+                let block = ContentBlock {
+                    block_type: if current_paragraph.trim().is_empty() {
+                        ContentBlockType::Blank
+                    } else {
+                        ContentBlockType::Text
+                    },
+                    content: Cow::Owned(mem::take(&mut current_paragraph)),
+                    styles: default_style.clone(),
+                    metrics: None,
+                    language: None,
+                };
+                // 重新分配容量
+                current_paragraph.reserve(1024);
+                blocks.push(block);
+            }
+        } else {
+            // 普通文本行，添加到当前段落
+            if !current_paragraph.is_empty() {
+                current_paragraph.push('\n');
+            }
+            current_paragraph.push_str(line);
+        }
+    }
+    
+    // 处理最后的段落，未闭合的诗词标记在文本末尾结束
+    if !current_paragraph.is_empty() {
+        let block = ContentBlock {
+            block_type: if in_poem { ContentBlockType::Verse } else { ContentBlockType::Text },
+            content: Cow::Owned(mem::take(&mut current_paragraph)),
+            styles: default_style,
+            metrics: None,
+            language: None,
+        };
+        blocks.push(block);
+    }
+    
+    // 添加最后一个章节
+    if !blocks.is_empty() {
+        chapters.push(RawChapter {
+            title: current_chapter_title,
+            content: blocks,
+            from_heading: current_from_heading,
+        });
+    }
+    
+    chapters
+}
+
+/// 把累积的段落内容作为一个内容块添加到块列表
+/// 
+/// # Arguments
+/// 
+/// * `blocks` - 当前章节的内容块列表
+/// * `paragraph` - 正在累积的段落内容，添加后被清空
+/// * `block_type` - 内容块类型
+/// * `style` - 文本样式
+fn push_paragraph(blocks: &mut Vec<ContentBlock>, paragraph: &mut String, block_type: ContentBlockType, style: &TextStyle) {
+    if paragraph.is_empty() {
+        return;
+    }
+    blocks.push(ContentBlock {
+        block_type,
+        content: Cow::Owned(mem::take(paragraph)),
+        styles: style.clone(),
+        metrics: None,
+        language: None,
+    });
+    paragraph.reserve(1024);
+}
+
+/// 为章节分配ID并识别诗词、作者的话和脚注，在没有任何内容时生成默认章节，没有章节标题时自动分章
+/// 
+/// 自动分章需要看到全部内容，在串行阶段完成；各章节内部的识别互不依赖，交给线程池并行处理
+/// 
+/// # Arguments
+/// 
+/// * `raw_chapters` - 按顺序排列的未编号章节
+/// * `content` - 原始文本内容
+/// * `language` - 文档语言
+/// 
+/// # Returns
+/// 
+/// 返回最终的章节列表
+fn finish_chapters(raw_chapters: Vec<RawChapter>, content: &str, language: Option<&str>) -> Vec<Chapter> {
+    if raw_chapters.is_empty() {
+        // 如果没有任何内容，创建一个默认章节
+        let mut blocks = vec![ContentBlock {
+            block_type: ContentBlockType::Text,
+            content: Cow::Owned(content.to_string()),
+            styles: default_text_style(),
+            metrics: None,
+            language: None,
+        }];
+        detect_block_languages(&mut blocks, language);
+        
+        return vec![Chapter {
+            id: Cow::Borrowed("chapter_0"),
+            title: Cow::Borrowed("全文"),
+            number: None,
+            content: blocks,
+            footnote_refs: vec![],
+            author_note: false,
+        }];
     }
-}
\ No newline at end of file
+    
+    // 整个文本都没有章节标题时，自动拆分为若干部分
+    let raw_chapters = if raw_chapters.len() == 1 && !raw_chapters[0].from_heading {
+        raw_chapters.into_iter().flat_map(split_into_parts).collect()
+    } else {
+        raw_chapters
+    };
+    
+    raw_chapters
+        .into_par_iter()
+        .enumerate()
+        .map(|(index, raw)| {
+            let mut chapter = Chapter {
+                id: Cow::Owned(format!("chapter_{}", index)),
+                number: chapter_number(&raw.title),
+                title: raw.title,
+                content: raw.content,
+                footnote_refs: vec![],
+                author_note: false,
+            };
+            // 诗词需要在脚注之前拆分，使脚注引用的块索引指向最终的内容块
+            detect_verse(&mut chapter);
+            // 标记作者的话和整章公告
+            detect_author_notes(&mut chapter);
+            // 把脚注标记与章节末尾（作者的话之前）的脚注正文关联起来
+            extract_footnotes(&mut chapter);
+            // 记录与文档语言不同的内容块语言
+            detect_block_languages(&mut chapter.content, language);
+            chapter
+        })
+        .collect()
+}
+
+/// 把没有章节标题的文本拆分为若干部分
+/// 
+/// 每累积到目标字数就在下一个段落处拆分，超过一半目标字数后遇到场景分隔符时提前在分隔符处拆分，
+/// 末尾过短的部分并入上一部分。拆分只取决于文本内容，因此同一本书的阅读位置保持稳定
+/// 
+/// # Arguments
+/// 
+/// * `raw` - 整个文本组成的章节
+/// 
+/// # Returns
+/// 
+/// 返回带有 `第N部分` 标题的章节列表，文本较短时原样返回
+fn split_into_parts(raw: RawChapter) -> Vec<RawChapter> {
+    let total: usize = raw.content.iter().map(|block| block.content.chars().count()).sum();
+    if total <= PART_TARGET_CHARS * 3 / 2 {
+        return vec![raw];
+    }
+    
+    // 没有空行分段的文本整体是一个段落，按行拆开
+    let blocks = raw.content.into_iter().flat_map(|block| {
+        if block.block_type == ContentBlockType::Text && block.content.chars().count() > PART_TARGET_CHARS {
+            block.content
+                .split('\n')
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| ContentBlock {
+                    content: Cow::Owned(line.to_string()),
+                    ..block.clone()
+                })
+                .collect()
+        } else {
+            vec![block]
+        }
+    });
+    
+    let mut parts: Vec<Vec<ContentBlock>> = Vec::new();
+    let mut current = Vec::new();
+    let mut size = 0;
+    for block in blocks {
+        if block.block_type == ContentBlockType::SceneBreak && size >= PART_TARGET_CHARS / 2 {
+            // 分隔符本身被章节边界取代
+            parts.push(mem::take(&mut current));
+            size = 0;
+            continue;
+        }
+        if size >= PART_TARGET_CHARS {
+            parts.push(mem::take(&mut current));
+            size = 0;
+        }
+        size += block.content.chars().count();
+        current.push(block);
+    }
+    if !current.is_empty() {
+        match parts.last_mut() {
+            Some(last) if size < PART_TARGET_CHARS / 4 => last.extend(current),
+            _ => parts.push(current),
+        }
+    }
+    
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, content)| RawChapter {
+            title: Cow::Owned(part_title(index + 1, &content)),
+            content,
+            from_heading: false,
+        })
+        .collect()
+}
+
+/// 生成自动分章的标题
+/// 
+/// 标题为 `第N部分` 加上该部分开头的文字，在第一个标点处截断
+fn part_title(number: usize, blocks: &[ContentBlock]) -> String {
+    let first_line = blocks
+        .iter()
+        .filter(|block| block.block_type == ContentBlockType::Text)
+        .flat_map(|block| block.content.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    
+    let clause: Vec<char> = first_line
+        .chars()
+        .take_while(|c| !c.is_ascii_punctuation() && !"，。！？；：、“”‘’「」『』（）《》…—".contains(*c))
+        .collect();
+    let mut words: String = clause.iter().take(PART_TITLE_CHARS).collect();
+    if clause.len() > PART_TITLE_CHARS {
+        words.push('…');
+    }
+    
+    if words.trim().is_empty() {
+        format!("第{}部分", number)
+    } else {
+        format!("第{}部分 {}", number, words.trim())
+    }
+}
@@ -3,8 +3,11 @@
 //! 测试解析引擎相关功能的正确性
 
 #[cfg(test)]
+#[allow(clippy::len_zero)]
 mod tests {
     use crate::parser::*;
+    use crate::document::ContentBlockType;
+    use crate::toc::TableOfContents;
 
     /// 测试简单文档解析功能
     #[test]
@@ -129,4 +132,429 @@ mod tests {
         assert_eq!(document1.chapters[0].title, document2.chapters[0].title);
         assert_eq!(document1.chapters[1].title, document2.chapters[1].title);
     }
-}
\ No newline at end of file
+
+    /// 测试并行解析与串行解析结果一致
+    #[test]
+    fn test_parallel_parsing_matches_serial() {
+        let mut content = String::from("玄鉴仙族\n作者：季越人\n\n");
+        for i in 1..=200 {
+            content.push_str(&format!("第{}章 标题{}\n\n", i, i));
+            // 偶尔出现连续的章节标题
+            if i % 17 == 0 {
+                continue;
+            }
+            content.push_str(&format!("第{}章的第一段。\n第二行。\n\n第{}章的第二段。\n\n", i, i));
+        }
+        
+        let parser = ParserEngine::new();
+        let serial = parser.parse_txt(&content);
+        let parallel = parser.parse_txt_parallel(&content);
+        
+        assert_eq!(serial.chapters, parallel.chapters);
+        assert_eq!(parallel.chapters[0].title, "全文");
+    }
+
+    /// 测试章节过少时并行解析回退到串行解析
+    #[test]
+    fn test_parallel_parsing_small_document() {
+        let content = "# Chapter 1\n\nFirst chapter content.\n\n# Chapter 2\n\nSecond chapter content.";
+        let parser = ParserEngine::new();
+        
+        assert_eq!(parser.parse_txt(content).chapters, parser.parse_txt_parallel(content).chapters);
+        assert_eq!(parser.parse_txt("").chapters, parser.parse_txt_parallel("").chapters);
+    }
+
+    /// 测试章节末尾脚注的提取
+    #[test]
+    fn test_trailing_footnotes() {
+        let content = "第一章 译者序\n\n他读过《神曲》[1]，也读过《浮士德》②。\n\n第二段正文。\n\n[1]但丁的长诗。\n② 歌德的诗剧。";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        let chapter = &document.chapters[0];
+        
+        // 脚注正文不再作为普通段落出现
+        assert_eq!(chapter.content.len(), 4);
+        assert_eq!(chapter.content[1].content, "第二段正文。");
+        assert_eq!(chapter.content[2].block_type, ContentBlockType::Footnote { anchor: "chapter_0-fn-1".into() });
+        assert_eq!(chapter.content[2].content, "但丁的长诗。");
+        assert_eq!(chapter.content[3].content, "歌德的诗剧。");
+        
+        // 正文中的标记与脚注通过锚点关联
+        assert_eq!(chapter.footnote_refs.len(), 2);
+        let first = &chapter.footnote_refs[0];
+        assert_eq!(first.block_index, 0);
+        assert_eq!(&chapter.content[0].content[first.range.clone()], "[1]");
+        assert_eq!(chapter.footnote_refs[1].anchor, "chapter_0-fn-2");
+    }
+
+    /// 测试行内注释的提取
+    #[test]
+    fn test_inline_annotations() {
+        let content = "第一章 开始\n\n他来到了青杜山（注：李家祖地）下。";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        let chapter = &document.chapters[0];
+        
+        assert_eq!(chapter.content[0].content, "他来到了青杜山下。");
+        assert_eq!(chapter.content[1].content, "李家祖地");
+        assert_eq!(chapter.footnote_refs[0].range, "他来到了青杜山".len().."他来到了青杜山".len());
+        assert_eq!(chapter.content[1].block_type, ContentBlockType::Footnote { anchor: chapter.footnote_refs[0].anchor.clone() });
+    }
+
+    /// 测试没有引用的编号列表不被当作脚注
+    #[test]
+    fn test_unreferenced_numbered_list() {
+        let content = "第一章 准备\n\n需要准备以下物品：\n\n①灵石\n②符箓";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        let chapter = &document.chapters[0];
+        
+        assert_eq!(chapter.content.len(), 2);
+        assert!(chapter.footnote_refs.is_empty());
+        assert!(chapter.content.iter().all(|block| block.block_type == ContentBlockType::Text));
+    }
+
+    /// 测试场景分隔符的识别
+    #[test]
+    fn test_scene_break_detection() {
+        let content = "第一章 开始\n\n第一幕。\n\n※※※\n\n第二幕。\n* * *\n第三幕。\n\n——————\n\n◇\n\n第四幕。\n\n……\n\n——\n\n完。";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        let blocks = &document.chapters[0].content;
+        
+        let scene_breaks: Vec<&str> = blocks.iter()
+            .filter(|block| block.block_type == ContentBlockType::SceneBreak)
+            .map(|block| block.content.as_ref())
+            .collect();
+        assert_eq!(scene_breaks, vec!["※※※", "* * *", "——————", "◇"]);
+        
+        // 没有空行包围的分隔符同样会结束段落
+        assert_eq!(blocks[2].content, "第二幕。");
+        assert_eq!(blocks[4].content, "第三幕。");
+        
+        // 省略号和短破折号不是分隔符
+        assert!(blocks.iter().any(|block| block.content == "……" && block.block_type == ContentBlockType::Text));
+        assert!(blocks.iter().any(|block| block.content == "——" && block.block_type == ContentBlockType::Text));
+    }
+
+    /// 测试跳过开头的目录
+    #[test]
+    fn test_skip_table_of_contents() {
+        let content = "玄鉴仙族\n作者：季越人\n\n目录\n\n第一章 青杜\n第二章 玉京\n第一卷 青池\n第三章 月华\n\n第一章 青杜\n李家的故事。\n\n第二章 玉京\n故事继续。\n\n第三章 月华\n故事结束。";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        
+        let titles: Vec<&str> = document.chapters.iter().map(|chapter| chapter.title.as_ref()).collect();
+        assert_eq!(titles, vec!["全文", "第一章 青杜", "第二章 玉京", "第三章 月华"]);
+        assert_eq!(document.chapters[0].content[0].content, "玄鉴仙族\n作者：季越人");
+        assert_eq!(document.chapters[1].content[0].content, "李家的故事。");
+        
+        // 并行解析同样跳过目录
+        let parallel = parser.parse_txt_parallel(content);
+        assert_eq!(parallel.chapters, document.chapters);
+        
+        let toc = TableOfContents::detect(content).unwrap();
+        assert_eq!(toc.entries, vec!["第一章 青杜", "第二章 玉京", "第三章 月华"]);
+        assert_eq!(toc.line_range, 3..10);
+        assert!(toc.verify(&document.chapters).is_consistent());
+    }
+
+    /// 测试目录后直接跟随正文的情况
+    #[test]
+    fn test_table_of_contents_followed_by_text() {
+        let content = "目 录：\n第一章 出发\n第二章 归来\n第一章 出发\n旅程开始了。\n\n第二章 归来\n旅程结束了。";
+        let toc = TableOfContents::detect(content).unwrap();
+        assert_eq!(toc.entries, vec!["第一章 出发", "第二章 归来"]);
+        
+        // 目录后直接是正文，没有重复第一章标题
+        let content = "目录\n第一章 出发\n第二章 归来\n第三章 远行\n旅程开始了。\n\n第四章 归来\n旅程结束了。";
+        let toc = TableOfContents::detect(content).unwrap();
+        assert_eq!(toc.entries, vec!["第一章 出发", "第二章 归来"]);
+        
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        let titles: Vec<&str> = document.chapters.iter().map(|chapter| chapter.title.as_ref()).collect();
+        assert_eq!(titles, vec!["第三章 远行", "第四章 归来"]);
+        
+        // 用目录校验正文章节
+        let verification = toc.verify(&document.chapters);
+        assert_eq!(verification.missing, vec!["第一章 出发", "第二章 归来"]);
+        assert_eq!(verification.unlisted, vec!["第三章 远行", "第四章 归来"]);
+        assert!(!verification.is_consistent());
+    }
+
+    /// 测试没有目录的文本不受影响
+    #[test]
+    fn test_no_table_of_contents() {
+        let content = "第一章 开始\n内容一\n\n第二章 继续\n内容二";
+        assert!(TableOfContents::detect(content).is_none());
+        
+        // 没有目录标题时，少量连续标题不视为目录
+        let content = "第一章 开始\n第二章 继续\n第一章 开始\n内容";
+        assert!(TableOfContents::detect(content).is_none());
+    }
+
+    /// 测试诗词的识别
+    #[test]
+    fn test_verse_detection() {
+        let content = "第一章 青杜\n老人抬头吟道：\n　　青杜山前水，\n　　玉京城外云。\n　　一朝仙路断，\n　　百世子孙勤。\n众人默然无语。\n\n门上挂着一副对联\n\n天道酬勤修大道\n\n人心向善积福田\n\n“走吧。”\n“走吧。”";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        let blocks = &document.chapters[0].content;
+        
+        let types: Vec<&ContentBlockType> = blocks.iter().map(|block| &block.block_type).collect();
+        assert_eq!(types, vec![
+            &ContentBlockType::Text,
+            &ContentBlockType::Verse,
+            &ContentBlockType::Text,
+            &ContentBlockType::Text,
+            &ContentBlockType::Verse,
+            &ContentBlockType::Text,
+        ]);
+        
+        // 诗句保留换行并去除缩进
+        assert_eq!(blocks[1].content, "青杜山前水，\n玉京城外云。\n一朝仙路断，\n百世子孙勤。");
+        assert_eq!(blocks[2].content, "众人默然无语。");
+        // 分段书写的对联被合并为一个诗词块
+        assert_eq!(blocks[4].content, "天道酬勤修大道\n人心向善积福田");
+        // 对话不是诗词
+        assert_eq!(blocks[5].content, "“走吧。”\n“走吧。”");
+    }
+    
+    /// 测试短句叙述不被识别为诗词
+    #[test]
+    fn test_prose_not_verse() {
+        let parser = ParserEngine::new();
+        let contents = [
+            // 两行四字短句
+            "第一章 夜\n天色已晚，众人散去。\n山中寂静，鸟鸣不止。",
+            // 每句都以句号结尾的单句行
+            "第一章 夜\n天色已晚。\n众人散去。\n山中寂静。\n鸟鸣不止。",
+            // 行中出现句末标点
+            "第一章 夜\n天色已晚。众人散去。\n山中寂静。鸟鸣不止。\n夜色渐深。灯火未熄。\n风声渐起。万籁俱寂。",
+        ];
+        for content in contents {
+            let document = parser.parse_txt(content);
+            assert!(
+                document.chapters[0].content.iter().all(|block| block.block_type == ContentBlockType::Text),
+                "{}",
+                content
+            );
+        }
+        
+        // 多句成行的诗词需要至少四行
+        let document = parser.parse_txt("第一章 夜\n天色已晚，众人散去。\n山中寂静，鸟鸣不止。\n月照松间，泉流石上。\n风过林梢，夜凉如水。");
+        assert_eq!(document.chapters[0].content[0].block_type, ContentBlockType::Verse);
+    }
+
+    /// 测试显式的诗词标记
+    #[test]
+    fn test_poem_markup() {
+        let content = "第一章 序\n开篇有诗：\n<poem>\n<stanza>\n<v>春眠不觉晓</v>\n<v>处处闻啼鸟</v>\n</stanza>\n<stanza>\n<v>夜来风雨声</v>\n</stanza>\n</poem>\n正文开始。";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        let blocks = &document.chapters[0].content;
+        
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0].content, "开篇有诗：");
+        assert_eq!(blocks[1].block_type, ContentBlockType::Verse);
+        assert_eq!(blocks[1].content, "春眠不觉晓\n处处闻啼鸟");
+        assert_eq!(blocks[2].block_type, ContentBlockType::Verse);
+        assert_eq!(blocks[2].content, "夜来风雨声");
+        assert_eq!(blocks[3].block_type, ContentBlockType::Text);
+    }
+
+    /// 测试与诗句写在同一行的诗词标记
+    #[test]
+    fn test_inline_poem_markup() {
+        let content = "第一章 序\n<poem>春眠不觉晓</poem>\n正文开始。\n\n<poem class=\"shi\">夜来风雨声\n花落知多少</poem>后文。";
+        let document = ParserEngine::new().parse_txt(content);
+        let blocks = &document.chapters[0].content;
+        
+        let types: Vec<&ContentBlockType> = blocks.iter().map(|block| &block.block_type).collect();
+        assert_eq!(types, vec![
+            &ContentBlockType::Verse,
+            &ContentBlockType::Text,
+            &ContentBlockType::Verse,
+            &ContentBlockType::Text,
+        ]);
+        assert_eq!(blocks[0].content, "春眠不觉晓");
+        assert_eq!(blocks[1].content, "正文开始。");
+        assert_eq!(blocks[2].content, "夜来风雨声\n花落知多少");
+        assert_eq!(blocks[3].content, "后文。");
+    }
+
+    /// 测试未闭合的诗词标记
+    #[test]
+    fn test_unclosed_poem_markup() {
+        // 在下一个章节标题处结束
+        let content = "第一章 序\n<poem>\n春眠不觉晓\n处处闻啼鸟\n第二章 归来\n众人散去。";
+        let document = ParserEngine::new().parse_txt(content);
+        assert_eq!(document.chapters.len(), 2);
+        assert_eq!(document.chapters[0].content.len(), 1);
+        assert_eq!(document.chapters[0].content[0].block_type, ContentBlockType::Verse);
+        assert_eq!(document.chapters[1].title, "第二章 归来");
+        assert_eq!(document.chapters[1].content[0].block_type, ContentBlockType::Text);
+        
+        // 在文本末尾结束
+        let document = ParserEngine::new().parse_txt("第一章 序\n正文。\n<poem>\n春眠不觉晓");
+        let blocks = &document.chapters[0].content;
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].block_type, ContentBlockType::Verse);
+        assert_eq!(blocks[1].content, "春眠不觉晓");
+    }
+
+    /// 测试诗词之后紧接着章节标题
+    #[test]
+    fn test_poem_followed_by_chapter() {
+        let content = "第一章 序\n<poem>\n春眠不觉晓\n</poem>\n第二章 归来\n众人散去。\n\n第三章 离开\n天色已晚。";
+        let document = ParserEngine::new().parse_txt(content);
+        let titles: Vec<&str> = document.chapters.iter().map(|chapter| chapter.title.as_ref()).collect();
+        assert_eq!(titles, vec!["第一章 序", "第二章 归来", "第三章 离开"]);
+        assert_eq!(document.chapters[0].content[0].block_type, ContentBlockType::Verse);
+        assert!(document.chapters[1..].iter()
+            .flat_map(|chapter| &chapter.content)
+            .all(|block| block.block_type == ContentBlockType::Text));
+    }
+
+    /// 测试没有章节标题的文本自动分章
+    #[test]
+    fn test_auto_split_headingless_text() {
+        let paragraph = "天色渐晚，山间的雾气慢慢升起来，".repeat(10);
+        let mut content = String::new();
+        for index in 0..60 {
+            content.push_str(&format!("第{}段。{}\n\n", index, paragraph));
+            if index == 20 {
+                content.push_str("※※※\n\n");
+            }
+        }
+        
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(&content);
+        assert!(document.chapters.len() > 1);
+        assert_eq!(document.chapters[0].title, "第1部分 第0段");
+        assert_eq!(document.chapters[1].title, "第2部分 第21段");
+        assert!(document.chapters.iter().all(|chapter| chapter.number.is_none()));
+        
+        // 在场景分隔符处拆分，分隔符本身不再保留
+        assert_eq!(document.chapters[0].content.len(), 21);
+        assert!(document.chapters.iter().all(|chapter| {
+            chapter.content.iter().all(|block| block.block_type != ContentBlockType::SceneBreak)
+        }));
+        
+        // 所有段落按顺序保留
+        let paragraphs: usize = document.chapters.iter().map(|chapter| chapter.content.len()).sum();
+        assert_eq!(paragraphs, 60);
+        
+        // 拆分结果稳定，且与并行解析、按章解析一致
+        assert_eq!(parser.parse_txt(&content).chapters, document.chapters);
+        assert_eq!(parser.parse_txt_parallel(&content).chapters, document.chapters);
+        let second = parser.parse_txt_chapter(&content, 1);
+        assert_eq!(second.chapters[0], document.chapters[1]);
+    }
+
+    /// 测试没有空行分段的无标题文本按行拆分
+    #[test]
+    fn test_auto_split_without_blank_lines() {
+        let line = "他沿着山路一直走，".repeat(10);
+        let content: String = (0..100).map(|index| format!("{}{}\n", index, line)).collect();
+        
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(&content);
+        assert!(document.chapters.len() > 1);
+        assert_eq!(document.chapters[0].title, "第1部分 0他沿着山路一直走");
+        assert!(document.chapters.iter().all(|chapter| {
+            let size: usize = chapter.content.iter().map(|block| block.content.chars().count()).sum();
+            size < 6000
+        }));
+        
+        let lines: usize = document.chapters.iter().map(|chapter| chapter.content.len()).sum();
+        assert_eq!(lines, 100);
+    }
+
+    /// 测试较短的无标题文本和有标题的文本不会自动分章
+    #[test]
+    fn test_auto_split_not_applied() {
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt("很短的一段文字。");
+        assert_eq!(document.chapters.len(), 1);
+        assert_eq!(document.chapters[0].title, "全文");
+        
+        let long_chapter = format!("第一章 开始\n{}", "很长的内容。\n".repeat(2000));
+        let document = parser.parse_txt(&long_chapter);
+        assert_eq!(document.chapters.len(), 1);
+        assert_eq!(document.chapters[0].title, "第一章 开始");
+    }
+
+    /// 测试作者的话识别
+    #[test]
+    fn test_author_notes() {
+        let content = "第一章 青杜\n陆江仙睁开眼。\n\nPS：感谢书友“青松”的打赏！\n\n山下传来钟声。\n\n作者有话说：\n\n今天晚了一点，明天会准时更新。\n\n求月票！\n\n第二章 请假条\n今天身体不适，停更一天。\n\n第三章 公告天下\n陆家贴出了公告。";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        assert_eq!(document.chapters.len(), 3);
+        
+        let types: Vec<&ContentBlockType> = document.chapters[0].content.iter().map(|block| &block.block_type).collect();
+        assert_eq!(types, vec![
+            &ContentBlockType::Text,
+            &ContentBlockType::AuthorNote,
+            &ContentBlockType::Text,
+            &ContentBlockType::AuthorNote,
+            &ContentBlockType::AuthorNote,
+            &ContentBlockType::AuthorNote,
+        ]);
+        assert!(!document.chapters[0].author_note);
+        
+        // 请假条整章都是作者的话
+        assert!(document.chapters[1].author_note);
+        assert!(document.chapters[1].content.iter().all(|block| block.block_type == ContentBlockType::AuthorNote));
+        
+        // 标题中带有公告字样的正文章节不受影响
+        assert!(!document.chapters[2].author_note);
+        assert_eq!(document.chapters[2].content[0].block_type, ContentBlockType::Text);
+    }
+    
+    /// 测试以请假开头的正文章节标题和只有诗词与作者的话的章节
+    #[test]
+    fn test_author_notes_in_story_chapters() {
+        let content = "第一章 请假的少女\n少女向先生告了假。\n\n第二章 请假两天\n家中有事，停更两天。\n\n第三章 题诗\n青杜山前水，\n玉京城外云。\n一朝仙路断，\n百世子孙勤。\n\n求月票！";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        assert_eq!(document.chapters.len(), 3);
+        
+        assert!(!document.chapters[0].author_note);
+        assert_eq!(document.chapters[0].content[0].block_type, ContentBlockType::Text);
+        assert!(document.chapters[1].author_note);
+        
+        // 诗词也算作正文，章节不是整章公告
+        let chapter = &document.chapters[2];
+        assert!(!chapter.author_note);
+        assert_eq!(chapter.content[0].block_type, ContentBlockType::Verse);
+        assert_eq!(chapter.content[1].block_type, ContentBlockType::AuthorNote);
+    }
+    
+    /// 测试章节末尾作者的话之前的脚注和目录中的整章公告
+    #[test]
+    fn test_author_notes_with_footnotes_and_toc() {
+        let content = "目录\n第一章 青杜\n第三章 玉京\n第一章 青杜\n他读过《神曲》[1]。\n\n[1]但丁的长诗。\n\n作者有话说：\n\n明天准时更新。\n\n第二章 请假条\n今天停更一天。\n\n第三章 玉京\n众人来到玉京。";
+        let parser = ParserEngine::new();
+        let document = parser.parse_txt(content);
+        
+        // 脚注正文被提取，脚注块排在作者的话之前
+        let chapter = &document.chapters[0];
+        let types: Vec<&ContentBlockType> = chapter.content.iter().map(|block| &block.block_type).collect();
+        assert_eq!(types, vec![
+            &ContentBlockType::Text,
+            &ContentBlockType::Footnote { anchor: "chapter_0-fn-1".into() },
+            &ContentBlockType::AuthorNote,
+            &ContentBlockType::AuthorNote,
+        ]);
+        assert_eq!(chapter.footnote_refs.len(), 1);
+        
+        // 整章公告不参与目录校验
+        assert!(document.chapters[1].author_note);
+        let toc = TableOfContents::detect(content).unwrap();
+        let verification = toc.verify(&document.chapters);
+        assert!(verification.is_consistent(), "{:?}", verification);
+    }
+}
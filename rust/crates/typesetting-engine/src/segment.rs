@@ -0,0 +1,453 @@
+//! 文本切分
+//!
+//! 按UAX #29切分扩展字素簇，按UAX #14查找断行机会。需要在字符之间拆分文本的地方
+//! 都应当在字素簇边界上进行，以免拆开表情符号序列、组合符号和谚文音节
+
+use icu_properties::props::{
+    EastAsianWidth, ExtendedPictographic, GeneralCategory, GraphemeClusterBreak, IndicConjunctBreak, LineBreak,
+};
+use icu_properties::{CodePointMapData, CodePointSetData};
+
+/// 断行机会
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakOpportunity {
+    /// 新行起点在文本中的字节偏移
+    pub offset: usize,
+    /// 是否为强制断行：换行符之后和文本末尾
+    pub mandatory: bool,
+}
+
+/// 获取字素簇边界
+///
+/// # Arguments
+///
+/// * `text` - 需要切分的文本
+///
+/// # Returns
+///
+/// 返回每个字素簇结尾的字节偏移，按升序排列，不包含文本开头，包含文本末尾
+pub fn grapheme_boundaries(text: &str) -> Vec<usize> {
+    let grapheme_break = CodePointMapData::<GraphemeClusterBreak>::new();
+    let conjunct_break = CodePointMapData::<IndicConjunctBreak>::new();
+    let pictographic = CodePointSetData::new::<ExtendedPictographic>();
+
+    let mut boundaries = Vec::new();
+    let mut chars = text.char_indices();
+    let Some((_, first)) = chars.next() else {
+        return boundaries;
+    };
+    let mut previous = grapheme_break.get(first);
+    // GB11：是否处于 ExtPict Extend* 之后，以及是否处于 ExtPict Extend* ZWJ 之后
+    let mut after_pictographic = pictographic.contains(first);
+    let mut after_pictographic_zwj = false;
+    // GB12、GB13：紧挨着的区域指示符数量
+    let mut regional_indicators = (previous == GraphemeClusterBreak::RegionalIndicator) as usize;
+    // GB9c：印度系文字辅音簇的状态
+    let mut conjunct = ConjunctState::None.next(conjunct_break.get(first));
+
+    for (offset, c) in chars {
+        let current = grapheme_break.get(c);
+        let keep = match (previous, current) {
+            // GB3、GB4、GB5
+            (GraphemeClusterBreak::CR, GraphemeClusterBreak::LF) => true,
+            (GraphemeClusterBreak::Control | GraphemeClusterBreak::CR | GraphemeClusterBreak::LF, _)
+            | (_, GraphemeClusterBreak::Control | GraphemeClusterBreak::CR | GraphemeClusterBreak::LF) => false,
+            // GB6、GB7、GB8：谚文音节
+            (
+                GraphemeClusterBreak::L,
+                GraphemeClusterBreak::L | GraphemeClusterBreak::V | GraphemeClusterBreak::LV | GraphemeClusterBreak::LVT,
+            )
+            | (GraphemeClusterBreak::LV | GraphemeClusterBreak::V, GraphemeClusterBreak::V | GraphemeClusterBreak::T)
+            | (GraphemeClusterBreak::LVT | GraphemeClusterBreak::T, GraphemeClusterBreak::T) => true,
+            // GB9、GB9a、GB9b
+            (
+                _,
+                GraphemeClusterBreak::Extend
+                | GraphemeClusterBreak::ZWJ
+                | GraphemeClusterBreak::SpacingMark
+                | GraphemeClusterBreak::EModifier,
+            )
+            | (GraphemeClusterBreak::Prepend, _) => true,
+            // GB9c
+            _ if conjunct == ConjunctState::Linked && conjunct_break.get(c) == IndicConjunctBreak::Consonant => true,
+            // GB11
+            (GraphemeClusterBreak::ZWJ, _) => after_pictographic_zwj && pictographic.contains(c),
+            // GB12、GB13
+            (GraphemeClusterBreak::RegionalIndicator, GraphemeClusterBreak::RegionalIndicator) => regional_indicators % 2 == 1,
+            // GB999
+            _ => false,
+        };
+        if !keep {
+            boundaries.push(offset);
+        }
+
+        after_pictographic_zwj = current == GraphemeClusterBreak::ZWJ && after_pictographic;
+        after_pictographic = pictographic.contains(c) || (after_pictographic && current == GraphemeClusterBreak::Extend);
+        regional_indicators = if current == GraphemeClusterBreak::RegionalIndicator { regional_indicators + 1 } else { 0 };
+        conjunct = conjunct.next(conjunct_break.get(c));
+        previous = current;
+    }
+    boundaries.push(text.len());
+    boundaries
+}
+
+/// 把文本切分为字素簇
+///
+/// # Arguments
+///
+/// * `text` - 需要切分的文本
+///
+/// # Returns
+///
+/// 返回依次排列的字素簇
+pub fn graphemes(text: &str) -> impl Iterator<Item = &str> {
+    let mut start = 0;
+    grapheme_boundaries(text).into_iter().map(move |end| {
+        let grapheme = &text[start..end];
+        start = end;
+        grapheme
+    })
+}
+
+/// 获取字素簇中占用宽度的字符：第一个字符和其后的间距组合符号
+///
+/// 表情符号序列、区域指示符对和谚文字母组合只按第一个字符计算宽度
+///
+/// # Arguments
+///
+/// * `grapheme` - 字素簇
+pub(crate) fn spacing_chars(grapheme: &str) -> impl Iterator<Item = char> + '_ {
+    let grapheme_break = CodePointMapData::<GraphemeClusterBreak>::new();
+    grapheme
+        .chars()
+        .enumerate()
+        .filter(move |&(index, c)| index == 0 || grapheme_break.get(c) == GraphemeClusterBreak::SpacingMark)
+        .map(|(_, c)| c)
+}
+
+/// GB9c规则的状态：`Consonant [Extend Linker]* Linker [Extend Linker]* × Consonant`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConjunctState {
+    /// 不在辅音簇中
+    None,
+    /// 辅音之后，尚未出现连接符
+    Consonant,
+    /// 辅音和连接符之后，可以与下一个辅音连接
+    Linked,
+}
+
+impl ConjunctState {
+    /// 读入一个字符后的状态
+    fn next(self, property: IndicConjunctBreak) -> Self {
+        match (self, property) {
+            (_, IndicConjunctBreak::Consonant) => ConjunctState::Consonant,
+            (ConjunctState::Consonant | ConjunctState::Linked, IndicConjunctBreak::Linker) => ConjunctState::Linked,
+            (state, IndicConjunctBreak::Extend) => state,
+            _ => ConjunctState::None,
+        }
+    }
+}
+
+/// UAX #14的断行类别（经过LB1规则归并）
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    AK, AL, AP, AS, B2, BA, BB, BK, CB, CL, CM, CP, CR, EB, EM, EX, GL, H2, H3, HL, HY, ID, IN, IS,
+    JL, JT, JV, LF, NL, NS, NU, OP, PO, PR, QU, RI, SP, SY, VF, VI, WJ, ZW, ZWJ,
+}
+
+impl Class {
+    /// 获取字符的断行类别
+    ///
+    /// 按LB1规则：AI、SG、XX归为AL，SA中的组合符号归为CM、其余归为AL，CJ归为NS
+    fn of(c: char) -> Class {
+        match CodePointMapData::<LineBreak>::new().get(c) {
+            LineBreak::Aksara => Class::AK,
+            LineBreak::AksaraPrebase => Class::AP,
+            LineBreak::AksaraStart => Class::AS,
+            LineBreak::BreakBoth => Class::B2,
+            LineBreak::BreakAfter => Class::BA,
+            LineBreak::BreakBefore => Class::BB,
+            LineBreak::MandatoryBreak => Class::BK,
+            LineBreak::ContingentBreak => Class::CB,
+            LineBreak::ClosePunctuation => Class::CL,
+            LineBreak::CombiningMark => Class::CM,
+            LineBreak::CloseParenthesis => Class::CP,
+            LineBreak::CarriageReturn => Class::CR,
+            LineBreak::EBase => Class::EB,
+            LineBreak::EModifier => Class::EM,
+            LineBreak::Exclamation => Class::EX,
+            LineBreak::Glue => Class::GL,
+            LineBreak::H2 => Class::H2,
+            LineBreak::H3 => Class::H3,
+            LineBreak::HebrewLetter => Class::HL,
+            LineBreak::Hyphen => Class::HY,
+            LineBreak::Ideographic => Class::ID,
+            LineBreak::Inseparable => Class::IN,
+            LineBreak::InfixNumeric => Class::IS,
+            LineBreak::JL => Class::JL,
+            LineBreak::JT => Class::JT,
+            LineBreak::JV => Class::JV,
+            LineBreak::LineFeed => Class::LF,
+            LineBreak::NextLine => Class::NL,
+            LineBreak::Nonstarter | LineBreak::ConditionalJapaneseStarter => Class::NS,
+            LineBreak::Numeric => Class::NU,
+            LineBreak::OpenPunctuation => Class::OP,
+            LineBreak::PostfixNumeric => Class::PO,
+            LineBreak::PrefixNumeric => Class::PR,
+            LineBreak::Quotation => Class::QU,
+            LineBreak::RegionalIndicator => Class::RI,
+            LineBreak::Space => Class::SP,
+            LineBreak::BreakSymbols => Class::SY,
+            LineBreak::ViramaFinal => Class::VF,
+            LineBreak::Virama => Class::VI,
+            LineBreak::WordJoiner => Class::WJ,
+            LineBreak::ZWSpace => Class::ZW,
+            LineBreak::ZWJ => Class::ZWJ,
+            LineBreak::ComplexContext => match CodePointMapData::<GeneralCategory>::new().get(c) {
+                GeneralCategory::NonspacingMark | GeneralCategory::SpacingMark => Class::CM,
+                _ => Class::AL,
+            },
+            _ => Class::AL,
+        }
+    }
+}
+
+/// 断行判断的单位：一个基础字符连同依附于它的组合符号（LB9）
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    /// 字节偏移
+    offset: usize,
+    /// 断行类别
+    class: Class,
+    /// 是否为东亚全角、宽或半角字符
+    east_asian: bool,
+    /// 是否为前引号（Pi）
+    initial_quote: bool,
+    /// 是否为后引号（Pf）
+    final_quote: bool,
+    /// 是否为虚线圆圈（U+25CC），LB28a把它视作字母
+    dotted_circle: bool,
+    /// 是否为连字号（U+2010）
+    hyphen: bool,
+    /// 是否为未分配码位的表情符号
+    unassigned_pictographic: bool,
+    /// 是否以零宽连接符结尾
+    ends_with_zwj: bool,
+}
+
+/// 按UAX #14的判断结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decision {
+    /// 必须断行
+    Mandatory,
+    /// 可以断行
+    Allowed,
+    /// 不能断行
+    Prohibited,
+}
+
+/// 查找断行机会
+///
+/// 实现UAX #14的默认断行规则（LB25使用成对规则的简化形式）。泰文等不用空格分词的文字
+/// 需要词典才能断词，这里按LB1归为字母，只在空格处断行
+///
+/// # Arguments
+///
+/// * `text` - 需要断行的文本
+///
+/// # Returns
+///
+/// 返回所有断行机会，按偏移升序排列，文本末尾是强制断行
+pub fn line_break_opportunities(text: &str) -> Vec<BreakOpportunity> {
+    let items = line_break_items(text);
+    let mut opportunities = Vec::new();
+    let mut regional_indicators = 0;
+    for index in 1..items.len() {
+        regional_indicators = if items[index - 1].class == Class::RI { regional_indicators + 1 } else { 0 };
+        let mandatory = match decide(&items, index, regional_indicators) {
+            Decision::Mandatory => true,
+            Decision::Allowed => false,
+            Decision::Prohibited => continue,
+        };
+        opportunities.push(BreakOpportunity { offset: items[index].offset, mandatory });
+    }
+    // LB3
+    if !text.is_empty() {
+        opportunities.push(BreakOpportunity { offset: text.len(), mandatory: true });
+    }
+    opportunities
+}
+
+/// 把文本分为断行判断的单位，组合符号依附于前一个字符（LB9、LB10）
+fn line_break_items(text: &str) -> Vec<Item> {
+    let east_asian_width = CodePointMapData::<EastAsianWidth>::new();
+    let general_category = CodePointMapData::<GeneralCategory>::new();
+    let pictographic = CodePointSetData::new::<ExtendedPictographic>();
+
+    let mut items: Vec<Item> = Vec::with_capacity(text.len());
+    for (offset, c) in text.char_indices() {
+        let mut class = Class::of(c);
+        if matches!(class, Class::CM | Class::ZWJ) {
+            if let Some(last) = items.last_mut() {
+                if !matches!(last.class, Class::BK | Class::CR | Class::LF | Class::NL | Class::SP | Class::ZW) {
+                    last.ends_with_zwj = class == Class::ZWJ;
+                    continue;
+                }
+            }
+            class = Class::AL;
+        }
+        let category = general_category.get(c);
+        items.push(Item {
+            offset,
+            class,
+            east_asian: matches!(
+                east_asian_width.get(c),
+                EastAsianWidth::Fullwidth | EastAsianWidth::Wide | EastAsianWidth::Halfwidth
+            ),
+            initial_quote: category == GeneralCategory::InitialPunctuation,
+            final_quote: category == GeneralCategory::FinalPunctuation,
+            dotted_circle: c == '\u{25CC}',
+            hyphen: c == '\u{2010}',
+            unassigned_pictographic: category == GeneralCategory::Unassigned && pictographic.contains(c),
+            ends_with_zwj: Class::of(c) == Class::ZWJ,
+        });
+    }
+    items
+}
+
+/// 按UAX #14的规则判断第 `index` 个单位之前能否断行
+///
+/// # Arguments
+///
+/// * `items` - 断行判断的单位
+/// * `index` - 断点之后的单位
+/// * `regional_indicators` - 断点之前紧挨着的区域指示符数量
+fn decide(items: &[Item], index: usize, regional_indicators: usize) -> Decision {
+    use Class::*;
+    use Decision::*;
+
+    let previous = &items[index - 1];
+    let current = &items[index];
+    let next = items.get(index + 1);
+    let before_previous = index.checked_sub(2).map(|position| &items[position]);
+    let (a, b) = (previous.class, current.class);
+
+    // LB4、LB5
+    match (a, b) {
+        (BK | LF | NL, _) => return Mandatory,
+        (CR, LF) => return Prohibited,
+        (CR, _) => return Mandatory,
+        _ => {}
+    }
+    // LB6、LB7
+    if matches!(b, BK | CR | LF | NL | SP | ZW) {
+        return Prohibited;
+    }
+    // 跳过空格后的前一个单位，用于带 SP* 的规则
+    let before_spaces = items[..index].iter().rposition(|item| item.class != SP);
+    let s = before_spaces.map(|position| items[position].class);
+    // LB8、LB8a
+    if s == Some(ZW) {
+        return Allowed;
+    }
+    if previous.ends_with_zwj {
+        return Prohibited;
+    }
+    // LB11、LB12、LB12a、LB13、LB14
+    if a == WJ || b == WJ || a == GL || (b == GL && !matches!(a, SP | BA | HY)) || matches!(b, CL | CP | EX | SY) || s == Some(OP) {
+        return Prohibited;
+    }
+    // LB15a
+    if let Some(position) = before_spaces {
+        let quote = &items[position];
+        let opening = position == 0 || matches!(items[position - 1].class, BK | CR | LF | NL | OP | QU | GL | SP | ZW);
+        if quote.class == QU && quote.initial_quote && opening {
+            return Prohibited;
+        }
+    }
+    // LB15b
+    if b == QU
+        && current.final_quote
+        && next.is_none_or(|item| matches!(item.class, SP | GL | WJ | CL | QU | CP | EX | IS | SY | BK | CR | LF | NL | ZW))
+    {
+        return Prohibited;
+    }
+    // LB15c、LB15d
+    if a == SP && b == IS && next.is_some_and(|item| item.class == NU) {
+        return Allowed;
+    }
+    if b == IS {
+        return Prohibited;
+    }
+    // LB16、LB17
+    if (matches!(s, Some(CL | CP)) && b == NS) || (s == Some(B2) && b == B2) {
+        return Prohibited;
+    }
+    // LB18
+    if a == SP {
+        return Allowed;
+    }
+    // LB19、LB19a
+    if (b == QU && !current.initial_quote) || (a == QU && !previous.final_quote) {
+        return Prohibited;
+    }
+    if b == QU && (!previous.east_asian || next.is_none_or(|item| !item.east_asian)) {
+        return Prohibited;
+    }
+    if a == QU && (!current.east_asian || before_previous.is_none_or(|item| !item.east_asian)) {
+        return Prohibited;
+    }
+    // LB20
+    if a == CB || b == CB {
+        return Allowed;
+    }
+    // LB20a
+    if (a == HY || previous.hyphen)
+        && matches!(b, AL | HL)
+        && before_previous.is_none_or(|item| matches!(item.class, BK | CR | LF | NL | SP | ZW | CB | GL))
+    {
+        return Prohibited;
+    }
+    // LB21、LB21a、LB21b、LB22
+    if matches!(b, BA | HY | NS | IN) || a == BB || (a == SY && b == HL) {
+        return Prohibited;
+    }
+    if before_previous.is_some_and(|item| item.class == HL) && (a == HY || (a == BA && !previous.east_asian)) && b != HL {
+        return Prohibited;
+    }
+    // LB23 - LB29
+    let prohibited = matches!(
+        (a, b),
+        (AL | HL, NU) | (NU, AL | HL)
+            | (PR, ID | EB | EM) | (ID | EB | EM, PO)
+            | (PR | PO, AL | HL) | (AL | HL, PR | PO)
+            | (CL | CP | NU, PO | PR) | (PO | PR, OP | NU) | (HY | IS | NU | SY, NU)
+            | (JL, JL | JV | H2 | H3) | (JV | H2, JV | JT) | (JT | H3, JT)
+            | (JL | JV | JT | H2 | H3, PO) | (PR, JL | JV | JT | H2 | H3)
+            | (AL | HL, AL | HL)
+            | (IS, AL | HL)
+    );
+    if prohibited {
+        return Prohibited;
+    }
+    // LB28a
+    let aksara = |item: &Item| matches!(item.class, AK | AS) || item.dotted_circle;
+    if (a == AP && aksara(current))
+        || (aksara(previous) && matches!(b, VF | VI))
+        || (a == VI && before_previous.is_some_and(aksara) && (b == AK || current.dotted_circle))
+        || (aksara(previous) && aksara(current) && next.is_some_and(|item| item.class == VF))
+    {
+        return Prohibited;
+    }
+    // LB30
+    if (matches!(a, AL | HL | NU) && b == OP && !current.east_asian) || (a == CP && !previous.east_asian && matches!(b, AL | HL | NU)) {
+        return Prohibited;
+    }
+    // LB30a、LB30b
+    if (a == RI && b == RI && regional_indicators % 2 == 1) || (b == EM && (a == EB || previous.unassigned_pictographic)) {
+        return Prohibited;
+    }
+    // LB31
+    Allowed
+}
@@ -0,0 +1,71 @@
+//! 文本切分单元测试
+//!
+//! 测试字素簇切分和断行机会查找的正确性
+
+#[cfg(test)]
+mod tests {
+    use crate::segment::*;
+
+    /// 获取断行机会的偏移
+    fn offsets(text: &str) -> Vec<usize> {
+        line_break_opportunities(text).iter().map(|opportunity| opportunity.offset).collect()
+    }
+
+    /// 测试表情符号序列、区域指示符和组合符号构成完整的字素簇
+    #[test]
+    fn test_emoji_graphemes() {
+        let text = "👨‍👩‍👧‍👦👍🏽🇨🇳🇯🇵e\u{301}\r\n";
+        let clusters: Vec<&str> = graphemes(text).collect();
+        assert_eq!(clusters, vec!["👨‍👩‍👧‍👦", "👍🏽", "🇨🇳", "🇯🇵", "e\u{301}", "\r\n"]);
+        assert!(grapheme_boundaries("").is_empty());
+
+        // 表情符号之间可以断行，但不拆开序列、肤色修饰和国旗
+        assert_eq!(offsets("😀😀"), vec![4, 8]);
+        assert_eq!(offsets("👍🏽👍🏽"), vec![8, 16]);
+        assert_eq!(offsets("🇨🇳🇯🇵"), vec![8, 16]);
+        assert_eq!(offsets("👨‍👩‍👧"), vec!["👨‍👩‍👧".len()]);
+    }
+
+    /// 测试泰文的字素簇和断行
+    #[test]
+    fn test_thai() {
+        let clusters: Vec<&str> = graphemes("สวัสดีครับ").collect();
+        assert_eq!(clusters, vec!["ส", "วั", "ส", "ดี", "ค", "รั", "บ"]);
+        // 间距元音与辅音属于同一个字素簇
+        assert_eq!(graphemes("กำ").count(), 1);
+        // 泰文词之间没有空格，只在空格处断行
+        assert_eq!(offsets("สวัสดี ครับ"), vec!["สวัสดี ".len(), "สวัสดี ครับ".len()]);
+    }
+
+    /// 测试谚文音节和字母组合
+    #[test]
+    fn test_korean() {
+        assert_eq!(graphemes("한국어").count(), 3);
+        assert_eq!(graphemes("\u{1100}\u{1161}\u{11A8}").count(), 1);
+        assert_eq!(offsets("이것은 한국어"), vec![3, 6, 10, 13, 16, 19]);
+    }
+
+    /// 测试印度系文字的辅音簇
+    #[test]
+    fn test_indic_conjuncts() {
+        assert_eq!(graphemes("क्षि").count(), 1);
+        let clusters: Vec<&str> = graphemes("नमस्ते").collect();
+        assert_eq!(clusters, vec!["न", "म", "स्ते"]);
+    }
+
+    /// 测试多种文字混排的断行机会
+    #[test]
+    fn test_mixed_scripts() {
+        assert_eq!(offsets("Hello, 世界！It's 2024年"), vec![7, 10, 16, 21, 25, 28]);
+        // 引号不与所引的文字分开
+        assert_eq!(offsets("他说“你好”。"), vec![3, 6, 12, 21]);
+        // 数字和标点保持在一起
+        assert_eq!(offsets("$100.00 (US)"), vec![8, 12]);
+
+        let opportunities = line_break_opportunities("a\nb");
+        assert_eq!(opportunities, vec![
+            BreakOpportunity { offset: 2, mandatory: true },
+            BreakOpportunity { offset: 3, mandatory: true },
+        ]);
+    }
+}
@@ -0,0 +1,255 @@
+//! 分句
+//!
+//! 把内容块的文本切分为句子，供朗读高亮、复制句子和逐句批注使用。
+//! 支持中文和中英混排文本，返回的范围始终对应内容块的原始文本
+
+use crate::document::ContentBlock;
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// 默认的英文缩写，其后的句点不表示句子结束
+const DEFAULT_ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "vs", "etc", "e.g", "i.e",
+    "no", "fig", "vol", "ch", "p", "pp", "inc", "ltd", "co", "jan", "feb", "mar", "apr",
+    "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec",
+];
+
+/// 句子
+///
+/// 同时记录句子在文本中的字节范围和字符范围，句子两端的空白不计入范围
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sentence {
+    /// 字节范围，可直接用于切片
+    pub byte_range: Range<usize>,
+    /// 字符（Unicode标量值）范围
+    pub char_range: Range<usize>,
+}
+
+impl Sentence {
+    /// 获取句子文本
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 分句时使用的文本
+    ///
+    /// # Returns
+    ///
+    /// 返回句子对应的文本切片
+    pub fn text<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.byte_range.clone()]
+    }
+}
+
+/// 分句器
+///
+/// 以 `。！？` 等句末标点、句末省略号和换行作为句子边界，句末标点后的右引号和右括号归入前一句。
+/// 英文句点会排除缩写、姓名首字母和小数
+#[derive(Debug, Clone)]
+pub struct SentenceSegmenter {
+    /// 小写的英文缩写（不含末尾句点）
+    abbreviations: HashSet<String>,
+}
+
+impl SentenceSegmenter {
+    /// 创建一个使用默认缩写表的分句器
+    ///
+    /// # Returns
+    ///
+    /// 返回一个新的SentenceSegmenter实例
+    pub fn new() -> Self {
+        SentenceSegmenter {
+            abbreviations: DEFAULT_ABBREVIATIONS.iter().map(|abbreviation| abbreviation.to_string()).collect(),
+        }
+    }
+
+    /// 添加英文缩写
+    ///
+    /// # Arguments
+    ///
+    /// * `abbreviation` - 缩写，如 `Gen.`，不区分大小写，末尾句点可省略
+    pub fn add_abbreviation(&mut self, abbreviation: &str) {
+        self.abbreviations.insert(abbreviation.trim_end_matches('.').to_lowercase());
+    }
+
+    /// 把文本切分为句子
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 需要分句的文本
+    ///
+    /// # Returns
+    ///
+    /// 返回按顺序排列的句子，空白文本返回空列表
+    pub fn segment(&self, text: &str) -> Vec<Sentence> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let len = chars.len();
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+
+        while i < len {
+            let c = chars[i].1;
+            if c == '\n' {
+                push_sentence(&mut sentences, &chars, text.len(), start, i);
+                start = i + 1;
+                i += 1;
+                continue;
+            }
+
+            let end = if is_terminal(c) {
+                // 连续的句末标点和省略号，如 `？！`、`。……`
+                let mut end = i + 1;
+                while end < len && (is_terminal(chars[end].1) || is_ellipsis_at(&chars, end)) {
+                    end += 1;
+                }
+                Some(end)
+            } else if is_ellipsis_at(&chars, i) {
+                let mut end = i;
+                while end < len && is_ellipsis_at(&chars, end) {
+                    end += 1;
+                }
+                if self.ellipsis_ends_sentence(&chars, end) {
+                    Some(end)
+                } else {
+                    i = end;
+                    continue;
+                }
+            } else if c == '.' && self.period_ends_sentence(&chars, i) {
+                Some(i + 1)
+            } else {
+                None
+            };
+
+            match end {
+                Some(mut end) => {
+                    // 句末标点后的右引号、右括号属于当前句
+                    while end < len && is_closing(chars[end].1) {
+                        end += 1;
+                    }
+                    push_sentence(&mut sentences, &chars, text.len(), start, end);
+                    start = end;
+                    i = end;
+                }
+                None => i += 1,
+            }
+        }
+        push_sentence(&mut sentences, &chars, text.len(), start, len);
+
+        sentences
+    }
+
+    /// 把内容块的文本切分为句子
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - 内容块
+    ///
+    /// # Returns
+    ///
+    /// 返回相对于内容块文本的句子范围
+    pub fn segment_block(&self, block: &ContentBlock) -> Vec<Sentence> {
+        self.segment(&block.content)
+    }
+
+    /// 判断省略号是否结束句子
+    ///
+    /// 省略号位于文本或行的末尾、后接右引号，或者在英文中后接空白和大写字母时结束句子；
+    /// 后面直接跟随正文时只表示停顿
+    fn ellipsis_ends_sentence(&self, chars: &[(usize, char)], end: usize) -> bool {
+        match chars.get(end).map(|&(_, c)| c) {
+            None | Some('\n') => true,
+            Some(c) if is_closing(c) => true,
+            Some(c) if c.is_whitespace() => next_visible(chars, end).is_none_or(|c| c.is_uppercase() || !c.is_ascii()),
+            _ => false,
+        }
+    }
+
+    /// 判断英文句点是否结束句子
+    fn period_ends_sentence(&self, chars: &[(usize, char)], index: usize) -> bool {
+        // 句点后必须是空白、右引号或文本末尾，排除 `3.14`、`example.com`
+        match chars.get(index + 1).map(|&(_, c)| c) {
+            None => {}
+            Some(c) if c.is_whitespace() || is_closing(c) => {}
+            _ => return false,
+        }
+
+        // 句点前的单词，包含缩写内部的句点，如 `e.g`
+        let word_start = chars[..index]
+            .iter()
+            .rposition(|&(_, c)| !(c.is_ascii_alphanumeric() || c == '.'))
+            .map_or(0, |position| position + 1);
+        let word: String = chars[word_start..index].iter().map(|&(_, c)| c).collect();
+
+        // 单个大写字母通常是姓名首字母，如 `J. K. Rowling`
+        let mut word_chars = word.chars();
+        if let (Some(first), None) = (word_chars.next(), word_chars.next()) {
+            if first.is_ascii_uppercase() {
+                return false;
+            }
+        }
+        if self.abbreviations.contains(&word.to_lowercase()) {
+            return false;
+        }
+
+        // 后面接小写字母时不是句子边界
+        !next_visible(chars, index + 1).is_some_and(|c| c.is_lowercase())
+    }
+}
+
+impl Default for SentenceSegmenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 去除首尾空白后添加句子
+fn push_sentence(sentences: &mut Vec<Sentence>, chars: &[(usize, char)], text_len: usize, mut start: usize, mut end: usize) {
+    while start < end && chars[start].1.is_whitespace() {
+        start += 1;
+    }
+    while end > start && chars[end - 1].1.is_whitespace() {
+        end -= 1;
+    }
+    if start == end {
+        return;
+    }
+
+    let byte_end = chars.get(end).map_or(text_len, |&(offset, _)| offset);
+    sentences.push(Sentence {
+        byte_range: chars[start].0..byte_end,
+        char_range: start..end,
+    });
+}
+
+/// 获取指定位置之后的第一个非空白字符，遇到换行时停止
+fn next_visible(chars: &[(usize, char)], from: usize) -> Option<char> {
+    chars[from..]
+        .iter()
+        .map(|&(_, c)| c)
+        .find(|c| !c.is_whitespace() || *c == '\n')
+        .filter(|&c| c != '\n')
+}
+
+/// 判断字符是否为句末标点
+fn is_terminal(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '!' | '?' | '｡' | '‼' | '⁇' | '⁈' | '⁉')
+}
+
+/// 判断指定位置是否为省略号的一部分，包括 `…` 和连续三个以上的英文句点
+fn is_ellipsis_at(chars: &[(usize, char)], index: usize) -> bool {
+    match chars[index].1 {
+        '…' | '⋯' => true,
+        '.' => {
+            // 所在的连续句点至少有三个
+            let before = chars[..index].iter().rev().take_while(|&&(_, c)| c == '.').count();
+            let after = chars[index + 1..].iter().take_while(|&&(_, c)| c == '.').count();
+            before + after >= 2
+        }
+        _ => false,
+    }
+}
+
+/// 判断字符是否为右引号或右括号
+fn is_closing(c: char) -> bool {
+    matches!(c, '”' | '’' | '」' | '』' | '）' | ')' | '】' | '》' | '〉' | '"' | '\'' | ']' | '］' | '〕')
+}
@@ -0,0 +1,85 @@
+//! 分句单元测试
+//! 
+//! 测试分句器对中文、英文和中英混排文本的处理
+
+#[cfg(test)]
+mod tests {
+    use crate::sentence::*;
+
+    /// 分句并返回句子文本
+    fn sentences(text: &str) -> Vec<&str> {
+        SentenceSegmenter::new()
+            .segment(text)
+            .iter()
+            .map(|sentence| sentence.text(text))
+            .collect()
+    }
+
+    /// 测试中文句末标点
+    #[test]
+    fn test_chinese_sentences() {
+        assert_eq!(
+            sentences("天色已晚。他还没回来！怎么办？？"),
+            vec!["天色已晚。", "他还没回来！", "怎么办？？"]
+        );
+        // 没有句末标点的结尾也是一句
+        assert_eq!(sentences("第一句。第二句"), vec!["第一句。", "第二句"]);
+        assert!(sentences("").is_empty());
+        assert!(sentences("  \n ").is_empty());
+    }
+
+    /// 测试句末标点后的右引号
+    #[test]
+    fn test_closing_quotes() {
+        assert_eq!(
+            sentences("他说：“我走了。”她没有回答。「真的？」"),
+            vec!["他说：“我走了。”", "她没有回答。", "「真的？」"]
+        );
+        assert_eq!(sentences("“你……”他愣住了。"), vec!["“你……”", "他愣住了。"]);
+    }
+
+    /// 测试省略号
+    #[test]
+    fn test_ellipsis() {
+        // 省略号后直接跟随正文时只是停顿
+        assert_eq!(sentences("我……我不知道。"), vec!["我……我不知道。"]);
+        assert_eq!(sentences("他走了……\n天黑了。"), vec!["他走了……", "天黑了。"]);
+        assert_eq!(sentences("算了……。走吧。"), vec!["算了……。", "走吧。"]);
+        assert_eq!(sentences("Wait... what? Then... Nothing."), vec!["Wait... what?", "Then...", "Nothing."]);
+    }
+
+    /// 测试英文缩写、首字母和小数
+    #[test]
+    fn test_latin_abbreviations() {
+        assert_eq!(
+            sentences("Mr. Smith paid $3.50 for it. J. K. Rowling agreed, e.g. yesterday. Done"),
+            vec!["Mr. Smith paid $3.50 for it.", "J. K. Rowling agreed, e.g. yesterday.", "Done"]
+        );
+        
+        let mut segmenter = SentenceSegmenter::new();
+        let text = "Gen. Lee arrived.";
+        assert_eq!(segmenter.segment(text).len(), 2);
+        segmenter.add_abbreviation("Gen.");
+        assert_eq!(segmenter.segment(text).len(), 1);
+    }
+
+    /// 测试中英混排文本的字节和字符范围
+    #[test]
+    fn test_ranges() {
+        let text = "  他读了《Dune》。It was good.  好！";
+        let result = SentenceSegmenter::new().segment(text);
+        
+        assert_eq!(result.len(), 3);
+        for sentence in &result {
+            let by_chars: String = text
+                .chars()
+                .skip(sentence.char_range.start)
+                .take(sentence.char_range.len())
+                .collect();
+            assert_eq!(by_chars, sentence.text(text));
+        }
+        assert_eq!(result[0].char_range, 2..12);
+        assert_eq!(result[1].text(text), "It was good.");
+        assert_eq!(result[2].byte_range.end, text.len());
+    }
+}
@@ -0,0 +1,174 @@
+//! 目录识别
+//!
+//! 识别txt小说开头的目录部分（如 `目录` 之后列出的全部章节标题），
+//! 使解析时可以跳过这些标题行，并可用目录校验正文中解析出的章节
+
+use crate::document::Chapter;
+use crate::parser::{chapter_label, chapter_number, heading_title, is_chapter_heading};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ops::Range;
+use regex::Regex;
+
+/// 没有 `目录` 标题时，至少需要的目录条目数
+const MIN_UNLABELED_ENTRIES: usize = 3;
+
+lazy_static::lazy_static! {
+    /// 目录标题行，如 `目录`、`目 录`、`目次：`
+    static ref TOC_HEADER_REGEX: Regex = Regex::new(r"^(?:目\s*录|目\s*次|章节目录)\s*[：:]?$").unwrap();
+    /// 目录中的分卷标题行，如 `第一卷 潜龙`
+    static ref VOLUME_REGEX: Regex = Regex::new(r"^第[零〇一二两三四五六七八九十百千万廿卅壹贰叁肆伍陆柒捌玖拾佰仟兩貳參陸萬\d]+[卷部集篇]").unwrap();
+}
+
+/// 目录
+///
+/// 从文本开头识别出的目录部分
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableOfContents {
+    /// 目录中列出的章节标题
+    pub entries: Vec<Cow<'static, str>>,
+    /// 目录在原文中占据的行范围（包含 `目录` 标题行）
+    pub line_range: Range<usize>,
+}
+
+/// 目录校验结果
+///
+/// 章节按序号比较，没有章节序号的标题按去除空白后的全文比较
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TocVerification {
+    /// 目录中列出但正文中没有找到的章节
+    pub missing: Vec<String>,
+    /// 正文中出现但目录中没有列出的章节
+    pub unlisted: Vec<String>,
+}
+
+impl TocVerification {
+    /// 判断目录与正文章节是否一致
+    ///
+    /// # Returns
+    ///
+    /// 没有缺失或多出的章节时返回true
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.unlisted.is_empty()
+    }
+}
+
+impl TableOfContents {
+    /// 从文本中识别开头的目录
+    ///
+    /// 目录是第一个章节标题开始的一串连续标题行（中间只允许空行和分卷标题），
+    /// 在正文第一章的标题再次出现处结束。有 `目录` 标题时，目录后直接跟随正文也可以识别，
+    /// 此时最后一个标题属于正文
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - 需要识别的文本内容
+    ///
+    /// # Returns
+    ///
+    /// 如果文本开头包含目录则返回Some，否则返回None
+    pub fn detect(content: &str) -> Option<Self> {
+        let lines: Vec<&str> = content.lines().collect();
+        detect_toc(&lines)
+    }
+
+    /// 用目录校验解析出的章节
+    ///
+    /// 标题为默认值 `全文` 的章节（目录之前的前言等内容）和整章公告不参与校验
+    ///
+    /// # Arguments
+    ///
+    /// * `chapters` - 解析出的章节列表
+    ///
+    /// # Returns
+    ///
+    /// 返回校验结果
+    pub fn verify(&self, chapters: &[Chapter]) -> TocVerification {
+        let listed: HashSet<String> = self.entries.iter().map(|entry| entry_key(entry)).collect();
+        let chapters: Vec<&Chapter> = chapters.iter().filter(|chapter| chapter.title != "全文" && !chapter.author_note).collect();
+        let found: HashSet<String> = chapters.iter().map(|chapter| entry_key(&chapter.title)).collect();
+
+        TocVerification {
+            missing: self.entries
+                .iter()
+                .filter(|entry| !found.contains(&entry_key(entry)))
+                .map(|entry| entry.to_string())
+                .collect(),
+            unlisted: chapters
+                .iter()
+                .filter(|chapter| !listed.contains(&entry_key(&chapter.title)))
+                .map(|chapter| chapter.title.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// 从按行分割的文本中识别开头的目录
+///
+/// # Arguments
+///
+/// * `lines` - 按行分割的文本内容
+///
+/// # Returns
+///
+/// 如果文本开头包含目录则返回Some，否则返回None
+pub(crate) fn detect_toc(lines: &[&str]) -> Option<TableOfContents> {
+    let first_heading = lines.iter().position(|line| is_chapter_heading(line))?;
+
+    // 第一个标题之前最近的非空行是否为目录标题
+    let header = lines[..first_heading]
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .filter(|&index| TOC_HEADER_REGEX.is_match(lines[index].trim()));
+
+    let mut entries: Vec<(usize, Cow<'static, str>)> = Vec::new();
+    let mut end = None;
+    for (index, line) in lines.iter().enumerate().skip(first_heading) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || VOLUME_REGEX.is_match(trimmed) {
+            continue;
+        }
+        if !is_chapter_heading(line) {
+            // 目录后直接跟随正文：最后一个标题是正文第一章，只有带目录标题时才认为前面是目录
+            if header.is_some() && entries.len() > 1 {
+                let (line_index, _) = entries.pop().unwrap();
+                end = Some(line_index);
+            }
+            break;
+        }
+
+        let title = heading_title(line);
+        if let Some((_, first)) = entries.first() {
+            if entry_key(first) == entry_key(&title) {
+                // 第一章的标题再次出现，正文从这里开始
+                end = Some(index);
+                break;
+            }
+        }
+        entries.push((index, title));
+    }
+
+    let end = end?;
+    if header.is_none() && entries.len() < MIN_UNLABELED_ENTRIES {
+        return None;
+    }
+
+    Some(TableOfContents {
+        entries: entries.into_iter().map(|(_, title)| title).collect(),
+        line_range: header.unwrap_or(first_heading)..end,
+    })
+}
+
+/// 生成用于比较的章节键
+///
+/// 优先使用章节序号，其次是 `第N章` 部分，目录中的标题常带有页码、省略了副标题或使用不同的数字写法
+fn entry_key(title: &str) -> String {
+    let trimmed = title.trim();
+    if let Some(number) = chapter_number(trimmed) {
+        return format!("#{}", number);
+    }
+    match chapter_label(trimmed) {
+        Some(label) => label.to_string(),
+        None => trimmed.chars().filter(|c| !c.is_whitespace()).collect(),
+    }
+}
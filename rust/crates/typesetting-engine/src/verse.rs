@@ -0,0 +1,230 @@
+//! 诗词识别
+//!
+//! 识别正文中引用的诗词、对联和口诀，把它们从普通段落中拆分出来，
+//! 作为保留换行的诗词块
+
+use crate::document::{Chapter, ContentBlock, ContentBlockType};
+use std::borrow::Cow;
+use std::mem;
+use std::ops::{Range, RangeInclusive};
+
+/// 诗句中每一小句允许的字数
+const CLAUSE_LENGTH: RangeInclusive<usize> = 4..=9;
+
+/// 带标点的诗词至少需要的行数，少于该数量时只有不带标点的对联才会被识别
+const MIN_LINES: usize = 4;
+
+/// 句末标点，只能出现在诗句的行尾
+const SENTENCE_END: [char; 6] = ['。', '！', '？', '.', '!', '?'];
+
+/// 一行诗句的结构
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct VerseLine {
+    /// 每一小句的字数
+    length: usize,
+    /// 小句数
+    clauses: usize,
+    /// 是否带标点
+    punctuated: bool,
+    /// 是否以句末标点结尾
+    sentence_end: bool,
+}
+
+/// 识别章节中的诗词
+///
+/// 连续四行以上、字数和小句数都相同且符合联句标点的短句，以及不带标点的对联，会被拆分为独立的诗词块，其余行保持为普通文本块。
+/// 每行单独成段的文本中，连续的单行段落会被合并后一起识别
+///
+/// # Arguments
+///
+/// * `chapter` - 需要处理的章节
+pub(crate) fn detect_verse(chapter: &mut Chapter) {
+    let mut content = Vec::with_capacity(chapter.content.len());
+    let mut single_lines: Vec<ContentBlock> = Vec::new();
+
+    for block in chapter.content.drain(..) {
+        if block.block_type == ContentBlockType::Text && !block.content.contains('\n') {
+            single_lines.push(block);
+            continue;
+        }
+        content.extend(split_lines(mem::take(&mut single_lines)));
+        if block.block_type == ContentBlockType::Text {
+            content.extend(split_lines(vec![block]));
+        } else {
+            content.push(block);
+        }
+    }
+    content.extend(split_lines(single_lines));
+
+    chapter.content = content;
+}
+
+/// 把若干文本块中的诗词行拆分为诗词块
+///
+/// # Arguments
+///
+/// * `blocks` - 连续的单行文本块，或者一个多行文本块
+///
+/// # Returns
+///
+/// 返回拆分后的内容块，没有诗词时原样返回
+fn split_lines(blocks: Vec<ContentBlock>) -> Vec<ContentBlock> {
+    // 每一行都记录来源块，不属于诗词的行按来源块还原
+    let lines: Vec<(usize, &str)> = blocks
+        .iter()
+        .enumerate()
+        .flat_map(|(index, block)| block.content.split('\n').map(move |line| (index, line)))
+        .collect();
+    let texts: Vec<&str> = lines.iter().map(|&(_, line)| line).collect();
+    let runs = verse_runs(&texts);
+    if runs.is_empty() {
+        return blocks;
+    }
+
+    let mut result = Vec::new();
+    let push_text = |range: Range<usize>, result: &mut Vec<ContentBlock>| {
+        let mut start = range.start;
+        while start < range.end {
+            let source = lines[start].0;
+            let end = (start..range.end).find(|&index| lines[index].0 != source).unwrap_or(range.end);
+            result.push(ContentBlock {
+                content: Cow::Owned(texts[start..end].join("\n")),
+                ..blocks[source].clone()
+            });
+            start = end;
+        }
+    };
+
+    let mut last_end = 0;
+    for (start, end) in runs {
+        push_text(last_end..start, &mut result);
+        let verse: Vec<&str> = texts[start..end].iter().map(|line| line.trim()).collect();
+        result.push(ContentBlock {
+            block_type: ContentBlockType::Verse,
+            content: Cow::Owned(verse.join("\n")),
+            styles: blocks[lines[start].0].styles.clone(),
+            metrics: None,
+            language: None,
+        });
+        last_end = end;
+    }
+    push_text(last_end..lines.len(), &mut result);
+
+    result
+}
+
+/// 找出段落中属于诗词的连续行
+///
+/// 诗词由字数和小句数都相同的连续行组成，带标点时至少需要四行，并且单句成行时
+/// 句末标点只能出现在每一联的下句，避免把每句都以句号结尾的短句叙述当作诗词
+///
+/// # Arguments
+///
+/// * `lines` - 段落中的各行
+///
+/// # Returns
+///
+/// 返回按顺序排列的 `(起始行, 结束行)` 区间，结束行不包含在内
+fn verse_runs(lines: &[&str]) -> Vec<(usize, usize)> {
+    let parsed: Vec<Option<VerseLine>> = lines.iter().map(|line| verse_line(line)).collect();
+    let mut runs = Vec::new();
+    let mut start = 0;
+
+    while start < lines.len() {
+        let Some(first) = parsed[start] else {
+            start += 1;
+            continue;
+        };
+
+        // 向后延伸到结构不同的行为止
+        let mut end = start + 1;
+        while end < lines.len()
+            && matches!(parsed[end], Some(next) if next.length == first.length && next.clauses == first.clauses)
+        {
+            end += 1;
+        }
+
+        let run: Vec<VerseLine> = parsed[start..end].iter().flatten().copied().collect();
+        let couplet = run.len() == 2 && run.iter().all(|line| !line.punctuated);
+        if couplet || (run.len() >= MIN_LINES && parallel(&run)) {
+            runs.push((start, end));
+        }
+        start = end;
+    }
+
+    runs
+}
+
+/// 判断连续的诗句是否构成对仗的联句
+///
+/// 单句成行时上句不能以句末标点结尾，多句成行时每行都要以句末标点结尾或都不带标点
+///
+/// # Arguments
+///
+/// * `run` - 结构相同的连续诗句
+///
+/// # Returns
+///
+/// 标点符合诗词的联句结构时返回true
+fn parallel(run: &[VerseLine]) -> bool {
+    if run[0].clauses == 1 {
+        run.iter().step_by(2).all(|line| !line.sentence_end)
+    } else {
+        run.iter().all(|line| line.sentence_end) || run.iter().all(|line| !line.punctuated)
+    }
+}
+
+/// 分析一行是否可能是诗句
+///
+/// # Arguments
+///
+/// * `line` - 一行文本
+///
+/// # Returns
+///
+/// 行中只有汉字和标点、每个小句字数相同且句末标点只出现在行尾时，返回该行的结构
+fn verse_line(line: &str) -> Option<VerseLine> {
+    let mut lengths = Vec::new();
+    let mut current = 0;
+    let mut punctuated = false;
+    let mut sentence_end = false;
+
+    for c in line.trim().chars() {
+        if is_han(c) {
+            if sentence_end {
+                // 行中出现句末标点说明这是多个完整的句子
+                return None;
+            }
+            current += 1;
+        } else if matches!(c, '，' | '。' | '！' | '？' | '；' | '、' | '：' | ',' | '.' | '!' | '?' | ';') || c.is_whitespace() {
+            punctuated |= !c.is_whitespace();
+            sentence_end |= SENTENCE_END.contains(&c);
+            if current > 0 {
+                lengths.push(current);
+                current = 0;
+            }
+        } else {
+            // 引号、括号、数字等说明这是对话或普通叙述
+            return None;
+        }
+    }
+    if current > 0 {
+        lengths.push(current);
+    }
+
+    let first = *lengths.first()?;
+    if !CLAUSE_LENGTH.contains(&first) || lengths.iter().any(|&length| length != first) {
+        return None;
+    }
+    Some(VerseLine {
+        length: first,
+        clauses: lengths.len(),
+        punctuated,
+        sentence_end,
+    })
+}
+
+/// 判断字符是否为汉字
+pub(crate) fn is_han(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{20000}'..='\u{2A6DF}' | '\u{F900}'..='\u{FAFF}')
+}
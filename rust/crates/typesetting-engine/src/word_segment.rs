@@ -0,0 +1,586 @@
+//! 中文分词
+//!
+//! 与 jieba 相同的算法：先用前缀词典为每个汉字片段构建有向无环图，按词频求出概率最大的切分路径，
+//! 再用隐马尔可夫模型切分词典中没有的连续单字（如人名）。打包的 `data/jieba/dict.txt` 和
+//! `data/jieba/hmm.model` 是 jieba 原版的词典和模型（MIT许可，见 `data/jieba/LICENSE`），
+//! 并支持为每本书加载人名、地名等用户词典
+
+use crate::document::ContentBlock;
+use crate::verse::is_han;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// 词典中没有的词使用的词频
+const UNKNOWN_FREQ: u64 = 1;
+
+/// 表示不可能的对数概率
+const MIN_LOG: f64 = -3.14e100;
+
+/// 隐马尔可夫模型的状态：词首、词中、词尾、单字词
+const B: usize = 0;
+const M: usize = 1;
+const E: usize = 2;
+const S: usize = 3;
+
+/// 初始状态的对数概率
+const START_PROB: [f64; 4] = [-0.262_686_608_092_500_2, MIN_LOG, MIN_LOG, -1.465_263_339_853_767_8];
+
+/// 状态转移的对数概率，`TRANS_PROB[from][to]`
+const TRANS_PROB: [[f64; 4]; 4] = [
+    [MIN_LOG, -0.916_290_731_874_155, -0.510_825_623_765_99, MIN_LOG],
+    [MIN_LOG, -1.260_362_382_026_822_6, -0.333_448_568_119_485_14, MIN_LOG],
+    [-0.589_714_973_685_451_3, MIN_LOG, MIN_LOG, -0.808_525_047_466_993_7],
+    [-0.721_196_565_466_984_1, MIN_LOG, MIN_LOG, -0.665_863_144_879_821_2],
+];
+
+/// 每个状态可能的前一个状态
+const PREV_STATES: [[usize; 2]; 4] = [[E, S], [M, B], [B, M], [S, E]];
+
+/// 词的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordKind {
+    /// 汉字词
+    Cjk,
+    /// 字母或数字组成的词，如 `Rust`、`3.14`
+    Alphanumeric,
+    /// 连续的空白
+    Whitespace,
+    /// 标点和其他符号，每个字符单独成词
+    Punctuation,
+}
+
+/// 词
+///
+/// 同时记录词在文本中的字节范围和字符范围
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    /// 词的类别
+    pub kind: WordKind,
+    /// 字节范围，可直接用于切片
+    pub byte_range: Range<usize>,
+    /// 字符（Unicode标量值）范围
+    pub char_range: Range<usize>,
+}
+
+impl Word {
+    /// 获取词的文本
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 分词时使用的文本
+    ///
+    /// # Returns
+    ///
+    /// 返回词对应的文本切片
+    pub fn text<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.byte_range.clone()]
+    }
+}
+
+/// 分词词典
+///
+/// 词条为“词 → 词频”的映射，同时记录所有词的前缀以便构建有向无环图
+#[derive(Debug, Clone, Default)]
+pub struct WordDictionary {
+    /// 词频
+    entries: HashMap<String, u64>,
+    /// 所有词条的真前缀
+    prefixes: HashSet<String>,
+    /// 词频总和
+    total: u64,
+}
+
+impl WordDictionary {
+    /// 创建一个空词典
+    ///
+    /// # Returns
+    ///
+    /// 返回一个新的WordDictionary实例
+    pub fn new() -> Self {
+        WordDictionary::default()
+    }
+
+    /// 解析 jieba 文本格式的词典
+    ///
+    /// 每行一个词条，格式为 `词 词频 词性`，词频和词性可以省略，省略词频时使用1
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 词典文本
+    ///
+    /// # Returns
+    ///
+    /// 返回解析后的词典
+    pub fn from_jieba(text: &str) -> Self {
+        let mut dictionary = WordDictionary::new();
+        for (word, freq) in jieba_entries(text) {
+            dictionary.insert(word, freq.unwrap_or(UNKNOWN_FREQ));
+        }
+        dictionary
+    }
+
+    /// 添加或覆盖一个词条
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - 词
+    /// * `freq` - 词频
+    pub fn insert(&mut self, word: &str, freq: u64) {
+        if word.is_empty() {
+            return;
+        }
+        for (index, _) in word.char_indices().skip(1) {
+            self.prefixes.insert(word[..index].to_string());
+        }
+        let old = self.entries.insert(word.to_string(), freq).unwrap_or(0);
+        self.total = self.total - old + freq;
+    }
+
+    /// 查询词频
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - 词
+    ///
+    /// # Returns
+    ///
+    /// 如果存在该词条则返回词频
+    pub fn freq(&self, word: &str) -> Option<u64> {
+        self.entries.get(word).copied()
+    }
+
+    /// 获取词频总和
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// 获取词条数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 判断词典是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 判断文本是否为某个词条或词条的前缀
+    fn is_prefix(&self, text: &str) -> bool {
+        self.entries.contains_key(text) || self.prefixes.contains(text)
+    }
+}
+
+/// 遍历 jieba 词典文本中的词条
+fn jieba_entries(text: &str) -> impl Iterator<Item = (&str, Option<u64>)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let word = parts.next()?;
+            Some((word, parts.next().and_then(|freq| freq.parse().ok())))
+        })
+}
+
+lazy_static::lazy_static! {
+    static ref DEFAULT_DICTIONARY: Arc<WordDictionary> =
+        Arc::new(WordDictionary::from_jieba(include_str!("../data/jieba/dict.txt")));
+    /// 各状态的发射概率
+    static ref EMIT_PROB: [HashMap<char, f64>; 4] = hmm_emit_prob(include_str!("../data/jieba/hmm.model"));
+}
+
+/// 读取 jieba 模型文件中的发射概率
+///
+/// `#prob_emit` 之后依次为状态行（如 `#B`）和该状态的概率行 `字:对数概率,字:对数概率,...`
+fn hmm_emit_prob(model: &str) -> [HashMap<char, f64>; 4] {
+    let mut tables: [HashMap<char, f64>; 4] = Default::default();
+    let mut state = None;
+    for line in model.lines().skip_while(|line| !line.starts_with("#prob_emit")).skip(1) {
+        if let Some(name) = line.strip_prefix('#') {
+            state = match name.trim() {
+                "B" => Some(B),
+                "M" => Some(M),
+                "E" => Some(E),
+                "S" => Some(S),
+                _ => None,
+            };
+            continue;
+        }
+        let Some(state) = state else {
+            continue;
+        };
+        for entry in line.split(',') {
+            if let Some((c, prob)) = entry.split_once(':') {
+                if let (Some(c), Ok(prob)) = (c.chars().next(), prob.parse()) {
+                    tables[state].insert(c, prob);
+                }
+            }
+        }
+    }
+    tables
+}
+
+/// 分词器
+///
+/// 默认使用内置词典并启用隐马尔可夫模型
+#[derive(Debug, Clone)]
+pub struct WordSegmenter {
+    /// 分词词典，添加用户词条时才复制内置词典
+    dictionary: Arc<WordDictionary>,
+    /// 是否用隐马尔可夫模型切分未登录词
+    hmm: bool,
+}
+
+impl WordSegmenter {
+    /// 创建一个使用内置词典的分词器
+    ///
+    /// # Returns
+    ///
+    /// 返回一个新的WordSegmenter实例
+    pub fn new() -> Self {
+        WordSegmenter {
+            dictionary: DEFAULT_DICTIONARY.clone(),
+            hmm: true,
+        }
+    }
+
+    /// 加载用户词典
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - jieba 格式的用户词典文本，适合为每本书补充人名、地名和功法名
+    ///
+    /// # Returns
+    ///
+    /// 返回加载了用户词典的分词器
+    pub fn with_user_dictionary(mut self, text: &str) -> Self {
+        for (word, freq) in jieba_entries(text) {
+            self.add_word(word, freq);
+        }
+        self
+    }
+
+    /// 设置是否启用隐马尔可夫模型
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - 关闭后词典中没有的连续单字会逐字切分
+    ///
+    /// # Returns
+    ///
+    /// 返回修改后的分词器
+    pub fn with_hmm(mut self, enabled: bool) -> Self {
+        self.hmm = enabled;
+        self
+    }
+
+    /// 添加用户词条
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - 词
+    /// * `freq` - 词频，为None时自动计算一个足以让该词被切分出来的词频
+    pub fn add_word(&mut self, word: &str, freq: Option<u64>) {
+        let freq = freq.unwrap_or_else(|| self.suggest_freq(word));
+        Arc::make_mut(&mut self.dictionary).insert(word, freq);
+    }
+
+    /// 把文本切分为词
+    ///
+    /// 返回的词首尾相接，覆盖整个文本
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 需要分词的文本
+    ///
+    /// # Returns
+    ///
+    /// 返回按顺序排列的词
+    pub fn segment(&self, text: &str) -> Vec<Word> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let mut words = Vec::new();
+        let mut start = 0;
+
+        while start < chars.len() {
+            let c = chars[start].1;
+            let (kind, end) = if is_han(c) {
+                (WordKind::Cjk, run_end(&chars, start, |_, c| is_han(c)))
+            } else if is_word_char(c) {
+                (WordKind::Alphanumeric, alphanumeric_end(&chars, start))
+            } else if c.is_whitespace() {
+                (WordKind::Whitespace, run_end(&chars, start, |_, c| c.is_whitespace()))
+            } else {
+                (WordKind::Punctuation, start + 1)
+            };
+
+            if kind == WordKind::Cjk {
+                for range in self.cut_han(text, &chars, start, end) {
+                    words.push(make_word(WordKind::Cjk, &chars, text.len(), range));
+                }
+            } else {
+                words.push(make_word(kind, &chars, text.len(), start..end));
+            }
+            start = end;
+        }
+
+        words
+    }
+
+    /// 把内容块的文本切分为词
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - 内容块
+    ///
+    /// # Returns
+    ///
+    /// 返回相对于内容块文本的词范围
+    pub fn segment_block(&self, block: &ContentBlock) -> Vec<Word> {
+        self.segment(&block.content)
+    }
+
+    /// 获取包含指定位置的词，用于双击选词
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 文本
+    /// * `byte_offset` - 字节位置
+    ///
+    /// # Returns
+    ///
+    /// 返回包含该位置的词，位置超出文本时返回None
+    pub fn word_at(&self, text: &str, byte_offset: usize) -> Option<Word> {
+        self.segment(text)
+            .into_iter()
+            .find(|word| word.byte_range.contains(&byte_offset))
+    }
+
+    /// 生成用于搜索索引的词
+    ///
+    /// 在分词结果的基础上，为较长的汉字词补充词典中存在的二字和三字子词，
+    /// 使搜索“仙族”也能命中“玄鉴仙族”。空白和标点不会出现在结果中
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 需要索引的文本
+    ///
+    /// # Returns
+    ///
+    /// 返回按起始位置排列的词，子词排在所属的词之前
+    pub fn search_terms(&self, text: &str) -> Vec<Word> {
+        let mut terms = Vec::new();
+        for word in self.segment(text) {
+            match word.kind {
+                WordKind::Whitespace | WordKind::Punctuation => continue,
+                WordKind::Alphanumeric => {
+                    terms.push(word);
+                    continue;
+                }
+                WordKind::Cjk => {}
+            }
+
+            let chars: Vec<(usize, char)> = word.text(text).char_indices().collect();
+            for gram in [2, 3] {
+                if chars.len() <= gram {
+                    continue;
+                }
+                for start in 0..=chars.len() - gram {
+                    let byte_start = chars[start].0;
+                    let byte_end = chars.get(start + gram).map_or(word.byte_range.len(), |&(offset, _)| offset);
+                    if self.dictionary.freq(&word.text(text)[byte_start..byte_end]).is_some() {
+                        terms.push(Word {
+                            kind: WordKind::Cjk,
+                            byte_range: word.byte_range.start + byte_start..word.byte_range.start + byte_end,
+                            char_range: word.char_range.start + start..word.char_range.start + start + gram,
+                        });
+                    }
+                }
+            }
+            terms.push(word);
+        }
+        terms
+    }
+
+    /// 切分一段连续的汉字
+    ///
+    /// # Returns
+    ///
+    /// 返回各个词的字符范围
+    fn cut_han(&self, text: &str, chars: &[(usize, char)], start: usize, end: usize) -> Vec<Range<usize>> {
+        let byte_at = |index: usize| chars.get(index).map_or(text.len(), |&(offset, _)| offset);
+        let slice = |from: usize, to: usize| &text[byte_at(from)..byte_at(to)];
+        let dictionary = &self.dictionary;
+        let log_total = (dictionary.total().max(1) as f64).ln();
+
+        // 有向无环图：从每个字开始、能在词典中找到的词的结束位置
+        let dag: Vec<Vec<usize>> = (start..end)
+            .map(|from| {
+                let mut ends = Vec::new();
+                let mut to = from + 1;
+                while to <= end && dictionary.is_prefix(slice(from, to)) {
+                    if dictionary.freq(slice(from, to)).is_some() {
+                        ends.push(to);
+                    }
+                    to += 1;
+                }
+                if ends.is_empty() {
+                    ends.push(from + 1);
+                }
+                ends
+            })
+            .collect();
+
+        // 从后向前求概率最大的切分路径
+        let len = end - start;
+        let mut route: Vec<(f64, usize)> = vec![(0.0, end); len + 1];
+        for from in (start..end).rev() {
+            route[from - start] = dag[from - start]
+                .iter()
+                .map(|&to| {
+                    let freq = dictionary.freq(slice(from, to)).unwrap_or(UNKNOWN_FREQ);
+                    ((freq.max(1) as f64).ln() - log_total + route[to - start].0, to)
+                })
+                .fold((f64::NEG_INFINITY, from + 1), |best, candidate| {
+                    if candidate.0 > best.0 || (candidate.0 == best.0 && candidate.1 > best.1) { candidate } else { best }
+                });
+        }
+
+        // 连续的单字交给隐马尔可夫模型处理
+        let mut words = Vec::new();
+        let mut pending = start;
+        let mut from = start;
+        while from < end {
+            let to = route[from - start].1;
+            if to - from > 1 {
+                self.flush_singles(slice, pending, from, &mut words);
+                words.push(from..to);
+                pending = to;
+            }
+            from = to;
+        }
+        self.flush_singles(slice, pending, end, &mut words);
+
+        words
+    }
+
+    /// 处理连续的单字
+    fn flush_singles<'a>(&self, slice: impl Fn(usize, usize) -> &'a str, start: usize, end: usize, words: &mut Vec<Range<usize>>) {
+        if end - start > 1 && self.hmm && self.dictionary.freq(slice(start, end)).is_none() {
+            let chars: Vec<char> = slice(start, end).chars().collect();
+            words.extend(viterbi(&chars).into_iter().map(|range| start + range.start..start + range.end));
+        } else {
+            words.extend((start..end).map(|index| index..index + 1));
+        }
+    }
+
+    /// 计算用户词条的建议词频
+    ///
+    /// 与 jieba 相同，取该词按当前词典切分后各部分概率之积，再加一，使该词成为更优的切分
+    fn suggest_freq(&self, word: &str) -> u64 {
+        let total = self.dictionary.total().max(1) as f64;
+        let segmenter = WordSegmenter {
+            dictionary: self.dictionary.clone(),
+            hmm: false,
+        };
+        let product = segmenter
+            .segment(word)
+            .iter()
+            .map(|part| self.dictionary.freq(part.text(word)).unwrap_or(UNKNOWN_FREQ) as f64 / total)
+            .product::<f64>();
+        let suggested = (product * total) as u64 + 1;
+        suggested.max(self.dictionary.freq(word).unwrap_or(UNKNOWN_FREQ))
+    }
+}
+
+impl Default for WordSegmenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 用维特比算法求出最可能的状态序列并切分
+///
+/// 与 jieba 相同，发射概率表中没有的字符在各状态下都取最小概率
+///
+/// # Returns
+///
+/// 返回相对于输入的各个词的字符范围
+fn viterbi(chars: &[char]) -> Vec<Range<usize>> {
+    let emit = |state: usize, c: char| EMIT_PROB[state].get(&c).copied().unwrap_or(MIN_LOG);
+
+    let mut prob: Vec<[f64; 4]> = Vec::with_capacity(chars.len());
+    let mut path: Vec<[usize; 4]> = Vec::with_capacity(chars.len());
+    prob.push([B, M, E, S].map(|state| START_PROB[state] + emit(state, chars[0])));
+    path.push([B, M, E, S]);
+
+    for &c in &chars[1..] {
+        let last = *prob.last().unwrap();
+        let mut current = [MIN_LOG; 4];
+        let mut previous = [0; 4];
+        for state in [B, M, E, S] {
+            let (best, from) = PREV_STATES[state]
+                .iter()
+                .map(|&from| (last[from] + TRANS_PROB[from][state], from))
+                .fold((f64::NEG_INFINITY, S), |best, candidate| if candidate.0 > best.0 { candidate } else { best });
+            current[state] = best + emit(state, c);
+            previous[state] = from;
+        }
+        prob.push(current);
+        path.push(previous);
+    }
+
+    // 最后一个字只能是词尾或单字词
+    let last = prob.last().unwrap();
+    let mut state = if last[E] >= last[S] { E } else { S };
+    let mut states = vec![S; chars.len()];
+    for index in (0..chars.len()).rev() {
+        states[index] = state;
+        state = path[index][state];
+    }
+
+    let mut words = Vec::new();
+    let mut begin = 0;
+    for (index, &state) in states.iter().enumerate() {
+        match state {
+            B => begin = index,
+            E => words.push(begin..index + 1),
+            S => words.push(index..index + 1),
+            _ => {}
+        }
+    }
+    words
+}
+
+/// 创建词
+fn make_word(kind: WordKind, chars: &[(usize, char)], text_len: usize, range: Range<usize>) -> Word {
+    let byte_end = chars.get(range.end).map_or(text_len, |&(offset, _)| offset);
+    Word {
+        kind,
+        byte_range: chars[range.start].0..byte_end,
+        char_range: range,
+    }
+}
+
+/// 找出从指定位置开始、满足条件的连续字符的结束位置
+fn run_end(chars: &[(usize, char)], start: usize, matches: impl Fn(usize, char) -> bool) -> usize {
+    (start..chars.len())
+        .find(|&index| !matches(index, chars[index].1))
+        .unwrap_or(chars.len())
+}
+
+/// 找出字母数字词的结束位置
+///
+/// 词内部的 `.`、`-`、`_`、`'` 不会把词断开，如 `3.14`、`e-mail`、`don't`
+fn alphanumeric_end(chars: &[(usize, char)], start: usize) -> usize {
+    run_end(chars, start, |index, c| {
+        is_word_char(c)
+            || (matches!(c, '.' | '-' | '_' | '\'')
+                && chars.get(index + 1).is_some_and(|&(_, next)| is_word_char(next)))
+    })
+}
+
+/// 判断字符是否属于字母数字词
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && !is_han(c)
+}
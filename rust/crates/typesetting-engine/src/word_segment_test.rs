@@ -0,0 +1,100 @@
+//! 中文分词单元测试
+//! 
+//! 测试词典分词、未登录词识别和用户词典
+
+#[cfg(test)]
+mod tests {
+    use crate::word_segment::*;
+
+    /// 分词并返回各个词的文本
+    fn words<'a>(segmenter: &WordSegmenter, text: &'a str) -> Vec<&'a str> {
+        segmenter.segment(text).iter().map(|word| word.text(text)).collect()
+    }
+
+    /// 测试基于词典的分词
+    #[test]
+    fn test_dictionary_segmentation() {
+        let segmenter = WordSegmenter::new();
+        assert_eq!(words(&segmenter, "我们已经知道了"), vec!["我们", "已经", "知道", "了"]);
+        assert_eq!(words(&segmenter, "我来到北京清华大学"), vec!["我", "来到", "北京", "清华大学"]);
+        assert!(segmenter.segment("").is_empty());
+    }
+
+    /// 测试中英混排和标点
+    #[test]
+    fn test_mixed_text() {
+        let segmenter = WordSegmenter::new();
+        let text = "他用Rust 1.75写了e-mail，花了3.5小时。";
+        let result = segmenter.segment(text);
+        
+        let texts: Vec<&str> = result.iter().map(|word| word.text(text)).collect();
+        assert_eq!(texts, vec!["他", "用", "Rust", " ", "1.75", "写", "了", "e-mail", "，", "花", "了", "3.5", "小时", "。"]);
+        assert_eq!(result[2].kind, WordKind::Alphanumeric);
+        assert_eq!(result[3].kind, WordKind::Whitespace);
+        assert_eq!(result[8].kind, WordKind::Punctuation);
+        
+        // 词首尾相接覆盖整个文本，字符范围与字节范围一致
+        assert_eq!(result.last().unwrap().byte_range.end, text.len());
+        for pair in result.windows(2) {
+            assert_eq!(pair[0].byte_range.end, pair[1].byte_range.start);
+            assert_eq!(pair[0].char_range.end, pair[1].char_range.start);
+        }
+        assert_eq!(result.last().unwrap().char_range.end, text.chars().count());
+    }
+
+    /// 测试隐马尔可夫模型识别未登录词
+    #[test]
+    fn test_hmm_unknown_words() {
+        let segmenter = WordSegmenter::new();
+        assert_eq!(words(&segmenter, "他来到了网易杭研大厦"), vec!["他", "来到", "了", "网易", "杭研", "大厦"]);
+        
+        // 关闭后逐字切分
+        let segmenter = WordSegmenter::new().with_hmm(false);
+        assert_eq!(words(&segmenter, "他来到了网易杭研大厦"), vec!["他", "来到", "了", "网易", "杭", "研", "大厦"]);
+    }
+
+    /// 测试小说段落中的常见多字词
+    #[test]
+    fn test_novel_paragraph() {
+        let segmenter = WordSegmenter::new();
+        let text = "陆江仙睁开眼睛，只见山下的村庄笼罩在一片薄雾之中。他深吸一口气，缓缓站起身来，\
+                    心中暗想：这一次无论如何也要突破筑基，不能再让家族失望了。";
+        let result = words(&segmenter, text);
+        for word in ["睁开眼睛", "只见", "村庄", "笼罩", "薄雾", "之中", "一口气", "缓缓", "心中", "暗想", "无论如何", "突破", "家族", "失望"] {
+            assert!(result.contains(&word), "{}", word);
+        }
+        // 大部分汉字都被切分进多字词
+        let han: usize = result.iter().filter(|word| word.chars().all(crate::verse::is_han)).map(|word| word.chars().count()).sum();
+        let multi: usize = result.iter().filter(|word| word.chars().count() > 1).map(|word| word.chars().count()).sum();
+        assert!(multi * 4 >= han * 3, "{:?}", result);
+    }
+
+    /// 测试用户词典
+    #[test]
+    fn test_user_dictionary() {
+        let segmenter = WordSegmenter::new();
+        assert_ne!(words(&segmenter, "季越人写了玄鉴仙族"), vec!["季越人", "写", "了", "玄鉴仙族"]);
+        
+        let segmenter = WordSegmenter::new().with_user_dictionary("# 人名\n季越人 nr\n玄鉴仙族 100 nz\n");
+        assert_eq!(words(&segmenter, "季越人写了玄鉴仙族"), vec!["季越人", "写", "了", "玄鉴仙族"]);
+        
+        let mut segmenter = WordSegmenter::new();
+        segmenter.add_word("青杜山", None);
+        assert_eq!(words(&segmenter, "回到青杜山"), vec!["回到", "青杜山"]);
+    }
+
+    /// 测试双击选词和搜索索引
+    #[test]
+    fn test_word_at_and_search_terms() {
+        let segmenter = WordSegmenter::new();
+        let text = "他已经知道了";
+        let word = segmenter.word_at(text, "他已".len()).unwrap();
+        assert_eq!(word.text(text), "已经");
+        assert!(segmenter.word_at(text, text.len()).is_none());
+        
+        let segmenter = WordSegmenter::new().with_user_dictionary("玄鉴仙族 1000\n仙族 500");
+        let text = "《玄鉴仙族》";
+        let terms: Vec<&str> = segmenter.search_terms(text).iter().map(|word| word.text(text)).collect();
+        assert_eq!(terms, vec!["仙族", "玄鉴仙族"]);
+    }
+}
@@ -0,0 +1,163 @@
+//! 解析器注册表测试
+//! 
+//! 测试自定义格式解析器的注册、查找和加载
+
+use typesetting_engine::prelude::*;
+use typesetting_engine::{
+    Chapter, ContentBlock, ContentBlockType, DocumentMetadata, DocumentModel, DocumentParser,
+    FileFormat, FileLoader, ParserRegistry, TextStyle,
+};
+
+use std::fs;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// 模拟下游crate的自定义格式：每行 `标题|内容` 表示一个章节
+struct PipeParser;
+
+impl PipeParser {
+    fn chapters(bytes: &[u8]) -> Vec<Chapter> {
+        let content = FileLoader::decode_text(bytes);
+        content
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once('|'))
+            .enumerate()
+            .map(|(i, (title, text))| Chapter {
+                id: format!("chapter_{}", i).into(),
+                title: title.to_string().into(),
+                number: None,
+                content: vec![ContentBlock {
+                    block_type: ContentBlockType::Text,
+                    content: text.to_string().into(),
+                    styles: TextStyle {
+                        font_size: 16.0,
+                        font_family: "Arial".into(),
+                        bold: false,
+                        italic: false,
+                    },
+                    metrics: None,
+                    language: None,
+                }],
+                footnote_refs: vec![],
+                author_note: false,
+            })
+            .collect()
+    }
+
+    fn document(chapters: Vec<Chapter>) -> DocumentModel {
+        DocumentModel {
+            metadata: DocumentMetadata {
+                title: "Pipe Document".into(),
+                author: "Unknown".into(),
+                created_at: "2025-01-01".into(),
+                language: None,
+            },
+            chapters,
+            styles: vec![],
+        }
+    }
+}
+
+impl DocumentParser for PipeParser {
+    fn name(&self) -> &str {
+        "Pipe"
+    }
+
+    fn formats(&self) -> Vec<FileFormat> {
+        vec![FileFormat::Other("pipe".to_string())]
+    }
+
+    fn mime_types(&self) -> Vec<&str> {
+        vec!["application/x-pipe"]
+    }
+
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"PIPE\n")
+    }
+
+    fn parse(&self, bytes: &[u8]) -> Result<DocumentModel> {
+        if !self.sniff(bytes) {
+            anyhow::bail!("不是Pipe格式");
+        }
+        Ok(Self::document(Self::chapters(bytes)))
+    }
+
+    fn parse_chapter(&self, bytes: &[u8], chapter_index: usize) -> Result<DocumentModel> {
+        let chapters = Self::chapters(bytes).into_iter().skip(chapter_index).take(1).collect();
+        Ok(Self::document(chapters))
+    }
+}
+
+fn registry_with_pipe() -> ParserRegistry {
+    let mut registry = ParserRegistry::with_defaults();
+    registry.register(Arc::new(PipeParser));
+    registry
+}
+
+#[test]
+fn test_default_registry() {
+    let registry = ParserRegistry::with_defaults();
+    
+    assert!(registry.parser_for_format(&FileFormat::Txt).is_some());
+    assert!(registry.parser_for_mime("text/plain; charset=utf-8").is_some());
+    assert!(registry.parser_for_format(&FileFormat::Other("epub".to_string())).is_none());
+    
+    let formats = registry.supported_formats();
+    assert_eq!(formats.len(), 1);
+    assert_eq!(formats[0].format.extension(), "txt");
+}
+
+#[test]
+fn test_lookup_custom_parser() {
+    let registry = registry_with_pipe();
+    
+    let by_format = registry.parser_for_format(&FileFormat::Other("pipe".to_string())).unwrap();
+    assert_eq!(by_format.name(), "Pipe");
+    
+    let by_mime = registry.parser_for_mime("Application/X-Pipe").unwrap();
+    assert_eq!(by_mime.name(), "Pipe");
+    
+    // 嗅探时自定义格式优先于纯文本
+    assert_eq!(registry.sniff(b"PIPE\nA|B").unwrap().name(), "Pipe");
+    assert_eq!(registry.sniff("第一章 开始".as_bytes()).unwrap().name(), "Plain Text");
+    
+    let extensions: Vec<String> = registry
+        .supported_formats()
+        .iter()
+        .map(|format| format.format.extension().to_string())
+        .collect();
+    assert_eq!(extensions, vec!["txt", "pipe"]);
+}
+
+#[test]
+fn test_parse_chapter_through_trait() {
+    let registry = registry_with_pipe();
+    let parser = registry.parser_for_format(&FileFormat::Other("pipe".to_string())).unwrap();
+    
+    let document = parser.parse_chapter(b"PIPE\nOne|first\nTwo|second", 1).unwrap();
+    assert_eq!(document.chapters.len(), 1);
+    assert_eq!(document.chapters[0].title, "Two");
+    
+    let txt = registry.parser_for_format(&FileFormat::Txt).unwrap();
+    let document = txt.parse_chapter("第一章 甲\n\n内容一\n\n第二章 乙\n\n内容二".as_bytes(), 1).unwrap();
+    assert_eq!(document.chapters[0].title, "第二章 乙");
+}
+
+#[test]
+fn test_load_custom_format_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("book.pipe");
+    fs::write(&file_path, "PIPE\n序章|很久以前\n第一章|后来").unwrap();
+    
+    let loader = FileLoader::with_registry(Arc::new(registry_with_pipe()));
+    let document = loader.load_and_parse_document(file_path.to_str().unwrap()).unwrap();
+    
+    assert_eq!(document.metadata.title, "Pipe Document");
+    assert_eq!(document.chapters.len(), 2);
+    assert_eq!(document.chapters[1].title, "第一章");
+    
+    // 默认加载器不认识该格式，按纯文本解析
+    let document = FileLoader::new().load_and_parse_document(file_path.to_str().unwrap()).unwrap();
+    assert_eq!(document.metadata.title, "Sample Document");
+}
@@ -8,6 +8,7 @@ use typesetting_engine::{ParserEngine, LayoutEngine, PageConfig};
 /// 
 /// 验证从解析到布局的整个流程是否正常工作
 #[test]
+#[allow(clippy::len_zero)]
 fn test_full_pipeline() {
     let content = "# Chapter 1\n\nThis is a test paragraph.\n\nThis is another paragraph.";
     
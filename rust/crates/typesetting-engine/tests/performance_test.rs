@@ -113,4 +113,40 @@ fn test_rendering_performance() {
     assert!(render_duration.as_secs() < 5); // 应该在5秒内完成
     
     assert!(!_rendered_data.is_empty());
-}
\ No newline at end of file
+}
+
+/// 测试并行解析大型文档的性能
+/// 
+/// 对比串行解析与并行解析5000个章节的耗时，并验证结果一致
+#[test]
+fn test_parallel_parsing_performance() {
+    let mut content = String::new();
+    for chapter in 1..=5000 {
+        content.push_str(&format!("第{}章 测试章节\n\n", chapter));
+        
+        for paragraph in 1..=10 {
+            content.push_str(&format!(
+                "这是第{}章的第{}个段落。它包含一些文本内容，用于测试并行解析的性能表现。\n\n",
+                chapter, paragraph
+            ));
+        }
+    }
+    
+    let parser = ParserEngine::new();
+    
+    let start_time = Instant::now();
+    let serial = parser.parse_txt(&content);
+    let serial_duration = start_time.elapsed();
+    
+    let start_time = Instant::now();
+    let parallel = parser.parse_txt_parallel(&content);
+    let parallel_duration = start_time.elapsed();
+    
+    println!("串行解析时间: {:?}", serial_duration);
+    println!("并行解析时间: {:?}", parallel_duration);
+    println!("加速比: {:.2}x", serial_duration.as_secs_f64() / parallel_duration.as_secs_f64());
+    assert!(parallel_duration.as_secs() < 10); // 应该在10秒内完成
+    
+    assert_eq!(parallel.chapters.len(), 5000);
+    assert_eq!(serial.chapters, parallel.chapters);
+}
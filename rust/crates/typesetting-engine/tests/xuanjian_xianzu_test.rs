@@ -7,6 +7,7 @@ use std::fs;
 
 /// 测试《玄鉴仙族》文件处理
 #[test]
+#[allow(clippy::len_zero)]
 fn test_xuanjian_xianzu_processing() {
     // 读取《玄鉴仙族》文件
     let content = fs::read_to_string("《玄鉴仙族》.txt").expect("无法读取《玄鉴仙族》文件");